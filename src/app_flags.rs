@@ -1,6 +1,43 @@
+use git2::Oid;
+
+/// Views which can be requested from a command or another view.
+#[derive(Clone, PartialEq, Eq)]
+pub enum SwitchView {
+    Repo,
+    /// The log with the given commit selected.
+    Log(Oid),
+    Status,
+    Commit,
+    Branches,
+    /// The rebase planner for the commits after the given one.
+    Rebase(Oid),
+    /// The conflicted files of a stopped rebase, cherry-pick, revert or merge.
+    Conflicts,
+    /// Reverting the given commits in that order, keeping the side of the given parent of
+    /// merges.
+    Revert(Vec<Oid>, Option<u32>),
+    Stashes,
+    /// The tags, new ones going on the commit selected in the log.
+    Tags,
+    Remotes,
+    /// The lines of a file with the commit which last changed each, blamed at the given
+    /// commit.
+    Blame(String, Oid),
+}
+
 #[derive(Default)]
 pub struct AppLoopFlag {
     terminate_execution: bool,
+    switch_view: Option<SwitchView>,
+    /// Command entered by the user, dispatched by the application loop.
+    command: Option<String>,
+    /// The command is run again after the user accepted the confirmation it asked for.
+    confirmed: bool,
+    /// The view wants the word in front of the cursor of its command input completed.
+    complete_input: bool,
+    /// Shown in the log once the requested view is the log, like the summary of what the
+    /// view did before leaving.
+    message: Option<String>,
 }
 
 impl AppLoopFlag {
@@ -12,21 +49,73 @@ impl AppLoopFlag {
         self.terminate_execution
     }
 
+    pub fn requested_view(&self) -> Option<SwitchView> {
+        self.switch_view.clone()
+    }
+
+    /// Takes the command the view asked the application loop to run.
+    pub fn take_command(&mut self) -> Option<String> {
+        self.command.take()
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed
+    }
+
+    pub fn should_complete_input(&self) -> bool {
+        self.complete_input
+    }
+
+    pub fn take_message(&mut self) -> Option<String> {
+        self.message.take()
+    }
+
     pub fn new(terminate: bool) -> Self {
         Self {
             terminate_execution: terminate,
+            ..Default::default()
         }
     }
 
     pub fn terminate() -> Self {
+        Self::new(true)
+    }
+
+    pub fn continue_() -> Self {
+        Self::new(false)
+    }
+
+    pub fn switch_view(view: SwitchView) -> Self {
         Self {
-            terminate_execution: true,
+            switch_view: Some(view),
+            ..Default::default()
         }
     }
 
-    pub fn continue_() -> Self {
+    pub fn with_message(mut self, message: String) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn run_command(command: String) -> Self {
+        Self {
+            command: Some(command),
+            ..Default::default()
+        }
+    }
+
+    pub fn run_confirmed_command(command: String) -> Self {
+        Self {
+            command: Some(command),
+            confirmed: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn complete_input() -> Self {
         Self {
-            terminate_execution: false,
+            complete_input: true,
+            ..Default::default()
         }
     }
 }
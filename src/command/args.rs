@@ -0,0 +1,334 @@
+/// A single option given to a command, `--name`, `--name=value` or one letter of `-abc`.
+pub struct Flag {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl Flag {
+    fn display_name(&self) -> String {
+        match self.name.chars().count() {
+            1 => format!("-{}", self.name),
+            _ => format!("--{}", self.name),
+        }
+    }
+}
+
+/// Arguments following a command name, split into positional arguments and options. Options
+/// end at the first `--`, everything after it is positional.
+pub struct Args {
+    words: Vec<String>,
+    positional: Vec<String>,
+    flags: Vec<Flag>,
+}
+
+impl Args {
+    pub fn parse(words: Vec<String>) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut flags = Vec::new();
+        let mut options_ended = false;
+
+        for word in &words {
+            if options_ended || word == "-" || !word.starts_with('-') {
+                positional.push(word.to_owned());
+            } else if word == "--" {
+                options_ended = true;
+            } else if let Some(long) = word.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_owned())),
+                    None => (long, None),
+                };
+                if name.is_empty() {
+                    return Err(format!("Option '{word}' is missing a name"));
+                }
+                flags.push(Flag {
+                    name: name.to_owned(),
+                    value,
+                });
+            } else {
+                let short = &word[1..];
+                if short.contains('=') {
+                    return Err(format!(
+                        "Short options cannot take a value with '=': '{word}'"
+                    ));
+                }
+                flags.extend(short.chars().map(|c| Flag {
+                    name: c.to_string(),
+                    value: None,
+                }));
+            }
+        }
+
+        Ok(Args {
+            words,
+            positional,
+            flags,
+        })
+    }
+
+    /// The arguments as they were given, before options were picked out.
+    pub fn raw(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(String::as_str)
+    }
+
+    /// Whether any of `names`, usually a short and a long spelling, was given.
+    pub fn has_flag(&self, names: &[&str]) -> bool {
+        self.flags
+            .iter()
+            .any(|flag| names.contains(&flag.name.as_str()))
+    }
+
+    /// Value of the last of `names` given as `--name=value`.
+    pub fn flag_value(&self, names: &[&str]) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|flag| names.contains(&flag.name.as_str()))
+            .and_then(|flag| flag.value.as_deref())
+    }
+
+    /// Fails on the first option not in `allowed`, so typos are reported instead of ignored.
+    pub fn check_flags(&self, allowed: &[&str]) -> Result<(), String> {
+        match self
+            .flags
+            .iter()
+            .find(|flag| !allowed.contains(&flag.name.as_str()))
+        {
+            Some(flag) => Err(format!("Unknown option '{}'", flag.display_name())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Quotes an argument for a POSIX shell when it contains anything but plain characters.
+pub fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_./@:=+,%^~".contains(c));
+
+    if plain {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Splits a command line into words, honouring single quotes, double quotes and backslash
+/// escapes. A `&&` outside of quotes is kept as its own word.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_owned()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_owned()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_owned()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err("Trailing backslash".to_owned()),
+            },
+            '&' if word.is_none() && chars.peek() == Some(&'&') => {
+                chars.next();
+                words.push("&&".to_owned());
+            }
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(word) = word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        tokenize(line).unwrap()
+    }
+
+    fn parse(line: &str) -> Result<Args, String> {
+        Args::parse(words(line))
+    }
+
+    #[test]
+    fn splits_on_any_whitespace() {
+        assert_eq!(words("  commit \t -m   fix  "), ["commit", "-m", "fix"]);
+        assert!(words("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_text_together() {
+        assert_eq!(
+            words(r#"commit "fix the bug" 'it''s done'"#),
+            ["commit", "fix the bug", "its done"]
+        );
+        assert_eq!(
+            words(r#"tag --message="v 1" ''"#),
+            ["tag", "--message=v 1", ""]
+        );
+        assert_eq!(
+            words(r#"echo '$HOME \n' "a\"b\\c\d""#),
+            ["echo", "$HOME \\n", r#"a"b\c\d"#]
+        );
+    }
+
+    #[test]
+    fn escapes_single_characters() {
+        assert_eq!(
+            words(r"add my\ file.txt \'quoted\'"),
+            ["add", "my file.txt", "'quoted'"]
+        );
+    }
+
+    #[test]
+    fn splits_off_and_operators() {
+        assert_eq!(
+            words("git add -- a && git commit -m 'a && b'"),
+            ["git", "add", "--", "a", "&&", "git", "commit", "-m", "a && b"]
+        );
+    }
+
+    #[test]
+    fn reports_unfinished_quotes_and_escapes() {
+        assert_eq!(
+            tokenize("commit 'fix"),
+            Err("Unterminated single quote".to_owned())
+        );
+        assert_eq!(
+            tokenize(r#"commit "fix"#),
+            Err("Unterminated double quote".to_owned())
+        );
+        assert_eq!(
+            tokenize(r#"commit "fix\"#),
+            Err("Unterminated double quote".to_owned())
+        );
+        assert_eq!(
+            tokenize(r"commit fix\"),
+            Err("Trailing backslash".to_owned())
+        );
+    }
+
+    #[test]
+    fn quote_round_trips_through_tokenize() {
+        for arg in [
+            "plain",
+            "with space",
+            "it's",
+            "",
+            "a\"b",
+            "$HOME",
+            "&&",
+            "~/x@{1}",
+        ] {
+            assert_eq!(words(&quote(arg)), [arg]);
+        }
+        assert_eq!(quote("origin/main"), "origin/main");
+    }
+
+    #[test]
+    fn parses_long_options_with_values() {
+        let args = parse("--message=first --ff --message=second name").unwrap();
+
+        assert!(args.has_flag(&["ff"]));
+        assert_eq!(args.flag_value(&["message"]), Some("second"));
+        assert_eq!(args.flag_value(&["ff"]), None);
+        assert_eq!(args.positional(), ["name"]);
+    }
+
+    #[test]
+    fn splits_grouped_short_options() {
+        let args = parse("push -uk message").unwrap();
+
+        assert!(args.has_flag(&["u", "include-untracked"]));
+        assert!(args.has_flag(&["k"]));
+        assert!(!args.has_flag(&["f"]));
+        assert_eq!(args.positional(), ["push", "message"]);
+    }
+
+    #[test]
+    fn double_dash_ends_the_options() {
+        let args = parse("-f -- -n - --all").unwrap();
+
+        assert!(args.has_flag(&["f"]));
+        assert!(!args.has_flag(&["n", "all"]));
+        assert_eq!(args.positional(), ["-n", "-", "--all"]);
+        assert_eq!(args.raw(), ["-f", "--", "-n", "-", "--all"]);
+    }
+
+    #[test]
+    fn a_lone_dash_is_positional() {
+        let args = parse("checkout -").unwrap();
+        assert_eq!(args.get(1), Some("-"));
+    }
+
+    #[test]
+    fn reports_malformed_options() {
+        assert_eq!(
+            parse("--=value").err(),
+            Some("Option '--=value' is missing a name".to_owned())
+        );
+        assert_eq!(
+            parse("-m=fix").err(),
+            Some("Short options cannot take a value with '=': '-m=fix'".to_owned())
+        );
+    }
+
+    #[test]
+    fn reports_unknown_options() {
+        let args = parse("-f --force --all").unwrap();
+
+        assert_eq!(
+            args.check_flags(&["f", "force"]),
+            Err("Unknown option '--all'".to_owned())
+        );
+        assert_eq!(
+            args.check_flags(&["all", "force"]),
+            Err("Unknown option '-f'".to_owned())
+        );
+        assert_eq!(args.check_flags(&["f", "force", "all"]), Ok(()));
+    }
+}
@@ -1,10 +1,42 @@
-use std::{collections::HashMap, str::Split};
+use std::collections::HashMap;
 
-use super::construct_handler::handler_create_callbacks;
+use crate::{
+    config::{
+        macros::{Macro, SavedMacros},
+        Config,
+    },
+    git::action::GitAction,
+};
+
+use super::{
+    args::{self, Args},
+    completion::CompletionKind,
+    construct_handler::{handler_create_callbacks, run_actions},
+    context::{CommandContext, CommandResult},
+};
+
+/// How deep macros may call other macros before giving up, this stops macros which call
+/// themselves.
+const MAX_MACRO_DEPTH: usize = 16;
+
+/// Handler called with the context of the view the command was entered in and the parsed
+/// arguments following the command name.
+pub type CommandCallback = fn(&mut CommandContext, &Args) -> CommandResult;
+
+/// Translates a command into the git actions it performs, so they can be previewed before the
+/// command runs. `None` for forms of the command which don't change the repository, like
+/// opening a view, which are left to the command's handler.
+pub type CommandTranslation = fn(&CommandContext, &Args) -> Option<Result<Vec<GitAction>, String>>;
 
 pub struct CommandHandler {
-    /// Handler returns a boolean, false meaning to quit the application, true meaning to continue execution
-    pub(in crate::command) handlers: HashMap<String, fn(Split<char>) -> bool>,
+    pub(in crate::command) handlers: HashMap<String, CommandCallback>,
+    pub(in crate::command) translations: HashMap<String, CommandTranslation>,
+    /// What the positional arguments of a command are, the last kind repeats for any further
+    /// arguments.
+    pub(in crate::command) completions: HashMap<String, Vec<CompletionKind>>,
+    pub(in crate::command) macros: SavedMacros,
+    /// Name of the macro being recorded and the commands entered so far.
+    pub(in crate::command) recording: Option<(String, Vec<String>)>,
 }
 
 impl Default for CommandHandler {
@@ -14,19 +46,266 @@ impl Default for CommandHandler {
 }
 
 impl CommandHandler {
-    pub fn add_handler(&mut self, command: &str, callback: fn(Split<char>) -> bool) {
+    pub fn add_handler(&mut self, command: &str, callback: CommandCallback) {
         self.handlers.insert(command.to_owned(), callback);
     }
 
-    pub fn call_handler(&self, command: &str) -> Option<bool> {
-        let mut split = command.split(' ');
+    /// Runs the actions `command` translates to in place of its handler.
+    pub fn add_translation(&mut self, command: &str, translation: CommandTranslation) {
+        self.translations.insert(command.to_owned(), translation);
+    }
+
+    pub fn add_completion(&mut self, command: &str, arguments: &[CompletionKind]) {
+        self.completions
+            .insert(command.to_owned(), arguments.to_vec());
+    }
+
+    /// Kind of the positional argument at `position` of `command`.
+    pub fn argument_kind(&self, command: &str, position: usize) -> Option<CompletionKind> {
+        let arguments = self.completions.get(command)?;
+        arguments
+            .get(position)
+            .or_else(|| arguments.last())
+            .copied()
+    }
+
+    /// Names of every command and saved macro.
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.handlers.keys().cloned().collect();
+        names.extend(
+            self.translations
+                .keys()
+                .filter(|name| !self.handlers.contains_key(*name))
+                .cloned(),
+        );
+        names.push("macro".to_owned());
+        names.extend(self.macro_names());
+        names
+    }
+
+    pub fn macro_names(&self) -> Vec<String> {
+        self.macros.macros.keys().cloned().collect()
+    }
+
+    /// Runs `command`, returns `None` if there is no command or macro of that name.
+    pub fn call_handler(
+        &mut self,
+        ctx: &mut CommandContext,
+        command: &str,
+    ) -> Option<CommandResult> {
+        let result = self.run_command(ctx, command, 0);
+
+        // A confirmed command repeats one which was recorded already.
+        let failed = matches!(result, None | Some(CommandResult::Error(_)));
+        if !failed && !ctx.confirmed && !command.starts_with("macro ") {
+            if let Some((_, commands)) = self.recording.as_mut() {
+                commands.push(command.to_owned());
+            }
+        }
+
+        result
+    }
+
+    /// The git actions `command` would perform, `None` when it performs none or isn't a
+    /// command with a translation.
+    pub fn translate(
+        &self,
+        ctx: &CommandContext,
+        command: &str,
+    ) -> Option<Result<Vec<GitAction>, String>> {
+        let mut words = args::tokenize(command).ok()?.into_iter();
+        let translation = self.translations.get(&words.next()?)?;
+
+        match Args::parse(words.collect()) {
+            Ok(args) => translation(ctx, &args),
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// Name of the macro currently being recorded.
+    pub fn recording(&self) -> Option<&str> {
+        self.recording.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    fn run_command(
+        &mut self,
+        ctx: &mut CommandContext,
+        command: &str,
+        depth: usize,
+    ) -> Option<CommandResult> {
+        let mut words = match args::tokenize(command) {
+            Ok(words) => words.into_iter(),
+            Err(err) => return Some(CommandResult::Error(format!("{err} in \"{command}\""))),
+        };
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Some(CommandResult::Continue),
+        };
+        let args = match Args::parse(words.collect()) {
+            Ok(args) => args,
+            Err(err) => return Some(CommandResult::Error(err)),
+        };
+
+        if name == "macro" {
+            return Some(self.macro_command(ctx, &args, depth));
+        }
+
+        if let Some(translation) = self.translations.get(&name) {
+            match translation(ctx, &args) {
+                Some(Ok(actions)) => return Some(run_actions(ctx, &actions)),
+                Some(Err(err)) => return Some(CommandResult::Error(err)),
+                None => {}
+            }
+        }
+
+        if let Some(callback) = self.handlers.get(&name) {
+            return Some(callback(ctx, &args));
+        }
+
+        let saved_macro = self.macros.macros.get(&name)?.clone();
+        Some(self.run_macro(ctx, &name, &saved_macro, args.raw(), depth))
+    }
+
+    fn run_macro(
+        &mut self,
+        ctx: &mut CommandContext,
+        name: &str,
+        saved_macro: &Macro,
+        args: &[String],
+        depth: usize,
+    ) -> CommandResult {
+        if depth >= MAX_MACRO_DEPTH {
+            return CommandResult::Error(format!("Macro '{name}' nests too deeply"));
+        }
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let commands = match saved_macro.expand(&args) {
+            Ok(commands) => commands,
+            Err(err) => return CommandResult::Error(err),
+        };
+
+        // Only the last view switch of a macro has any effect.
+        let mut switch_view = None;
+        let mut refresh = false;
+        let mut message = None;
+
+        for command in commands {
+            match self.run_command(ctx, &command, depth + 1) {
+                Some(CommandResult::Quit) => return CommandResult::Quit,
+                Some(CommandResult::SwitchView(view)) => switch_view = Some(view),
+                Some(CommandResult::Refresh) => refresh = true,
+                Some(CommandResult::RefreshWithMessage(msg)) => {
+                    refresh = true;
+                    message = Some(msg);
+                }
+                Some(CommandResult::Message(msg)) => message = Some(msg),
+                Some(CommandResult::Continue) => {}
+                Some(CommandResult::Error(err)) => {
+                    return CommandResult::Error(format!("Macro '{name}' failed: {err}"))
+                }
+                None => {
+                    return CommandResult::Error(format!(
+                        "Unknown command in macro '{name}': {command}"
+                    ))
+                }
+            }
+        }
+
+        match (switch_view, refresh, message) {
+            (Some(view), _, _) => CommandResult::SwitchView(view),
+            (None, true, Some(message)) => CommandResult::RefreshWithMessage(message),
+            (None, true, None) => CommandResult::Refresh,
+            (None, false, Some(message)) => CommandResult::Message(message),
+            (None, false, None) => CommandResult::Continue,
+        }
+    }
+
+    fn macro_command(
+        &mut self,
+        ctx: &mut CommandContext,
+        args: &Args,
+        depth: usize,
+    ) -> CommandResult {
+        let subcommand = args.get(0).unwrap_or_default();
+        let name = args.get(1).unwrap_or_default();
+
+        // Options after `macro run <name>` belong to the macro.
+        if subcommand != "run" {
+            if let Err(err) = args.check_flags(&[]) {
+                return CommandResult::Error(err);
+            }
+        }
+
+        match (subcommand, name) {
+            ("record", "") | ("run", "") | ("delete", "") => {
+                CommandResult::Error(format!("Usage: macro {subcommand} <name>"))
+            }
+            ("record", name) => {
+                self.recording = Some((name.to_owned(), Vec::new()));
+                CommandResult::Message(format!(
+                    "Recording macro '{name}', finish with 'macro stop'"
+                ))
+            }
+            ("stop", _) => match self.recording.take() {
+                Some((name, commands)) => {
+                    let count = commands.len();
+                    self.macros.macros.insert(
+                        name.to_owned(),
+                        Macro {
+                            description: String::new(),
+                            commands,
+                        },
+                    );
+                    self.save_macros(format!("Saved macro '{name}' with {count} command(s)"))
+                }
+                None => CommandResult::Error("No macro is being recorded".to_owned()),
+            },
+            ("cancel", _) => match self.recording.take() {
+                Some((name, _)) => {
+                    CommandResult::Message(format!("Stopped recording macro '{name}'"))
+                }
+                None => CommandResult::Error("No macro is being recorded".to_owned()),
+            },
+            ("run", name) => match self.macros.macros.get(name).cloned() {
+                Some(saved_macro) => {
+                    let macro_args = args.raw().get(2..).unwrap_or_default();
+                    self.run_macro(ctx, name, &saved_macro, macro_args, depth)
+                }
+                None => CommandResult::Error(format!("No macro named '{name}'")),
+            },
+            ("delete", name) => match self.macros.macros.remove(name) {
+                Some(_) => self.save_macros(format!("Deleted macro '{name}'")),
+                None => CommandResult::Error(format!("No macro named '{name}'")),
+            },
+            ("list", _) => {
+                let lines = self
+                    .macros
+                    .macros
+                    .iter()
+                    .map(
+                        |(name, saved_macro)| match saved_macro.description.as_str() {
+                            "" => format!("{name}: {}", saved_macro.commands.join("; ")),
+                            description => format!("{name}: {description}"),
+                        },
+                    )
+                    .collect::<Vec<String>>();
+
+                if lines.is_empty() {
+                    return CommandResult::Message("No macros saved".to_owned());
+                }
+                ctx.ui.show_popup("Macros", lines);
+                CommandResult::Continue
+            }
+            _ => CommandResult::Error(
+                "Usage: macro <record|stop|cancel|run|delete|list> [name] [args...]".to_owned(),
+            ),
+        }
+    }
 
-        self.handlers
-            .get(
-                split.next().expect(
-                    "call_handler() split contains no data in the first entry when it should",
-                ),
-            )
-            .map(|callback| callback(split))
+    fn save_macros(&self, success: String) -> CommandResult {
+        match self.macros.save_config() {
+            Ok(()) => CommandResult::Message(success),
+            Err(err) => CommandResult::Error(format!("Failed to save macros: {err}")),
+        }
     }
 }
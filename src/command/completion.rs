@@ -0,0 +1,195 @@
+use git2::{BranchType, Repository};
+use tui_input::Input;
+
+use crate::git::names;
+
+use super::{args, command_handler::CommandHandler};
+
+const MACRO_SUBCOMMANDS: [&str; 6] = ["cancel", "delete", "list", "record", "run", "stop"];
+
+/// What an argument of a command names, so it can be completed.
+#[derive(Clone, Copy)]
+pub enum CompletionKind {
+    Branch,
+    /// A path of a file in the index.
+    Path,
+    /// Anything resolving to a commit: branches, tags, remotes and special refs like `HEAD`.
+    Revision,
+    Remote,
+}
+
+/// Candidates for the word in front of the cursor.
+pub struct Completion {
+    /// Char index in the input where the word being completed starts.
+    pub start: usize,
+    /// Part of the word kept in front of every candidate, like `main..` in a range.
+    pub prefix: String,
+    /// What was typed of the candidate so far.
+    pub typed: String,
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// Longest text shared by every candidate.
+    pub fn common_prefix(&self) -> &str {
+        let first = match self.candidates.first() {
+            Some(first) => first.as_str(),
+            None => return "",
+        };
+
+        let mut common = first;
+        for candidate in &self.candidates[1..] {
+            while !candidate.starts_with(common) {
+                let last = common.char_indices().last().map(|(i, _)| i);
+                common = &common[..last.unwrap_or(0)];
+            }
+        }
+        common
+    }
+
+    /// Replaces the word being completed with `candidate`. A finished word is followed by a
+    /// space, unless it is a directory which may be completed further.
+    pub fn apply(&self, input: &Input, candidate: &str, finished: bool) -> Input {
+        let chars: Vec<char> = input.value().chars().collect();
+        let cursor = input.cursor().min(chars.len());
+
+        let mut replacement = args::quote(&format!("{}{candidate}", self.prefix));
+        if finished && !candidate.ends_with('/') {
+            replacement.push(' ');
+        }
+
+        let mut value: String = chars[..self.start.min(cursor)].iter().collect();
+        value.push_str(&replacement);
+        let new_cursor = value.chars().count();
+        value.extend(&chars[cursor..]);
+
+        Input::new(value).with_cursor(new_cursor)
+    }
+}
+
+/// Completes the last word of `line`, the input up to the cursor. Command names are completed
+/// first, then the arguments registered for the command with
+/// [`CommandHandler::add_completion`].
+pub fn complete(
+    handler: &CommandHandler,
+    repo: Option<&Repository>,
+    line: &str,
+) -> Option<Completion> {
+    let (start, open_quote) = word_start(line);
+    let words = args::tokenize(&line[..start]).ok()?;
+
+    let mut word = line[start..].to_owned();
+    if let Some(quote) = open_quote {
+        word.push(quote);
+    }
+    let current = args::tokenize(&word).ok()?.pop().unwrap_or_default();
+
+    if current.starts_with('-') {
+        return None;
+    }
+
+    let (prefix, typed) = match current.rfind("..") {
+        Some(i) => {
+            let end = if current[i + 2..].starts_with('.') {
+                i + 3
+            } else {
+                i + 2
+            };
+            (current[..end].to_owned(), current[end..].to_owned())
+        }
+        None => (String::new(), current),
+    };
+
+    let mut candidates = match words.split_first() {
+        None => handler.command_names(),
+        Some((name, rest)) => {
+            let position = rest.iter().filter(|word| !word.starts_with('-')).count();
+            let kind = handler.argument_kind(name, position);
+
+            // Ranges only make sense between revisions.
+            if !prefix.is_empty() && !matches!(kind, Some(CompletionKind::Revision)) {
+                return None;
+            }
+
+            match (name.as_str(), kind, repo) {
+                ("macro", _, _) => match (position, rest.first().map(String::as_str)) {
+                    (0, _) => MACRO_SUBCOMMANDS.map(str::to_owned).to_vec(),
+                    (1, Some("run" | "delete")) => handler.macro_names(),
+                    _ => Vec::new(),
+                },
+                (_, Some(CompletionKind::Revision), Some(repo)) => revision_names(repo),
+                (_, Some(CompletionKind::Branch), Some(repo)) => {
+                    let mut branches = names::branch_names(repo, BranchType::Local);
+                    branches.extend(names::branch_names(repo, BranchType::Remote));
+                    branches
+                }
+                (_, Some(CompletionKind::Remote), Some(repo)) => names::remote_names(repo),
+                (_, Some(CompletionKind::Path), Some(repo)) => {
+                    path_components(names::index_paths(repo), &typed)
+                }
+                _ => Vec::new(),
+            }
+        }
+    };
+
+    candidates.retain(|candidate| candidate.starts_with(&typed));
+    candidates.sort();
+    candidates.dedup();
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    Some(Completion {
+        start: line[..start].chars().count(),
+        prefix,
+        typed,
+        candidates,
+    })
+}
+
+fn revision_names(repo: &Repository) -> Vec<String> {
+    let mut revisions = names::special_revisions(repo);
+    revisions.extend(names::branch_names(repo, BranchType::Local));
+    revisions.extend(names::branch_names(repo, BranchType::Remote));
+    revisions.extend(names::tag_names(repo));
+    revisions.extend(names::remote_names(repo));
+    revisions
+}
+
+/// Cuts paths off after the directory following `typed`, so a directory is completed before
+/// the files inside it.
+fn path_components(paths: Vec<String>, typed: &str) -> Vec<String> {
+    paths
+        .into_iter()
+        .filter(|path| path.starts_with(typed))
+        .map(|path| match path[typed.len()..].find('/') {
+            Some(i) => path[..typed.len() + i + 1].to_owned(),
+            None => path,
+        })
+        .collect()
+}
+
+/// Byte index where the last word of `line` starts, and the quote it leaves open if any.
+fn word_start(line: &str) -> (usize, Option<char>) {
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match (quote, c) {
+            (None, '\\') | (Some('"'), '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, c) if c.is_whitespace() => start = i + c.len_utf8(),
+            _ => {}
+        }
+    }
+
+    (start, quote)
+}
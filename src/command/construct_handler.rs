@@ -1,13 +1,738 @@
 use std::collections::HashMap;
 
-use super::command_handler::CommandHandler;
+use crate::{
+    app_flags::SwitchView,
+    config::{macros::SavedMacros, Config},
+    git::{
+        action::{self, GitAction, SequenceCommand},
+        blame,
+        branch::{self, BranchKind},
+        merge::{self, MergeMode},
+        rebase::{self, RebaseState, SequenceKind},
+        remote,
+        reset::{self, ResetMode},
+        stash,
+    },
+};
+
+use super::{
+    args::Args,
+    command_handler::CommandHandler,
+    completion::CompletionKind,
+    context::{CommandContext, CommandResult},
+};
+
+/// Commits or files listed in the reset confirmation at most, so the popup fits the screen.
+const MAX_LISTED: usize = 10;
 
 pub fn handler_create_callbacks() -> CommandHandler {
     let mut handler = CommandHandler {
         handlers: HashMap::default(),
+        translations: HashMap::default(),
+        completions: HashMap::default(),
+        macros: SavedMacros::load_or_create_config(),
+        recording: None,
     };
 
-    handler.add_handler("quit", |_h| false);
+    handler.add_handler("quit", |_ctx, args| match no_args(args) {
+        Ok(()) => CommandResult::Quit,
+        Err(err) => err,
+    });
+    handler.add_handler("refresh", |_ctx, args| match no_args(args) {
+        Ok(()) => CommandResult::Refresh,
+        Err(err) => err,
+    });
+    handler.add_handler("status", |ctx, args| {
+        switch_to(ctx, args, SwitchView::Status)
+    });
+    handler.add_handler("commit", |ctx, args| {
+        switch_to(ctx, args, SwitchView::Commit)
+    });
+    handler.add_handler("branch", |ctx, args| {
+        switch_to(ctx, args, SwitchView::Branches)
+    });
+    handler.add_handler("conflicts", |ctx, args| {
+        switch_to(ctx, args, SwitchView::Conflicts)
+    });
+
+    handler.add_translation("git", |_ctx, args| {
+        let mut words = vec!["git".to_owned()];
+        words.extend(args.raw().iter().cloned());
+        Some(action::parse_words(&words))
+    });
+    handler.add_translation("checkout", |ctx, args| {
+        Some(translate(ctx, args, &[], |repo| {
+            let name = match args.positional() {
+                [name] => name,
+                _ => return Err(usage("checkout <branch>")),
+            };
+            let kind = branch::branch_kind(repo, name)?;
+            Ok(vec![GitAction::Checkout {
+                branch: name.to_owned(),
+                remote: kind == BranchKind::Remote,
+            }])
+        }))
+    });
+    handler.add_translation("add", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| match args.positional() {
+            [] => Err(usage("add <path>...")),
+            paths => Ok(vec![GitAction::Add {
+                paths: paths.to_vec(),
+            }]),
+        }))
+    });
+    handler.add_translation("unstage", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| match args.positional() {
+            [] => Err(usage("unstage <path>...")),
+            paths => Ok(vec![GitAction::Unstage {
+                paths: paths.to_vec(),
+            }]),
+        }))
+    });
+    handler.add_handler("goto", |ctx, args| {
+        let revision = match args.positional() {
+            [revision] => revision,
+            _ => return CommandResult::Error("Usage: goto <revision>".to_owned()),
+        };
+        let repo = match ctx.require_repo() {
+            Ok(repo) => repo,
+            Err(err) => return err,
+        };
+
+        match resolve_commit(&repo.git2_repository, revision) {
+            Ok(id) => {
+                ctx.view.selected_commit = Some(id);
+                CommandResult::Continue
+            }
+            Err(err) => CommandResult::Error(err.message().to_owned()),
+        }
+    });
+    handler.add_translation("rebase", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| {
+            let command = args.get(0).and_then(SequenceCommand::from_name);
+            let action = match (args.positional(), command, ctx.view.selected_commit) {
+                ([_], Some(command), _) => GitAction::Sequence {
+                    kind: SequenceKind::Rebase,
+                    command,
+                },
+                ([revision], None, _) => GitAction::Rebase {
+                    onto: revision.to_owned(),
+                },
+                ([], _, Some(id)) => GitAction::Rebase {
+                    onto: id.to_string(),
+                },
+                ([], _, None) => {
+                    return Err(git2::Error::from_str(
+                        "Select the commit to rebase onto, or give a revision",
+                    ))
+                }
+                _ => return Err(usage("rebase [<revision> | continue | skip | abort]")),
+            };
+            Ok(vec![action])
+        }))
+    });
+    handler.add_translation("cherry-pick", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| {
+            let command = args.get(0).and_then(SequenceCommand::from_name);
+            let revisions = match (args.positional(), command) {
+                ([_], Some(command)) => {
+                    return Ok(vec![GitAction::Sequence {
+                        kind: SequenceKind::CherryPick,
+                        command,
+                    }])
+                }
+                // The log lists the newest commit first, they are picked oldest first.
+                ([], _) if !ctx.view.marked_commits.is_empty() => ctx
+                    .view
+                    .marked_commits
+                    .iter()
+                    .rev()
+                    .map(|id| id.to_string())
+                    .collect(),
+                ([], _) => match ctx.view.selected_commit {
+                    Some(id) => vec![id.to_string()],
+                    None => {
+                        return Err(usage(
+                            "cherry-pick [<revision>... | continue | skip | abort]",
+                        ))
+                    }
+                },
+                (revisions, _) => revisions.to_vec(),
+            };
+            Ok(vec![GitAction::CherryPick { revisions }])
+        }))
+    });
+    handler.add_translation("merge", |ctx, args| {
+        Some(translate(ctx, args, &["ff", "no-ff", "ff-only"], |_repo| {
+            let mode = merge_mode(args)?;
+            let action = match (args.positional(), mode) {
+                ([subcommand], None) if subcommand == "continue" => GitAction::MergeContinue,
+                ([subcommand], None) if subcommand == "abort" => GitAction::MergeAbort,
+                ([revision], mode) => GitAction::Merge {
+                    revision: revision.to_owned(),
+                    mode,
+                },
+                _ => {
+                    return Err(usage(
+                        "merge [--ff | --no-ff | --ff-only] <branch> | merge continue \
+                         | merge abort",
+                    ))
+                }
+            };
+            Ok(vec![action])
+        }))
+    });
+    handler.add_handler("blame", |ctx, args| {
+        if let Err(err) = args.check_flags(&[]) {
+            return CommandResult::Error(err);
+        }
+        let repo = match ctx.require_repo() {
+            Ok(repo) => &repo.git2_repository,
+            Err(err) => return err,
+        };
+
+        let (path, at) = match args.positional() {
+            [path] => (path, resolve_commit(repo, "HEAD")),
+            [path, revision] => (path, resolve_commit(repo, revision)),
+            _ => return CommandResult::Error("Usage: blame <path> [<revision>]".to_owned()),
+        };
+
+        // Checked here so mistakes are reported in the log rather than in an empty view.
+        match at.and_then(|at| blame::check_path(repo, path, at).map(|_| at)) {
+            Ok(at) => CommandResult::SwitchView(SwitchView::Blame(path.to_owned(), at)),
+            Err(err) => CommandResult::Error(err.message().to_owned()),
+        }
+    });
+    handler.add_translation("reset", |ctx, args| {
+        Some(translate(ctx, args, &["soft", "mixed", "hard"], |repo| {
+            let modes: Vec<ResetMode> = ["soft", "mixed", "hard"]
+                .into_iter()
+                .filter(|name| args.has_flag(&[name]))
+                .filter_map(ResetMode::from_name)
+                .collect();
+            let mode = match modes.as_slice() {
+                [] => None,
+                [mode] => Some(*mode),
+                _ => {
+                    return Err(git2::Error::from_str(
+                        "Give only one of --soft, --mixed and --hard",
+                    ))
+                }
+            };
+
+            let (target, mode) = match (args.positional(), ctx.view.selected_commit) {
+                ([undo], _) if undo == "undo" => {
+                    let (target, backup_mode) = reset::backup(repo)?;
+                    (target.to_string(), mode.unwrap_or(backup_mode))
+                }
+                ([revision], _) => (revision.to_owned(), mode.unwrap_or(ResetMode::Mixed)),
+                ([], Some(id)) => (id.to_string(), mode.unwrap_or(ResetMode::Mixed)),
+                _ => {
+                    return Err(usage(
+                        "reset [--soft | --mixed | --hard] [<revision> | undo]",
+                    ))
+                }
+            };
+            Ok(vec![GitAction::Reset { mode, target }])
+        }))
+    });
+    handler.add_handler("stash", |ctx, args| {
+        switch_to(ctx, args, SwitchView::Stashes)
+    });
+    handler.add_translation("stash", |ctx, args| {
+        // Without arguments the stash manager opens.
+        if args.raw().is_empty() {
+            return None;
+        }
+        let allowed: &[&str] = match args.get(0) {
+            Some("push") => &["u", "include-untracked", "k", "keep-index"],
+            _ => &[],
+        };
+
+        Some(translate(ctx, args, allowed, |_repo| {
+            let (subcommand, rest) = match args.positional() {
+                [] => ("", &[][..]),
+                [subcommand, rest @ ..] => (subcommand.as_str(), rest),
+            };
+            let action =
+                match (subcommand, rest) {
+                    ("push", message) => GitAction::StashPush {
+                        message: (!message.is_empty()).then(|| message.join(" ")),
+                        include_untracked: args.has_flag(&["u", "include-untracked"]),
+                        keep_index: args.has_flag(&["k", "keep-index"]),
+                    },
+                    ("apply", [] | [_]) => GitAction::StashApply {
+                        index: stash_index(rest)?,
+                        pop: false,
+                    },
+                    ("pop", [] | [_]) => GitAction::StashApply {
+                        index: stash_index(rest)?,
+                        pop: true,
+                    },
+                    ("drop", [] | [_]) => GitAction::StashDrop {
+                        index: stash_index(rest)?,
+                    },
+                    ("branch", [name, given @ ..]) if given.len() <= 1 => GitAction::StashBranch {
+                        name: name.to_owned(),
+                        index: stash_index(given)?,
+                    },
+                    _ => return Err(usage(
+                        "stash [push [-u] [-k] [<message>] | apply [<stash>] | pop [<stash>] | \
+                         drop [<stash>] | branch <name> [<stash>]]",
+                    )),
+                };
+            Ok(vec![action])
+        }))
+    });
+    handler.add_translation("revert", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| {
+            let command = args.get(0).and_then(SequenceCommand::from_name);
+            let revisions = match (args.positional(), command) {
+                ([_], Some(command)) => {
+                    return Ok(vec![GitAction::Sequence {
+                        kind: SequenceKind::Revert,
+                        command,
+                    }])
+                }
+                // Reverted newest first, in the order of the log.
+                ([], _) if !ctx.view.marked_commits.is_empty() => ctx
+                    .view
+                    .marked_commits
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                ([], _) if ctx.view.selected_commit.is_some() => ctx
+                    .view
+                    .selected_commit
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                _ => {
+                    return Err(usage(
+                        "revert [continue | skip | abort], reverting the marked or selected \
+                         commits",
+                    ))
+                }
+            };
+            Ok(vec![GitAction::Revert {
+                revisions,
+                mainline: None,
+                edit: true,
+            }])
+        }))
+    });
+
+    handler.add_handler("tag", |ctx, args| switch_to(ctx, args, SwitchView::Tags));
+    handler.add_translation("tag", |ctx, args| {
+        // Without arguments the tags view opens.
+        if args.raw().is_empty() {
+            return None;
+        }
+
+        Some(translate(ctx, args, &["d", "delete", "message"], |_repo| {
+            let message = args.flag_value(&["message"]).map(str::to_owned);
+            let action = match (args.has_flag(&["d", "delete"]), args.positional()) {
+                (true, [name]) if message.is_none() => GitAction::DeleteTag {
+                    name: name.to_owned(),
+                },
+                (false, [name]) => match ctx.view.selected_commit {
+                    Some(id) => GitAction::CreateTag {
+                        name: name.to_owned(),
+                        target: id.to_string(),
+                        message,
+                    },
+                    None => return Err(git2::Error::from_str("Select a commit to tag")),
+                },
+                (false, [name, revision]) => GitAction::CreateTag {
+                    name: name.to_owned(),
+                    target: revision.to_owned(),
+                    message,
+                },
+                _ => {
+                    return Err(usage(
+                        "tag [[--message=<message>] <name> [<revision>] | -d <name>]",
+                    ))
+                }
+            };
+            Ok(vec![action])
+        }))
+    });
+
+    handler.add_handler("remote", |ctx, args| {
+        if let Err(err) = args.check_flags(&[]) {
+            return CommandResult::Error(err);
+        }
+        let repo = match ctx.require_repo() {
+            Ok(repo) => &repo.git2_repository,
+            Err(err) => return err,
+        };
+
+        // The remote changes git has commands for are translated, what's left is the view and
+        // the Volnita setting of which remote to fetch from.
+        let result = match args.positional() {
+            [] => return CommandResult::SwitchView(SwitchView::Remotes),
+            [prefer, name] if prefer == "prefer" => remote::set_preferred(repo, Some(name)),
+            [prefer] if prefer == "prefer" => remote::set_preferred(repo, None),
+            _ => return CommandResult::Error("Usage: remote [prefer [<name>]]".to_owned()),
+        };
+
+        match result {
+            Ok(summary) => CommandResult::Message(summary),
+            Err(err) => CommandResult::Error(err.message().to_owned()),
+        }
+    });
+    handler.add_translation("remote", |ctx, args| {
+        let subcommand = args.get(0).unwrap_or_default();
+        if matches!(subcommand, "" | "prefer") {
+            return None;
+        }
+        let allowed: &[&str] = match subcommand {
+            "set-url" => &["push"],
+            _ => &[],
+        };
+
+        Some(translate(ctx, args, allowed, |_repo| {
+            let rest = &args.positional()[1..];
+            let action = match (subcommand, rest) {
+                ("add", [name, url]) => GitAction::AddRemote {
+                    name: name.to_owned(),
+                    url: url.to_owned(),
+                },
+                ("rename", [old_name, new_name]) => GitAction::RenameRemote {
+                    old_name: old_name.to_owned(),
+                    new_name: new_name.to_owned(),
+                },
+                ("remove", [name]) => GitAction::RemoveRemote {
+                    name: name.to_owned(),
+                },
+                ("set-url", [name, url]) => GitAction::SetRemoteUrl {
+                    name: name.to_owned(),
+                    url: url.to_owned(),
+                    push: args.has_flag(&["push"]),
+                },
+                _ => {
+                    return Err(usage(
+                        "remote [add <name> <url> | rename <name> <new name> | remove <name> \
+                         | set-url [--push] <name> <url> | prefer [<name>]]",
+                    ))
+                }
+            };
+            Ok(vec![action])
+        }))
+    });
+
+    handler.add_translation("fetch", |ctx, args| {
+        Some(translate(ctx, args, &[], |_repo| match args.positional() {
+            [] => Ok(vec![GitAction::Fetch { remote: None }]),
+            [remote] => Ok(vec![GitAction::Fetch {
+                remote: Some(remote.to_owned()),
+            }]),
+            _ => Err(usage("fetch [<remote>]")),
+        }))
+    });
+    handler.add_translation("pull", |ctx, args| {
+        Some(translate(ctx, args, &["ff", "no-ff", "ff-only"], |_repo| {
+            let mode = merge_mode(args)?;
+            match args.positional() {
+                [] => Ok(vec![GitAction::Pull { remote: None, mode }]),
+                [remote] => Ok(vec![GitAction::Pull {
+                    remote: Some(remote.to_owned()),
+                    mode,
+                }]),
+                _ => Err(usage("pull [--ff | --no-ff | --ff-only] [<remote>]")),
+            }
+        }))
+    });
+    handler.add_translation("push", |ctx, args| {
+        Some(translate(ctx, args, &["f", "force"], |_repo| {
+            let (remote, branches) = match args.positional() {
+                [] => (None, &[][..]),
+                [remote, branches @ ..] => (Some(remote.to_owned()), branches),
+            };
+            Ok(vec![GitAction::Push {
+                remote,
+                branches: branches.to_vec(),
+                force: args.has_flag(&["f", "force"]),
+            }])
+        }))
+    });
+
+    handler.add_completion("checkout", &[CompletionKind::Branch]);
+    handler.add_completion("add", &[CompletionKind::Path]);
+    handler.add_completion("unstage", &[CompletionKind::Path]);
+    handler.add_completion("goto", &[CompletionKind::Revision]);
+    handler.add_completion("rebase", &[CompletionKind::Revision]);
+    handler.add_completion("cherry-pick", &[CompletionKind::Revision]);
+    handler.add_completion("merge", &[CompletionKind::Branch]);
+    handler.add_completion("blame", &[CompletionKind::Path, CompletionKind::Revision]);
+    handler.add_completion("reset", &[CompletionKind::Revision]);
+    handler.add_completion("tag", &[CompletionKind::Revision]);
+    handler.add_completion("fetch", &[CompletionKind::Remote]);
+    handler.add_completion("pull", &[CompletionKind::Remote]);
+    handler.add_completion("push", &[CompletionKind::Remote, CompletionKind::Branch]);
 
     handler
 }
+
+/// The merge mode picked with `--ff`, `--no-ff` or `--ff-only`, if any.
+fn merge_mode(args: &Args) -> Result<Option<MergeMode>, git2::Error> {
+    let modes: Vec<MergeMode> = ["ff", "no-ff", "ff-only"]
+        .into_iter()
+        .filter(|name| args.has_flag(&[name]))
+        .filter_map(MergeMode::from_name)
+        .collect();
+    match modes.as_slice() {
+        [] => Ok(None),
+        [mode] => Ok(Some(*mode)),
+        _ => Err(git2::Error::from_str(
+            "Give only one of --ff, --no-ff and --ff-only",
+        )),
+    }
+}
+
+/// Translates a command taking the options in `allowed` into the actions `build` makes from
+/// the open repository.
+fn translate(
+    ctx: &CommandContext,
+    args: &Args,
+    allowed: &[&str],
+    build: impl FnOnce(&git2::Repository) -> Result<Vec<GitAction>, git2::Error>,
+) -> Result<Vec<GitAction>, String> {
+    args.check_flags(allowed)?;
+    let repo = ctx.repo.ok_or_else(|| "No repository is open".to_owned())?;
+
+    build(&repo.git2_repository).map_err(|err| err.message().to_owned())
+}
+
+/// Error for a command used the wrong way, `usage` is what it takes.
+fn usage(usage: &str) -> git2::Error {
+    git2::Error::from_str(&format!("Usage: {usage}"))
+}
+
+/// Runs the actions a command translated to, reloading the log with what they did. A reset
+/// asks first, and an operation or merge which stopped on conflicts opens them. `git rebase -i`
+/// ends by opening the planner where git would open the todo list in an editor, and
+/// `git revert` without `--no-edit` the revert view where git would open the messages.
+pub(super) fn run_actions(ctx: &mut CommandContext, actions: &[GitAction]) -> CommandResult {
+    let repo = match ctx.require_repo() {
+        Ok(repo) => &repo.git2_repository,
+        Err(err) => return err,
+    };
+
+    let reset = actions.iter().find_map(|action| match action {
+        GitAction::Reset { mode, target } => Some((*mode, target)),
+        _ => None,
+    });
+    if let (Some((mode, target)), false) = (reset, ctx.confirmed) {
+        return confirm_reset(ctx, repo, actions, mode, target);
+    }
+
+    let (actions, editor) = match actions.split_last() {
+        Some((last @ (GitAction::Rebase { .. } | GitAction::Revert { edit: true, .. }), rest)) => {
+            (rest, Some(last))
+        }
+        _ => (actions, None),
+    };
+    let merged = actions
+        .iter()
+        .any(|action| matches!(action, GitAction::Merge { .. } | GitAction::Pull { .. }));
+
+    // Fetches, pulls and pushes show their progress while they run.
+    let summary = action::execute_all_with(repo, actions, |action| {
+        match ctx.transfer_ui.as_deref_mut() {
+            Some(ui) => action.execute_with_ui(repo, ui),
+            None => action.execute(repo),
+        }
+    });
+    let summary = match summary {
+        Ok(summary) => summary,
+        Err(err) => return CommandResult::Error(err.message().to_owned()),
+    };
+
+    match editor {
+        _ if rebase::stopped_on_conflict(repo) => CommandResult::SwitchView(SwitchView::Conflicts),
+        _ if merged && merge::in_progress(repo).is_some() => {
+            CommandResult::SwitchView(SwitchView::Conflicts)
+        }
+        Some(GitAction::Rebase { onto }) => open_rebase_planner(repo, onto),
+        Some(GitAction::Revert {
+            revisions,
+            mainline,
+            ..
+        }) => open_revert_view(repo, revisions, *mainline),
+        _ => CommandResult::RefreshWithMessage(summary),
+    }
+}
+
+/// Opens the planner for rebasing onto `revision`.
+fn open_rebase_planner(repo: &git2::Repository, revision: &str) -> CommandResult {
+    match RebaseState::load(repo) {
+        Ok(Some(state)) => return CommandResult::Error(state.describe()),
+        Ok(None) => {}
+        Err(err) => return CommandResult::Error(err.message().to_owned()),
+    }
+
+    // Checked here so mistakes are reported in the log rather than in an empty planner.
+    match resolve_commit(repo, revision).and_then(|onto| rebase::plan(repo, onto).map(|_| onto)) {
+        Ok(onto) => CommandResult::SwitchView(SwitchView::Rebase(onto)),
+        Err(err) => CommandResult::Error(err.message().to_owned()),
+    }
+}
+
+/// Asks whether to run `actions`, showing what their reset of the branch does.
+fn confirm_reset(
+    ctx: &mut CommandContext,
+    repo: &git2::Repository,
+    actions: &[GitAction],
+    mode: ResetMode,
+    target: &str,
+) -> CommandResult {
+    let plan = resolve_commit(repo, target)
+        .and_then(|target| reset::plan_reset(repo, target, mode).map(|plan| (plan, target)));
+    let (plan, target) = match plan {
+        Ok(plan) => plan,
+        Err(err) => return CommandResult::Error(err.message().to_owned()),
+    };
+
+    let command = action::command_line(actions);
+    let mut lines = reset_summary(&plan, target, mode);
+    if actions.len() > 1 {
+        lines.splice(0..0, [format!("Runs {command}"), String::new()]);
+    }
+    ctx.confirm(&format!("Reset --{}", mode.name()), lines, command)
+}
+
+/// Opens the revert view for reverting `revisions` in that order.
+fn open_revert_view(
+    repo: &git2::Repository,
+    revisions: &[String],
+    mainline: Option<u32>,
+) -> CommandResult {
+    match RebaseState::load(repo) {
+        Ok(Some(state)) => return CommandResult::Error(state.describe()),
+        Ok(None) => {}
+        Err(err) => return CommandResult::Error(err.message().to_owned()),
+    }
+
+    match revisions
+        .iter()
+        .map(|revision| resolve_commit(repo, revision))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(ids) => CommandResult::SwitchView(SwitchView::Revert(ids, mainline)),
+        Err(err) => CommandResult::Error(err.message().to_owned()),
+    }
+}
+
+/// What a reset is going to do, shown before it runs.
+fn reset_summary(plan: &reset::ResetPlan, target: git2::Oid, mode: ResetMode) -> Vec<String> {
+    let short = |id: git2::Oid| id.to_string()[..7].to_owned();
+    let mut lines = vec![
+        format!(
+            "Reset '{}' from {} to {}?",
+            plan.branch_name(),
+            short(plan.head),
+            short(target)
+        ),
+        String::new(),
+    ];
+
+    match plan.leaving.len() {
+        0 => lines.push("No commits leave the branch.".to_owned()),
+        count => {
+            lines.push(format!("{count} commit(s) leave the branch:"));
+            lines.extend(
+                plan.leaving
+                    .iter()
+                    .take(MAX_LISTED)
+                    .map(|(id, summary)| format!("  {} {summary}", short(*id))),
+            );
+            if count > MAX_LISTED {
+                lines.push(format!("  … and {} more", count - MAX_LISTED));
+            }
+        }
+    }
+    if plan.joining > 0 {
+        lines.push(format!(
+            "{} commit(s) of {} join the branch.",
+            plan.joining,
+            short(target)
+        ));
+    }
+    lines.push(String::new());
+
+    match mode {
+        ResetMode::Soft => lines.push(
+            "The index and working tree are kept, the changes of those commits stay staged."
+                .to_owned(),
+        ),
+        ResetMode::Mixed => lines.push(
+            "The working tree is kept, the changes of those commits and staged changes are \
+             unstaged."
+                .to_owned(),
+        ),
+        ResetMode::Hard if plan.lost.is_empty() => {
+            lines.push("There are no uncommitted changes to lose.".to_owned())
+        }
+        ResetMode::Hard => {
+            lines.push("These uncommitted changes are lost for good:".to_owned());
+            lines.extend(
+                plan.lost
+                    .iter()
+                    .take(MAX_LISTED)
+                    .map(|entry| format!("  {} {}", entry.change, entry.path)),
+            );
+            if plan.lost.len() > MAX_LISTED {
+                lines.push(format!("  … and {} more", plan.lost.len() - MAX_LISTED));
+            }
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "The old position is saved as {}, `reset undo` goes back to it.",
+        reset::BACKUP_REF
+    ));
+    lines
+}
+
+/// The stash given as `n` or `stash@{n}`, the newest one when none is given.
+fn stash_index(args: &[String]) -> Result<usize, git2::Error> {
+    let arg = match args.first() {
+        Some(arg) => arg,
+        None => return Ok(0),
+    };
+
+    stash::parse_name(arg).ok_or_else(|| {
+        git2::Error::from_str(&format!("'{arg}' is not a stash, use n or stash@{{n}}"))
+    })
+}
+
+/// The commit `revision` points to.
+fn resolve_commit(repo: &git2::Repository, revision: &str) -> Result<git2::Oid, git2::Error> {
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+}
+
+/// Switches to a view of the open repository.
+fn switch_to(ctx: &CommandContext, args: &Args, view: SwitchView) -> CommandResult {
+    if let Err(err) = no_args(args) {
+        return err;
+    }
+
+    match ctx.require_repo() {
+        Ok(_) => CommandResult::SwitchView(view),
+        Err(err) => err,
+    }
+}
+
+/// Rejects any argument given to a command which takes none.
+fn no_args(args: &Args) -> Result<(), CommandResult> {
+    args.check_flags(&[]).map_err(CommandResult::Error)?;
+
+    match args.positional().first() {
+        Some(arg) => Err(CommandResult::Error(format!("Unexpected argument '{arg}'"))),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,53 @@
+use git2::Oid;
+
+use crate::{
+    app_flags::SwitchView, git::GitRepo, traits::transfer_ui::TransferUi,
+    view_components::ui_services::UiServices,
+};
+
+/// Everything a command handler may look at or drive while it runs.
+pub struct CommandContext<'a> {
+    /// The open repository, `None` while the start view is shown.
+    pub repo: Option<&'a GitRepo>,
+    pub view: ViewState,
+    pub ui: &'a mut UiServices,
+    /// Whether the user accepted the confirmation this command asked for on an earlier run.
+    pub confirmed: bool,
+    /// Shows the progress of fetches and pushes, `None` where there is nothing to draw on.
+    pub transfer_ui: Option<&'a mut dyn TransferUi>,
+}
+
+/// Selection of the view the command was entered in. Commands may change `selected_commit` to
+/// move the selection.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ViewState {
+    pub selected_commit: Option<Oid>,
+    pub marked_commits: Vec<Oid>,
+}
+
+/// What the application should do once a command has run.
+pub enum CommandResult {
+    Continue,
+    Quit,
+    SwitchView(SwitchView),
+    /// Reload whatever the view shows from the repository.
+    Refresh,
+    /// Reload the view and show the message, for commands whose outcome is worth reading.
+    RefreshWithMessage(String),
+    Message(String),
+    Error(String),
+}
+
+impl<'a> CommandContext<'a> {
+    /// Asks the user to confirm `lines`, running `command` with `confirmed` set once they do.
+    pub fn confirm(&mut self, title: &str, lines: Vec<String>, command: String) -> CommandResult {
+        self.ui.confirm(title, lines, command);
+        CommandResult::Continue
+    }
+
+    /// The open repository, or an error result for commands which need one.
+    pub fn require_repo(&self) -> Result<&'a GitRepo, CommandResult> {
+        self.repo
+            .ok_or_else(|| CommandResult::Error("No repository is open".to_owned()))
+    }
+}
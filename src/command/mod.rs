@@ -1,2 +1,5 @@
+pub mod args;
 pub mod command_handler;
+pub mod completion;
 pub mod construct_handler;
+pub mod context;
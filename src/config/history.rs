@@ -0,0 +1,58 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{get_config_path, Config};
+
+const FILE_NAME: &str = "history";
+const FILE_NAME_WITH_EXT: &str = "history.toml";
+
+/// Most commands kept for each repository, the oldest are dropped first.
+const MAX_ENTRIES: usize = 500;
+
+/// Commands entered in each repository, oldest first, keyed by the path of the repository.
+#[derive(Serialize, Deserialize, Default)]
+pub struct CommandHistory {
+    #[serde(default)]
+    pub repositories: BTreeMap<String, Vec<String>>,
+}
+
+impl Config for CommandHistory {
+    fn load_config() -> Option<CommandHistory> {
+        let mut path = get_config_path()?;
+        path.push(FILE_NAME_WITH_EXT);
+
+        let string = fs::read_to_string(path).ok()?;
+        let data = toml::from_str::<CommandHistory>(&string).ok()?;
+        Some(data)
+    }
+
+    fn save_config(&self) -> Result<(), io::Error> {
+        super::save_config_internal(FILE_NAME, self)?;
+        Ok(())
+    }
+}
+
+impl CommandHistory {
+    pub fn entries(&self, repo_path: &Path) -> Vec<String> {
+        self.repositories
+            .get(repo_path.to_string_lossy().as_ref())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Adds `command` as the newest entry, moving it there if it was entered before.
+    pub fn record(&mut self, repo_path: &Path, command: &str) {
+        let entries = self
+            .repositories
+            .entry(repo_path.to_string_lossy().into_owned())
+            .or_default();
+
+        entries.retain(|entry| entry != command);
+        entries.push(command.to_owned());
+
+        if entries.len() > MAX_ENTRIES {
+            entries.drain(..entries.len() - MAX_ENTRIES);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::BTreeMap, fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::command::args;
+
+use super::{get_config_path, Config};
+
+const FILE_NAME: &str = "macros";
+const FILE_NAME_WITH_EXT: &str = "macros.toml";
+
+/// A named list of Volnita commands. Commands may reference the arguments the macro was called
+/// with as `$1` to `$9`, or all of them at once as `$@`. Substituted arguments are quoted so
+/// they stay single arguments.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Macro {
+    #[serde(default)]
+    pub description: String,
+    pub commands: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SavedMacros {
+    #[serde(default)]
+    pub macros: BTreeMap<String, Macro>,
+}
+
+impl Config for SavedMacros {
+    fn load_config() -> Option<SavedMacros> {
+        let mut path = get_config_path()?;
+        path.push(FILE_NAME_WITH_EXT);
+
+        let string = fs::read_to_string(path).ok()?;
+        let data = toml::from_str::<SavedMacros>(&string).ok()?;
+        Some(data)
+    }
+
+    fn save_config(&self) -> Result<(), io::Error> {
+        super::save_config_internal(FILE_NAME, self)?;
+        Ok(())
+    }
+}
+
+impl Macro {
+    /// Substitutes the positional parameters of every command with `args`.
+    pub fn expand(&self, args: &[&str]) -> Result<Vec<String>, String> {
+        self.commands
+            .iter()
+            .map(|command| expand_command(command, args))
+            .collect()
+    }
+}
+
+fn expand_command(command: &str, args: &[&str]) -> Result<String, String> {
+    let mut expanded = String::with_capacity(command.len());
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        match chars.peek().copied() {
+            Some('@') => {
+                chars.next();
+                let quoted: Vec<String> = args.iter().map(|arg| args::quote(arg)).collect();
+                expanded.push_str(&quoted.join(" "));
+            }
+            Some('$') => {
+                chars.next();
+                expanded.push('$');
+            }
+            Some(digit @ '1'..='9') => {
+                chars.next();
+                let index = digit as usize - '1' as usize;
+                let arg = args
+                    .get(index)
+                    .ok_or_else(|| format!("Missing argument ${digit} for \"{command}\""))?;
+                expanded.push_str(&args::quote(arg));
+            }
+            _ => expanded.push('$'),
+        }
+    }
+
+    Ok(expanded)
+}
@@ -1,3 +1,5 @@
+pub mod history;
+pub mod macros;
 pub mod repo;
 
 use std::{
@@ -14,13 +16,7 @@ pub trait Config: Sized + Default {
     fn load_config() -> Option<Self>;
 
     fn load_or_create_config() -> Self {
-        let data = Self::load_config();
-
-        if let Some(repo) = data {
-            repo
-        } else {
-            Self::default()
-        }
+        Self::load_config().unwrap_or_default()
     }
 }
 
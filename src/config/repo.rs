@@ -1,4 +1,7 @@
-use std::{fs, io, path::PathBuf};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +15,11 @@ pub struct SerializedRepository {
     pub path: PathBuf,
     pub name: String,
     pub repo_url: String,
+    /// Remote used for the URL and as the default of remote commands, instead of `origin`.
+    /// Only the saved copy is used, it is set by `remote prefer` and read back with
+    /// [`SavedRepositories::preferred_remote`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_remote: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -19,12 +27,39 @@ pub struct SavedRepositories {
     pub recent_repositories: Vec<SerializedRepository>,
 }
 
+impl SavedRepositories {
+    /// The remote chosen for the repository saved at `path`, if any.
+    pub fn preferred_remote(&self, path: &Path) -> Option<String> {
+        self.recent_repositories
+            .iter()
+            .find(|repo| repo.path == path)
+            .and_then(|repo| repo.preferred_remote.to_owned())
+    }
+
+    /// Remembers `remote` as the preferred remote of the repository saved at `path`, or forgets
+    /// the preferred remote when it's `None`.
+    pub fn set_preferred_remote(&mut self, path: &Path, remote: Option<&str>) -> io::Result<()> {
+        let repo = self
+            .recent_repositories
+            .iter_mut()
+            .find(|repo| repo.path == path)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "The repository is not in the saved repositories",
+                )
+            })?;
+        repo.preferred_remote = remote.map(str::to_owned);
+        Ok(())
+    }
+}
+
 impl Config for SavedRepositories {
     fn load_config() -> Option<SavedRepositories> {
         let mut path = get_config_path()?;
         path.push(FILE_NAME_WITH_EXT);
 
-        let string = fs::read_to_string(path).unwrap();
+        let string = fs::read_to_string(path).ok()?;
         let data = toml::from_str::<SavedRepositories>(&string).ok()?;
         Some(data)
     }
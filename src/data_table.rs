@@ -1,7 +1,95 @@
+use std::collections::BTreeSet;
+
 use tui::widgets::TableState;
 
 #[derive(Default)]
 pub struct DataTable {
     pub table_state: TableState,
     pub table_items: Vec<Vec<String>>,
+    /// Rows marked for a bulk action, separate from the single row cursor in `table_state`.
+    pub marked_rows: BTreeSet<usize>,
+}
+
+impl DataTable {
+    /// Moves the selection down a row, wrapping around to the top.
+    pub fn select_next(&mut self) {
+        let count = self.table_items.len();
+        if count == 0 {
+            return;
+        }
+
+        let i = match self.table_state.selected() {
+            Some(i) if i >= count - 1 => 0,
+            Some(i) => i + 1,
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    /// Moves the selection up a row, wrapping around to the bottom.
+    pub fn select_previous(&mut self) {
+        let count = self.table_items.len();
+        if count == 0 {
+            return;
+        }
+
+        let i = match self.table_state.selected() {
+            Some(0) => count - 1,
+            Some(i) => i - 1,
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn is_marked(&self, row: usize) -> bool {
+        self.marked_rows.contains(&row)
+    }
+
+    /// Marks the row under the cursor, or unmarks it if it was already marked.
+    pub fn toggle_marked(&mut self) {
+        if let Some(i) = self.table_state.selected() {
+            if !self.marked_rows.remove(&i) && i < self.table_items.len() {
+                self.marked_rows.insert(i);
+            }
+        }
+    }
+
+    pub fn mark_all(&mut self) {
+        self.marked_rows = (0..self.table_items.len()).collect();
+    }
+
+    pub fn invert_marked(&mut self) {
+        self.marked_rows = (0..self.table_items.len())
+            .filter(|i| !self.marked_rows.contains(i))
+            .collect();
+    }
+
+    pub fn clear_marked(&mut self) {
+        self.marked_rows.clear();
+    }
+
+    /// Rows a bulk action should apply to, the marked rows or the row under the cursor when
+    /// nothing has been marked.
+    pub fn marked_or_selected(&self) -> Vec<usize> {
+        if self.marked_rows.is_empty() {
+            self.table_state
+                .selected()
+                .filter(|i| *i < self.table_items.len())
+                .into_iter()
+                .collect()
+        } else {
+            self.marked_rows.iter().copied().collect()
+        }
+    }
+
+    /// Keeps the selection inside the table after the items have been replaced.
+    pub fn clamp_selection(&mut self) {
+        let count = self.table_items.len();
+        match self.table_state.selected() {
+            _ if count == 0 => self.table_state.select(None),
+            Some(i) if i >= count => self.table_state.select(Some(count - 1)),
+            None => self.table_state.select(Some(0)),
+            _ => {}
+        }
+    }
 }
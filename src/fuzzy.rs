@@ -0,0 +1,44 @@
+/// Matches `pattern` against `text` ignoring case, returning the char indices of `text` which
+/// matched. A contiguous match is preferred, otherwise the pattern's characters only have to
+/// appear in order.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    if pattern.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let contiguous = (0..text.len().saturating_sub(pattern.len() - 1)).find(|start| {
+        pattern
+            .iter()
+            .zip(&text[*start..])
+            .all(|(p, t)| same_char(*p, *t))
+    });
+    if let Some(start) = contiguous {
+        return Some((start..start + pattern.len()).collect());
+    }
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut pattern_chars = pattern.iter().peekable();
+
+    for (i, c) in text.iter().enumerate() {
+        match pattern_chars.peek() {
+            Some(p) if same_char(**p, *c) => {
+                positions.push(i);
+                pattern_chars.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    match pattern_chars.peek() {
+        Some(_) => None,
+        None => Some(positions),
+    }
+}
+
+fn same_char(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
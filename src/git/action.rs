@@ -0,0 +1,1487 @@
+use git2::Repository;
+
+use crate::{command::args, traits::transfer_ui::TransferUi};
+
+use super::{
+    branch::{self, BranchKind},
+    cherry_pick, commit,
+    conflict::{self, ConflictSide},
+    merge::{self, MergeMode},
+    rebase::{self, RebaseState, SequenceKind},
+    remote,
+    reset::{self, ResetMode},
+    revert, stash,
+    status::{self, FileState, StatusEntry},
+    tag, transfer,
+};
+
+/// Every change Volnita makes to a repository, expressed so it can be shown as the equivalent
+/// git command line and parsed back from an edited one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitAction {
+    Add {
+        paths: Vec<String>,
+    },
+    Unstage {
+        paths: Vec<String>,
+    },
+    Restore {
+        paths: Vec<String>,
+        from_head: bool,
+    },
+    Clean {
+        paths: Vec<String>,
+    },
+    Remove {
+        paths: Vec<String>,
+    },
+    /// Writes one side of conflicted files to the working tree, leaving them conflicted.
+    CheckoutSide {
+        side: ConflictSide,
+        paths: Vec<String>,
+    },
+    Commit {
+        message: String,
+    },
+    Checkout {
+        branch: String,
+        remote: bool,
+    },
+    CreateBranch {
+        name: String,
+    },
+    RenameBranch {
+        old_name: String,
+        new_name: String,
+    },
+    DeleteBranch {
+        name: String,
+        remote: bool,
+    },
+    /// `git rebase -i`, whose todo list is planned in the rebase planner.
+    Rebase {
+        onto: String,
+    },
+    CherryPick {
+        revisions: Vec<String>,
+    },
+    /// `git revert`, which keeps the side of `mainline` for merges. Without `--no-edit` the
+    /// messages are edited in the revert view first.
+    Revert {
+        revisions: Vec<String>,
+        mainline: Option<u32>,
+        edit: bool,
+    },
+    StashPush {
+        message: Option<String>,
+        include_untracked: bool,
+        keep_index: bool,
+    },
+    /// `git stash apply`, or `git stash pop` dropping the stash once it applied.
+    StashApply {
+        index: usize,
+        pop: bool,
+    },
+    StashDrop {
+        index: usize,
+    },
+    StashBranch {
+        name: String,
+        index: usize,
+    },
+    /// A tag on `target`, annotated when it has a message.
+    CreateTag {
+        name: String,
+        target: String,
+        message: Option<String>,
+    },
+    DeleteTag {
+        name: String,
+    },
+    AddRemote {
+        name: String,
+        url: String,
+    },
+    RenameRemote {
+        old_name: String,
+        new_name: String,
+    },
+    RemoveRemote {
+        name: String,
+    },
+    /// Sets the fetch URL, or with `push` the push URL which an empty URL removes.
+    SetRemoteUrl {
+        name: String,
+        url: String,
+        push: bool,
+    },
+    /// Merges `revision` into the current branch, following `merge.ff` without a mode.
+    Merge {
+        revision: String,
+        mode: Option<MergeMode>,
+    },
+    /// Commits the merge in progress once its conflicts are resolved.
+    MergeContinue,
+    MergeAbort,
+    /// Fetches a remote, the default one when `remote` is `None`, see [`transfer::fetch`].
+    Fetch {
+        remote: Option<String>,
+    },
+    Pull {
+        remote: Option<String>,
+        mode: Option<MergeMode>,
+    },
+    /// Pushes `branches`, or the current branch when there are none.
+    Push {
+        remote: Option<String>,
+        branches: Vec<String>,
+        force: bool,
+    },
+    /// Moves the current branch, see [`reset::reset`].
+    Reset {
+        mode: ResetMode,
+        target: String,
+    },
+    /// Goes on with a stopped rebase, cherry-pick or revert.
+    Sequence {
+        kind: SequenceKind,
+        command: SequenceCommand,
+    },
+}
+
+/// What to do with a stopped rebase, cherry-pick or revert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceCommand {
+    Continue,
+    Skip,
+    Abort,
+}
+
+impl SequenceCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SequenceCommand::Continue => "continue",
+            SequenceCommand::Skip => "skip",
+            SequenceCommand::Abort => "abort",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SequenceCommand> {
+        match name {
+            "continue" => Some(SequenceCommand::Continue),
+            "skip" => Some(SequenceCommand::Skip),
+            "abort" => Some(SequenceCommand::Abort),
+            _ => None,
+        }
+    }
+}
+
+impl GitAction {
+    /// Actions discarding the changes of `entries`, see [`status::restore_paths`].
+    pub fn discard(entries: &[&StatusEntry]) -> Vec<GitAction> {
+        let paths = |keep: &dyn Fn(&StatusEntry) -> bool| -> Vec<String> {
+            entries
+                .iter()
+                .filter(|entry| keep(entry))
+                .map(|entry| entry.path.to_owned())
+                .collect()
+        };
+        let staged = |entry: &StatusEntry| entry.state == FileState::Staged;
+        // Files added since HEAD have no version there to check out, they are removed instead.
+        let added = |entry: &StatusEntry| staged(entry) && entry.change == "new";
+        // Discarding the staged changes of a file discards the ones made to it since as well.
+        let staged_paths = paths(&staged);
+
+        let actions = [
+            GitAction::Remove {
+                paths: paths(&added),
+            },
+            GitAction::Restore {
+                paths: paths(&|entry| staged(entry) && !added(entry)),
+                from_head: true,
+            },
+            GitAction::Restore {
+                paths: paths(&|entry| {
+                    entry.state == FileState::Unstaged && !staged_paths.contains(&entry.path)
+                }),
+                from_head: false,
+            },
+            GitAction::Clean {
+                paths: paths(&|entry| entry.state == FileState::Untracked),
+            },
+        ];
+
+        actions
+            .into_iter()
+            .filter(|action| action.paths().is_some_and(|paths| !paths.is_empty()))
+            .collect()
+    }
+
+    /// The paths of actions working on files, `None` for every other action.
+    fn paths(&self) -> Option<&[String]> {
+        match self {
+            GitAction::Add { paths }
+            | GitAction::Unstage { paths }
+            | GitAction::Restore { paths, .. }
+            | GitAction::Clean { paths }
+            | GitAction::Remove { paths }
+            | GitAction::CheckoutSide { paths, .. } => Some(paths),
+            _ => None,
+        }
+    }
+
+    pub fn to_argv(&self) -> Vec<String> {
+        let mut argv = vec!["git".to_owned()];
+        let with_paths = |argv: &mut Vec<String>, paths: &[String]| {
+            argv.push("--".to_owned());
+            argv.extend(paths.iter().cloned());
+        };
+        // One `-m` for each paragraph, git joins them with blank lines again.
+        let with_message = |argv: &mut Vec<String>, message: &str| {
+            for paragraph in message.trim().split("\n\n") {
+                argv.extend(["-m".to_owned(), paragraph.to_owned()]);
+            }
+        };
+
+        match self {
+            GitAction::Add { paths } => {
+                argv.push("add".to_owned());
+                with_paths(&mut argv, paths);
+            }
+            GitAction::Unstage { paths } => {
+                argv.push("reset".to_owned());
+                with_paths(&mut argv, paths);
+            }
+            GitAction::Restore { paths, from_head } => {
+                argv.push("checkout".to_owned());
+                if *from_head {
+                    argv.push("HEAD".to_owned());
+                }
+                with_paths(&mut argv, paths);
+            }
+            GitAction::Clean { paths } => {
+                argv.extend(["clean".to_owned(), "-f".to_owned()]);
+                with_paths(&mut argv, paths);
+            }
+            GitAction::Remove { paths } => {
+                argv.extend(["rm".to_owned(), "-f".to_owned()]);
+                with_paths(&mut argv, paths);
+            }
+            // git has no `checkout --base`, the base is stage 1 of the index.
+            GitAction::CheckoutSide { side, paths } => {
+                match side {
+                    ConflictSide::Base => argv.extend([
+                        "checkout-index".to_owned(),
+                        "-f".to_owned(),
+                        "--stage=1".to_owned(),
+                    ]),
+                    side => argv.extend(["checkout".to_owned(), format!("--{}", side.name())]),
+                }
+                with_paths(&mut argv, paths);
+            }
+            GitAction::Commit { message } => {
+                argv.push("commit".to_owned());
+                with_message(&mut argv, message);
+            }
+            GitAction::Checkout { branch, remote } => {
+                argv.push("checkout".to_owned());
+                if *remote {
+                    argv.push("--track".to_owned());
+                }
+                argv.push(branch.to_owned());
+            }
+            GitAction::CreateBranch { name } => {
+                argv.extend(["branch".to_owned(), name.to_owned()]);
+            }
+            GitAction::RenameBranch { old_name, new_name } => {
+                argv.extend([
+                    "branch".to_owned(),
+                    "-m".to_owned(),
+                    old_name.to_owned(),
+                    new_name.to_owned(),
+                ]);
+            }
+            GitAction::DeleteBranch { name, remote } => {
+                argv.extend(["branch".to_owned(), "-D".to_owned()]);
+                if *remote {
+                    argv.push("-r".to_owned());
+                }
+                argv.push(name.to_owned());
+            }
+            GitAction::Rebase { onto } => {
+                argv.extend(["rebase".to_owned(), "-i".to_owned(), onto.to_owned()]);
+            }
+            GitAction::CherryPick { revisions } => {
+                argv.push("cherry-pick".to_owned());
+                argv.extend(revisions.iter().cloned());
+            }
+            GitAction::Revert {
+                revisions,
+                mainline,
+                edit,
+            } => {
+                argv.push("revert".to_owned());
+                if !edit {
+                    argv.push("--no-edit".to_owned());
+                }
+                if let Some(mainline) = mainline {
+                    argv.extend(["-m".to_owned(), mainline.to_string()]);
+                }
+                argv.extend(revisions.iter().cloned());
+            }
+            GitAction::StashPush {
+                message,
+                include_untracked,
+                keep_index,
+            } => {
+                argv.extend(["stash".to_owned(), "push".to_owned()]);
+                if *include_untracked {
+                    argv.push("-u".to_owned());
+                }
+                if *keep_index {
+                    argv.push("-k".to_owned());
+                }
+                if let Some(message) = message {
+                    argv.extend(["-m".to_owned(), message.to_owned()]);
+                }
+            }
+            GitAction::StashApply { index, pop } => {
+                let command = if *pop { "pop" } else { "apply" };
+                argv.extend([
+                    "stash".to_owned(),
+                    command.to_owned(),
+                    stash::stash_name(*index),
+                ]);
+            }
+            GitAction::StashDrop { index } => {
+                argv.extend([
+                    "stash".to_owned(),
+                    "drop".to_owned(),
+                    stash::stash_name(*index),
+                ]);
+            }
+            GitAction::StashBranch { name, index } => {
+                argv.extend([
+                    "stash".to_owned(),
+                    "branch".to_owned(),
+                    name.to_owned(),
+                    stash::stash_name(*index),
+                ]);
+            }
+            GitAction::CreateTag {
+                name,
+                target,
+                message,
+            } => {
+                argv.push("tag".to_owned());
+                if let Some(message) = message {
+                    with_message(&mut argv, message);
+                }
+                argv.extend([name.to_owned(), target.to_owned()]);
+            }
+            GitAction::DeleteTag { name } => {
+                argv.extend(["tag".to_owned(), "-d".to_owned(), name.to_owned()]);
+            }
+            GitAction::AddRemote { name, url } => {
+                argv.extend([
+                    "remote".to_owned(),
+                    "add".to_owned(),
+                    name.to_owned(),
+                    url.to_owned(),
+                ]);
+            }
+            GitAction::RenameRemote { old_name, new_name } => {
+                argv.extend([
+                    "remote".to_owned(),
+                    "rename".to_owned(),
+                    old_name.to_owned(),
+                    new_name.to_owned(),
+                ]);
+            }
+            GitAction::RemoveRemote { name } => {
+                argv.extend(["remote".to_owned(), "remove".to_owned(), name.to_owned()]);
+            }
+            // git has no command to go back to pushing to the fetch URL but unsetting the config.
+            GitAction::SetRemoteUrl { name, url, push } if *push && url.is_empty() => {
+                argv.extend([
+                    "config".to_owned(),
+                    "--unset".to_owned(),
+                    format!("remote.{name}.pushurl"),
+                ]);
+            }
+            GitAction::SetRemoteUrl { name, url, push } => {
+                argv.extend(["remote".to_owned(), "set-url".to_owned()]);
+                if *push {
+                    argv.push("--push".to_owned());
+                }
+                argv.extend([name.to_owned(), url.to_owned()]);
+            }
+            GitAction::Merge { revision, mode } => {
+                argv.push("merge".to_owned());
+                argv.extend(mode.map(|mode| format!("--{}", mode.name())));
+                argv.push(revision.to_owned());
+            }
+            GitAction::MergeContinue => argv.extend(["merge".to_owned(), "--continue".to_owned()]),
+            GitAction::MergeAbort => argv.extend(["merge".to_owned(), "--abort".to_owned()]),
+            GitAction::Fetch { remote } => {
+                argv.push("fetch".to_owned());
+                argv.extend(remote.iter().cloned());
+            }
+            GitAction::Pull { remote, mode } => {
+                argv.push("pull".to_owned());
+                argv.extend(mode.map(|mode| format!("--{}", mode.name())));
+                argv.extend(remote.iter().cloned());
+            }
+            GitAction::Push {
+                remote,
+                branches,
+                force,
+            } => {
+                argv.push("push".to_owned());
+                if *force {
+                    argv.push("--force".to_owned());
+                }
+                argv.extend(remote.iter().cloned());
+                argv.extend(branches.iter().cloned());
+            }
+            GitAction::Reset { mode, target } => {
+                argv.extend([
+                    "reset".to_owned(),
+                    format!("--{}", mode.name()),
+                    target.to_owned(),
+                ]);
+            }
+            GitAction::Sequence { kind, command } => {
+                argv.extend([kind.name().to_owned(), format!("--{}", command.name())]);
+            }
+        }
+
+        argv
+    }
+
+    pub fn from_argv(argv: &[String]) -> Result<GitAction, String> {
+        let unsupported = || format!("Unsupported git command: {}", join_argv(argv));
+
+        let (git, rest) = argv.split_first().ok_or("Empty git command")?;
+        if git != "git" {
+            return Err(format!("Expected a git command, found '{git}'"));
+        }
+
+        let (subcommand, args) = rest.split_first().ok_or_else(unsupported)?;
+        let (options, paths) = split_paths(args);
+
+        let action = match subcommand.as_str() {
+            "add" if options.is_empty() => GitAction::Add { paths },
+            "reset" if !args.iter().any(|arg| arg == "--") => match parse_reset(args) {
+                Some(action) => action,
+                // Without `--` git reads the first path as the revision to reset to.
+                None if options.is_empty() => {
+                    return Err("Put the paths to unstage after `--` in git reset".to_owned())
+                }
+                None => return Err(unsupported()),
+            },
+            "reset" if options.is_empty() || options == ["HEAD"] => GitAction::Unstage { paths },
+            "restore" => match options.as_slice() {
+                [] => GitAction::Restore {
+                    paths,
+                    from_head: false,
+                },
+                [staged] if staged == "--staged" || staged == "-S" => GitAction::Unstage { paths },
+                _ => return Err(unsupported()),
+            },
+            "checkout" if args.iter().any(|arg| arg == "--") => match options.as_slice() {
+                [] => GitAction::Restore {
+                    paths,
+                    from_head: false,
+                },
+                [head] if head == "HEAD" => GitAction::Restore {
+                    paths,
+                    from_head: true,
+                },
+                [side] if side == "--ours" || side == "--theirs" => GitAction::CheckoutSide {
+                    side: if side == "--ours" {
+                        ConflictSide::Ours
+                    } else {
+                        ConflictSide::Theirs
+                    },
+                    paths,
+                },
+                _ => return Err(unsupported()),
+            },
+            "checkout-index" => GitAction::CheckoutSide {
+                side: parse_stage(&options).ok_or_else(unsupported)?,
+                paths,
+            },
+            "checkout" | "switch" => match args {
+                [branch] => GitAction::Checkout {
+                    branch: branch.to_owned(),
+                    remote: false,
+                },
+                [track, branch] if track == "--track" || track == "-t" => GitAction::Checkout {
+                    branch: branch.to_owned(),
+                    remote: true,
+                },
+                _ => return Err(unsupported()),
+            },
+            "clean" => match options.as_slice() {
+                [force] if force == "-f" || force == "--force" => GitAction::Clean { paths },
+                _ => return Err(unsupported()),
+            },
+            "rm" => match options.as_slice() {
+                [force] if force == "-f" || force == "--force" => GitAction::Remove { paths },
+                _ => return Err(unsupported()),
+            },
+            "commit" => GitAction::Commit {
+                message: parse_commit_message(args).ok_or_else(unsupported)?,
+            },
+            "branch" => parse_branch(args).ok_or_else(unsupported)?,
+            "rebase" => match args {
+                [interactive, onto] if interactive == "-i" || interactive == "--interactive" => {
+                    GitAction::Rebase {
+                        onto: onto.to_owned(),
+                    }
+                }
+                _ => parse_sequence(SequenceKind::Rebase, args).ok_or_else(unsupported)?,
+            },
+            "cherry-pick" if !args.is_empty() && args.iter().all(|arg| !arg.starts_with('-')) => {
+                GitAction::CherryPick {
+                    revisions: args.to_vec(),
+                }
+            }
+            "cherry-pick" => {
+                parse_sequence(SequenceKind::CherryPick, args).ok_or_else(unsupported)?
+            }
+            "stash" => parse_stash(args).ok_or_else(unsupported)?,
+            "tag" => parse_tag(args).ok_or_else(unsupported)?,
+            "remote" => parse_remote(args).ok_or_else(unsupported)?,
+            "merge" => parse_merge(args).ok_or_else(unsupported)?,
+            "fetch" => match args {
+                [] => GitAction::Fetch { remote: None },
+                [remote] if !remote.starts_with('-') => GitAction::Fetch {
+                    remote: Some(remote.to_owned()),
+                },
+                _ => return Err(unsupported()),
+            },
+            "pull" => parse_pull(args).ok_or_else(unsupported)?,
+            "push" => parse_push(args).ok_or_else(unsupported)?,
+            "config" => match args {
+                [unset, key] if unset == "--unset" => GitAction::SetRemoteUrl {
+                    name: key
+                        .strip_prefix("remote.")
+                        .and_then(|key| key.strip_suffix(".pushurl"))
+                        .ok_or_else(unsupported)?
+                        .to_owned(),
+                    url: String::new(),
+                    push: true,
+                },
+                _ => return Err(unsupported()),
+            },
+            "revert" => parse_sequence(SequenceKind::Revert, args)
+                .or_else(|| parse_revert(args))
+                .ok_or_else(unsupported)?,
+            _ => return Err(unsupported()),
+        };
+
+        if action.paths().is_some_and(<[String]>::is_empty) {
+            return Err(format!("No paths given to git {subcommand}"));
+        }
+
+        Ok(action)
+    }
+
+    /// [`execute`](Self::execute) showing the progress of fetches, pulls and pushes on `ui`.
+    pub fn execute_with_ui(
+        &self,
+        repo: &Repository,
+        ui: &mut dyn TransferUi,
+    ) -> Result<String, git2::Error> {
+        match self {
+            GitAction::Fetch { remote } => transfer::fetch(repo, remote.as_deref(), ui),
+            GitAction::Pull { remote, mode } => transfer::pull(repo, remote.as_deref(), *mode, ui),
+            GitAction::Push {
+                remote,
+                branches,
+                force,
+            } => transfer::push(repo, remote.as_deref(), branches, *force, ui),
+            _ => self.execute(repo),
+        }
+    }
+
+    /// Runs the action, returning a short summary of what was done.
+    pub fn execute(&self, repo: &Repository) -> Result<String, git2::Error> {
+        match self {
+            GitAction::Add { paths } => {
+                for path in paths {
+                    status::stage_path(repo, path)?;
+                }
+                Ok(format!("Staged {}", paths.join(", ")))
+            }
+            GitAction::Unstage { paths } => {
+                for path in paths {
+                    status::unstage_path(repo, path)?;
+                }
+                Ok(format!("Unstaged {}", paths.join(", ")))
+            }
+            GitAction::Restore { paths, from_head } => {
+                status::restore_paths(repo, paths, *from_head)?;
+                Ok(format!("Discarded changes to {} file(s)", paths.len()))
+            }
+            GitAction::Clean { paths } => {
+                status::clean_paths(repo, paths)?;
+                Ok(format!("Deleted {} untracked file(s)", paths.len()))
+            }
+            GitAction::Remove { paths } => {
+                status::remove_paths(repo, paths)?;
+                Ok(format!("Removed {} file(s)", paths.len()))
+            }
+            GitAction::CheckoutSide { side, paths } => {
+                let conflicts = conflict::load_conflicts(repo)?;
+                for path in paths {
+                    let entry = conflicts
+                        .iter()
+                        .find(|entry| &entry.path == path)
+                        .ok_or_else(|| {
+                            git2::Error::from_str(&format!("{path} is not conflicted"))
+                        })?;
+                    conflict::checkout_side(repo, entry, *side)?;
+                }
+                Ok(format!("Took {} for {}", side.name(), paths.join(", ")))
+            }
+            GitAction::Commit { message } => {
+                let id = commit::create_commit(repo, message)?;
+                Ok(format!("Created commit {}", &id.to_string()[..7]))
+            }
+            GitAction::Checkout { branch, remote } => {
+                let kind = if *remote {
+                    BranchKind::Remote
+                } else {
+                    branch::branch_kind(repo, branch)?
+                };
+                let name = branch::checkout_branch(repo, branch, kind)?;
+                Ok(format!("Switched to branch '{name}'"))
+            }
+            GitAction::CreateBranch { name } => {
+                branch::create_branch(repo, name)?;
+                Ok(format!("Created branch '{name}'"))
+            }
+            GitAction::RenameBranch { old_name, new_name } => {
+                branch::rename_branch(repo, old_name, new_name)?;
+                Ok(format!("Renamed '{old_name}' to '{new_name}'"))
+            }
+            GitAction::DeleteBranch { name, remote } => {
+                let kind = if *remote {
+                    BranchKind::Remote
+                } else {
+                    BranchKind::Local
+                };
+                branch::delete_branch(repo, name, kind)?;
+                Ok(format!("Deleted branch '{name}'"))
+            }
+            // Only the command input can open the planner to edit the todo list.
+            GitAction::Rebase { .. } => Err(git2::Error::from_str(
+                "git rebase -i opens the rebase planner, run it last from the command input",
+            )),
+            GitAction::CherryPick { revisions } => {
+                let ids = revisions
+                    .iter()
+                    .map(|revision| {
+                        repo.revparse_single(revision)
+                            .and_then(|object| object.peel_to_commit())
+                            .map(|commit| commit.id())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                cherry_pick::cherry_pick(repo, &ids)
+            }
+            GitAction::Revert {
+                revisions,
+                mainline,
+                edit: false,
+            } => {
+                let steps = revisions
+                    .iter()
+                    .map(|revision| {
+                        let id = repo.revparse_single(revision)?.peel_to_commit()?.id();
+                        revert::revert_step(repo, id, *mainline)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                revert::revert(repo, steps)
+            }
+            // Only the command input can open the revert view to edit the messages.
+            GitAction::Revert { edit: true, .. } => Err(git2::Error::from_str(
+                "git revert without --no-edit opens the revert view, run it last from the \
+                 command input",
+            )),
+            GitAction::StashPush {
+                message,
+                include_untracked,
+                keep_index,
+            } => stash::save(repo, message.as_deref(), *include_untracked, *keep_index),
+            GitAction::StashApply { index, pop: false } => stash::apply(repo, *index),
+            GitAction::StashApply { index, pop: true } => stash::pop(repo, *index),
+            GitAction::StashDrop { index } => stash::drop(repo, *index),
+            GitAction::StashBranch { name, index } => stash::branch(repo, *index, name),
+            GitAction::CreateTag {
+                name,
+                target,
+                message,
+            } => {
+                let target = repo.revparse_single(target)?.peel_to_commit()?.id();
+                tag::create_tag(repo, name, target, message.as_deref())
+            }
+            GitAction::DeleteTag { name } => tag::delete_tag(repo, name),
+            GitAction::AddRemote { name, url } => remote::add(repo, name, url),
+            GitAction::RenameRemote { old_name, new_name } => {
+                remote::rename(repo, old_name, new_name)
+            }
+            GitAction::RemoveRemote { name } => remote::remove(repo, name),
+            GitAction::SetRemoteUrl { name, url, push } => remote::set_url(repo, name, url, *push),
+            GitAction::Merge { revision, mode } => merge::merge(repo, revision, *mode),
+            GitAction::MergeContinue => merge::commit_merge(repo),
+            GitAction::MergeAbort => merge::abort_merge(repo),
+            GitAction::Fetch { .. } | GitAction::Pull { .. } | GitAction::Push { .. } => Err(
+                git2::Error::from_str("Remote commands can't run here, run them from the log"),
+            ),
+            GitAction::Reset { mode, target } => {
+                let target = repo.revparse_single(target)?.peel_to_commit()?.id();
+                reset::reset(repo, target, *mode)
+            }
+            GitAction::Sequence { kind, command } => match command {
+                SequenceCommand::Continue => rebase::continue_sequence(repo, *kind),
+                SequenceCommand::Skip => rebase::skip_step(repo, *kind),
+                SequenceCommand::Abort => rebase::abort_sequence(repo, *kind),
+            },
+        }
+    }
+
+    /// Whether the action may leave an operation stopped, waiting on the user.
+    fn may_stop(&self) -> bool {
+        matches!(
+            self,
+            GitAction::CherryPick { .. }
+                | GitAction::Revert { .. }
+                | GitAction::Sequence { .. }
+                | GitAction::Merge { .. }
+                | GitAction::Pull { .. }
+        )
+    }
+}
+
+/// Splits the arguments of commands working on files into options and paths. Everything after
+/// `--` is a path, without it any argument which isn't an option or `HEAD` is.
+fn split_paths(args: &[String]) -> (Vec<String>, Vec<String>) {
+    match args.iter().position(|arg| arg == "--") {
+        Some(i) => (args[..i].to_vec(), args[i + 1..].to_vec()),
+        None => {
+            let (options, paths): (Vec<String>, Vec<String>) = args
+                .iter()
+                .cloned()
+                .partition(|arg| arg.starts_with('-') || arg == "HEAD");
+            (options, paths)
+        }
+    }
+}
+
+fn parse_commit_message(options: &[String]) -> Option<String> {
+    let mut paragraphs = Vec::new();
+    let mut options = options.iter();
+
+    while let Some(option) = options.next() {
+        if option == "-m" || option == "--message" {
+            paragraphs.push(options.next()?.to_owned());
+        } else if let Some(message) = option.strip_prefix("--message=") {
+            paragraphs.push(message.to_owned());
+        } else {
+            return None;
+        }
+    }
+
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+/// `--continue`, `--skip` or `--abort` of a rebase, cherry-pick or revert.
+fn parse_sequence(kind: SequenceKind, args: &[String]) -> Option<GitAction> {
+    let command = match args {
+        [option] => SequenceCommand::from_name(option.strip_prefix("--")?)?,
+        _ => return None,
+    };
+    Some(GitAction::Sequence { kind, command })
+}
+
+/// `git stash push|apply|pop|drop|branch`, with stashes given as `stash@{n}` or `n`.
+fn parse_stash(args: &[String]) -> Option<GitAction> {
+    let strs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let index = |stash: &[&str]| match stash {
+        [] => Some(0),
+        [name] => stash::parse_name(name),
+        _ => None,
+    };
+
+    match strs.as_slice() {
+        ["push", options @ ..] => parse_stash_push(options),
+        ["apply", stash @ ..] => Some(GitAction::StashApply {
+            index: index(stash)?,
+            pop: false,
+        }),
+        ["pop", stash @ ..] => Some(GitAction::StashApply {
+            index: index(stash)?,
+            pop: true,
+        }),
+        ["drop", stash @ ..] => Some(GitAction::StashDrop {
+            index: index(stash)?,
+        }),
+        ["branch", name, stash @ ..] if !name.starts_with('-') => Some(GitAction::StashBranch {
+            name: name.to_string(),
+            index: index(stash)?,
+        }),
+        _ => None,
+    }
+}
+
+/// The options of `git stash push`, paths to stash are not supported.
+fn parse_stash_push(options: &[&str]) -> Option<GitAction> {
+    let mut message = None;
+    let (mut include_untracked, mut keep_index) = (false, false);
+    let mut options = options.iter();
+
+    while let Some(option) = options.next() {
+        match *option {
+            "-u" | "--include-untracked" => include_untracked = true,
+            "-k" | "--keep-index" => keep_index = true,
+            "-m" | "--message" => message = Some(options.next()?.to_string()),
+            option => message = Some(option.strip_prefix("--message=")?.to_owned()),
+        }
+    }
+
+    Some(GitAction::StashPush {
+        message,
+        include_untracked,
+        keep_index,
+    })
+}
+
+/// `git tag [-a] [-m <message>]... <name> [<revision>]` or `git tag -d <name>`.
+fn parse_tag(args: &[String]) -> Option<GitAction> {
+    if let [delete, name] = args {
+        if delete == "-d" || delete == "--delete" {
+            return Some(GitAction::DeleteTag {
+                name: name.to_owned(),
+            });
+        }
+    }
+
+    let mut paragraphs = Vec::new();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-a" || arg == "--annotate" {
+            continue;
+        } else if arg == "-m" || arg == "--message" {
+            paragraphs.push(args.next()?.to_owned());
+        } else if let Some(message) = arg.strip_prefix("--message=") {
+            paragraphs.push(message.to_owned());
+        } else if arg.starts_with('-') {
+            return None;
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+
+    let (name, target) = match positional.as_slice() {
+        [name] => (name, "HEAD"),
+        [name, target] => (name, *target),
+        _ => return None,
+    };
+    Some(GitAction::CreateTag {
+        name: name.to_string(),
+        target: target.to_owned(),
+        message: (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n")),
+    })
+}
+
+fn parse_remote(args: &[String]) -> Option<GitAction> {
+    let strs: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match strs.as_slice() {
+        ["add", name, url] => Some(GitAction::AddRemote {
+            name: name.to_string(),
+            url: url.to_string(),
+        }),
+        ["rename", old_name, new_name] => Some(GitAction::RenameRemote {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        }),
+        ["remove" | "rm", name] => Some(GitAction::RemoveRemote {
+            name: name.to_string(),
+        }),
+        ["set-url", name, url] | ["set-url", "--push", name, url] => {
+            Some(GitAction::SetRemoteUrl {
+                name: name.to_string(),
+                url: url.to_string(),
+                push: strs[1] == "--push",
+            })
+        }
+        _ => None,
+    }
+}
+
+/// `git merge [--ff | --no-ff | --ff-only] <revision>`, `git merge --continue` and
+/// `git merge --abort`.
+fn parse_merge(args: &[String]) -> Option<GitAction> {
+    let strs: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match strs.as_slice() {
+        ["--continue"] => Some(GitAction::MergeContinue),
+        ["--abort"] => Some(GitAction::MergeAbort),
+        [revision] if !revision.starts_with('-') => Some(GitAction::Merge {
+            revision: revision.to_string(),
+            mode: None,
+        }),
+        [option, revision] if !revision.starts_with('-') => Some(GitAction::Merge {
+            revision: revision.to_string(),
+            mode: Some(MergeMode::from_name(option.strip_prefix("--")?)?),
+        }),
+        _ => None,
+    }
+}
+
+/// `git pull [--ff | --no-ff | --ff-only] [<remote>]`, a refspec to pull isn't supported.
+fn parse_pull(args: &[String]) -> Option<GitAction> {
+    let (options, remote): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+    let mode = match options.as_slice() {
+        [] => None,
+        [option] => Some(MergeMode::from_name(option.strip_prefix("--")?)?),
+        _ => return None,
+    };
+
+    match remote.as_slice() {
+        [] => Some(GitAction::Pull { remote: None, mode }),
+        [remote] => Some(GitAction::Pull {
+            remote: Some(remote.to_string()),
+            mode,
+        }),
+        _ => None,
+    }
+}
+
+/// `git push [-f | --force] [<remote> [<branch>...]]`, branches being pushed to the branches
+/// of the same name.
+fn parse_push(args: &[String]) -> Option<GitAction> {
+    let (options, rest): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+    let force = match options.as_slice() {
+        [] => false,
+        [force] if *force == "-f" || *force == "--force" => true,
+        _ => return None,
+    };
+    if rest.iter().any(|branch| branch.contains(':')) {
+        return None;
+    }
+
+    let (remote, branches) = match rest.split_first() {
+        Some((remote, branches)) => (Some(remote.to_string()), branches),
+        None => (None, &[][..]),
+    };
+    Some(GitAction::Push {
+        remote,
+        branches: branches.iter().map(|branch| branch.to_string()).collect(),
+        force,
+    })
+}
+
+/// `git reset [--soft | --mixed | --hard] <revision>`, moving the branch.
+fn parse_reset(args: &[String]) -> Option<GitAction> {
+    let (options, revisions): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+
+    let mode = match options.as_slice() {
+        [] => ResetMode::Mixed,
+        [option] => ResetMode::from_name(option.strip_prefix("--")?)?,
+        _ => return None,
+    };
+    match revisions.as_slice() {
+        [target] => Some(GitAction::Reset {
+            mode,
+            target: target.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// `git revert [--no-edit] [-m <parent>] <revision>...`.
+fn parse_revert(args: &[String]) -> Option<GitAction> {
+    let mut edit = true;
+    let mut mainline = None;
+    let mut revisions = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--no-edit" {
+            edit = false;
+        } else if arg == "-m" || arg == "--mainline" {
+            mainline = Some(args.next()?.parse().ok()?);
+        } else if let Some(parent) = arg.strip_prefix("--mainline=") {
+            mainline = Some(parent.parse().ok()?);
+        } else if arg.starts_with('-') {
+            return None;
+        } else {
+            revisions.push(arg.to_owned());
+        }
+    }
+
+    if revisions.is_empty() {
+        return None;
+    }
+    Some(GitAction::Revert {
+        revisions,
+        mainline,
+        edit,
+    })
+}
+
+/// The side `git checkout-index -f --stage=<n>` writes, stage 1 being the base.
+fn parse_stage(options: &[String]) -> Option<ConflictSide> {
+    let strs: Vec<&str> = options.iter().map(String::as_str).collect();
+    let stage = match strs.as_slice() {
+        ["-f" | "--force", stage] | [stage, "-f" | "--force"] => stage.strip_prefix("--stage=")?,
+        _ => return None,
+    };
+
+    match stage {
+        "1" => Some(ConflictSide::Base),
+        "2" => Some(ConflictSide::Ours),
+        "3" => Some(ConflictSide::Theirs),
+        _ => None,
+    }
+}
+
+fn parse_branch(options: &[String]) -> Option<GitAction> {
+    let strs: Vec<&str> = options.iter().map(String::as_str).collect();
+
+    match strs.as_slice() {
+        [name] if !name.starts_with('-') => Some(GitAction::CreateBranch {
+            name: name.to_string(),
+        }),
+        ["-m", old_name, new_name] => Some(GitAction::RenameBranch {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        }),
+        ["-d" | "-D", name] => Some(GitAction::DeleteBranch {
+            name: name.to_string(),
+            remote: false,
+        }),
+        ["-d" | "-D", "-r", name] | ["-r", "-d" | "-D", name] => Some(GitAction::DeleteBranch {
+            name: name.to_string(),
+            remote: true,
+        }),
+        _ => None,
+    }
+}
+
+/// Shell command line equivalent to running every action in order.
+pub fn command_line(actions: &[GitAction]) -> String {
+    actions
+        .iter()
+        .map(|action| join_argv(&action.to_argv()))
+        .collect::<Vec<String>>()
+        .join(" && ")
+}
+
+/// Parses a command line as produced by [`command_line`], possibly edited by the user.
+pub fn parse_command_line(line: &str) -> Result<Vec<GitAction>, String> {
+    parse_words(&args::tokenize(line)?)
+}
+
+/// Parses a command line already split into words, with `&&` separating the commands.
+pub fn parse_words(words: &[String]) -> Result<Vec<GitAction>, String> {
+    words
+        .split(|word| word == "&&")
+        .filter(|argv| !argv.is_empty())
+        .map(GitAction::from_argv)
+        .collect()
+}
+
+fn join_argv(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| args::quote(arg))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Runs `actions` in order, stopping at the first one to fail. Like a failing command of a
+/// shell's `&&` chain, an operation which stopped for the user skips the actions after it.
+pub fn execute_all(repo: &Repository, actions: &[GitAction]) -> Result<String, git2::Error> {
+    execute_all_with(repo, actions, |action| action.execute(repo))
+}
+
+/// [`execute_all`] with the actions run by `execute`, for views which run some of them their
+/// own way.
+pub fn execute_all_with(
+    repo: &Repository,
+    actions: &[GitAction],
+    mut execute: impl FnMut(&GitAction) -> Result<String, git2::Error>,
+) -> Result<String, git2::Error> {
+    let mut summaries = Vec::new();
+    for (i, action) in actions.iter().enumerate() {
+        summaries.push(execute(action)?);
+
+        let left = actions.len() - i - 1;
+        if left > 0
+            && action.may_stop()
+            && (RebaseState::load(repo)?.is_some() || merge::in_progress(repo).is_some())
+        {
+            summaries.push(format!("{left} command(s) after it didn't run"));
+            break;
+        }
+    }
+
+    Ok(summaries.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    /// One action of every variant, with paths and messages needing quotes.
+    fn every_action() -> Vec<GitAction> {
+        let paths = strings(&["src/main.rs", "notes with spaces.txt"]);
+        vec![
+            GitAction::Add {
+                paths: paths.clone(),
+            },
+            GitAction::Unstage {
+                paths: paths.clone(),
+            },
+            GitAction::Restore {
+                paths: paths.clone(),
+                from_head: false,
+            },
+            GitAction::Restore {
+                paths: paths.clone(),
+                from_head: true,
+            },
+            GitAction::Clean {
+                paths: paths.clone(),
+            },
+            GitAction::Remove {
+                paths: paths.clone(),
+            },
+            GitAction::CheckoutSide {
+                side: ConflictSide::Ours,
+                paths: paths.clone(),
+            },
+            GitAction::CheckoutSide {
+                side: ConflictSide::Theirs,
+                paths: paths.clone(),
+            },
+            GitAction::CheckoutSide {
+                side: ConflictSide::Base,
+                paths,
+            },
+            GitAction::Commit {
+                message: "Fix the \"quoted\" bug\n\nIt's fixed.".to_owned(),
+            },
+            GitAction::Checkout {
+                branch: "main".to_owned(),
+                remote: false,
+            },
+            GitAction::Checkout {
+                branch: "origin/main".to_owned(),
+                remote: true,
+            },
+            GitAction::CreateBranch {
+                name: "topic".to_owned(),
+            },
+            GitAction::RenameBranch {
+                old_name: "topic".to_owned(),
+                new_name: "feature".to_owned(),
+            },
+            GitAction::DeleteBranch {
+                name: "topic".to_owned(),
+                remote: false,
+            },
+            GitAction::DeleteBranch {
+                name: "origin/topic".to_owned(),
+                remote: true,
+            },
+            GitAction::Rebase {
+                onto: "HEAD~3".to_owned(),
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Rebase,
+                command: SequenceCommand::Continue,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Rebase,
+                command: SequenceCommand::Skip,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Rebase,
+                command: SequenceCommand::Abort,
+            },
+            GitAction::CherryPick {
+                revisions: strings(&["topic~2", "1a2b3c4"]),
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::CherryPick,
+                command: SequenceCommand::Continue,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::CherryPick,
+                command: SequenceCommand::Skip,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::CherryPick,
+                command: SequenceCommand::Abort,
+            },
+            GitAction::Revert {
+                revisions: strings(&["HEAD", "topic~1"]),
+                mainline: None,
+                edit: false,
+            },
+            GitAction::Revert {
+                revisions: strings(&["1a2b3c4"]),
+                mainline: Some(2),
+                edit: true,
+            },
+            GitAction::StashPush {
+                message: Some("work in progress".to_owned()),
+                include_untracked: true,
+                keep_index: true,
+            },
+            GitAction::StashPush {
+                message: None,
+                include_untracked: false,
+                keep_index: false,
+            },
+            GitAction::StashApply {
+                index: 0,
+                pop: false,
+            },
+            GitAction::StashApply {
+                index: 2,
+                pop: true,
+            },
+            GitAction::StashDrop { index: 1 },
+            GitAction::StashBranch {
+                name: "topic".to_owned(),
+                index: 3,
+            },
+            GitAction::CreateTag {
+                name: "v1.0".to_owned(),
+                target: "HEAD~1".to_owned(),
+                message: None,
+            },
+            GitAction::CreateTag {
+                name: "v1.1".to_owned(),
+                target: "1a2b3c4".to_owned(),
+                message: Some("Release 1.1\n\nWith \"quoted\" notes.".to_owned()),
+            },
+            GitAction::DeleteTag {
+                name: "v1.0".to_owned(),
+            },
+            GitAction::AddRemote {
+                name: "upstream".to_owned(),
+                url: "https://example.com/volnita.git".to_owned(),
+            },
+            GitAction::RenameRemote {
+                old_name: "origin".to_owned(),
+                new_name: "fork".to_owned(),
+            },
+            GitAction::RemoveRemote {
+                name: "fork".to_owned(),
+            },
+            GitAction::SetRemoteUrl {
+                name: "origin".to_owned(),
+                url: "git@example.com:volnita.git".to_owned(),
+                push: false,
+            },
+            GitAction::SetRemoteUrl {
+                name: "origin".to_owned(),
+                url: "git@example.com:fork.git".to_owned(),
+                push: true,
+            },
+            GitAction::SetRemoteUrl {
+                name: "origin".to_owned(),
+                url: String::new(),
+                push: true,
+            },
+            GitAction::Merge {
+                revision: "topic".to_owned(),
+                mode: None,
+            },
+            GitAction::Merge {
+                revision: "origin/main".to_owned(),
+                mode: Some(MergeMode::NoFastForward),
+            },
+            GitAction::MergeContinue,
+            GitAction::MergeAbort,
+            GitAction::Fetch { remote: None },
+            GitAction::Fetch {
+                remote: Some("upstream".to_owned()),
+            },
+            GitAction::Pull {
+                remote: None,
+                mode: None,
+            },
+            GitAction::Pull {
+                remote: Some("origin".to_owned()),
+                mode: Some(MergeMode::FastForwardOnly),
+            },
+            GitAction::Push {
+                remote: None,
+                branches: Vec::new(),
+                force: false,
+            },
+            GitAction::Push {
+                remote: Some("origin".to_owned()),
+                branches: strings(&["main", "topic"]),
+                force: true,
+            },
+            GitAction::Reset {
+                mode: ResetMode::Soft,
+                target: "HEAD~1".to_owned(),
+            },
+            GitAction::Reset {
+                mode: ResetMode::Mixed,
+                target: "origin/main".to_owned(),
+            },
+            GitAction::Reset {
+                mode: ResetMode::Hard,
+                target: "1a2b3c4".to_owned(),
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Revert,
+                command: SequenceCommand::Continue,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Revert,
+                command: SequenceCommand::Skip,
+            },
+            GitAction::Sequence {
+                kind: SequenceKind::Revert,
+                command: SequenceCommand::Abort,
+            },
+        ]
+    }
+
+    #[test]
+    fn argv_round_trips() {
+        for action in every_action() {
+            let argv = action.to_argv();
+            assert_eq!(GitAction::from_argv(&argv), Ok(action));
+        }
+    }
+
+    #[test]
+    fn command_line_round_trips() {
+        let actions = every_action();
+        assert_eq!(parse_command_line(&command_line(&actions)), Ok(actions));
+    }
+
+    #[test]
+    fn rejects_unsupported_and_extra_options() {
+        for line in [
+            "git rm --cached -f -- file",
+            "git rm -- file",
+            "git clean -n -f -- file",
+            "git clean -- file",
+            "git add -p -- file",
+            "git reset --hard -- file",
+            "git checkout -b topic",
+            "git checkout --ours --theirs -- file",
+            "git checkout-index --stage=1 -- file",
+            "git checkout-index -f --stage=all -- file",
+            "git restore --source HEAD~1 -- file",
+            "git branch -m topic",
+            "git commit --amend -m message",
+            "git rebase main",
+            "git rebase -i --autosquash main",
+            "git rebase --continue --skip",
+            "git rebase --edit-todo",
+            "git cherry-pick",
+            "git cherry-pick -x main",
+            "git cherry-pick --quit",
+            "git revert --no-edit",
+            "git revert -m one HEAD",
+            "git revert --no-commit HEAD",
+            "git reset --hard",
+            "git reset --soft --hard HEAD~1",
+            "git reset --keep HEAD~1",
+            "git stash",
+            "git stash push -- file",
+            "git stash apply stash@{x}",
+            "git stash drop 0 1",
+            "git stash show",
+            "git tag",
+            "git tag -l v1.0",
+            "git tag -f v1.0 HEAD",
+            "git tag -d",
+            "git remote",
+            "git remote add -f origin url",
+            "git remote set-url --add origin url",
+            "git config --unset user.name",
+            "git config user.name me",
+            "git merge",
+            "git merge --squash topic",
+            "git merge topic main",
+            "git fetch --all",
+            "git pull --rebase",
+            "git pull --ff --no-ff",
+            "git pull origin main",
+            "git push origin main:release",
+            "git push --tags",
+            "git gc",
+        ] {
+            let argv = args::tokenize(line).unwrap();
+            assert!(GitAction::from_argv(&argv).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn reads_stashes_by_number() {
+        let parse = |line: &str| GitAction::from_argv(&args::tokenize(line).unwrap());
+
+        assert_eq!(
+            parse("git stash pop 1"),
+            Ok(GitAction::StashApply {
+                index: 1,
+                pop: true
+            })
+        );
+        assert_eq!(
+            parse("git stash drop"),
+            Ok(GitAction::StashDrop { index: 0 })
+        );
+    }
+
+    #[test]
+    fn reports_what_is_wrong() {
+        let parse = |line: &str| GitAction::from_argv(&args::tokenize(line).unwrap()).err();
+
+        assert_eq!(
+            parse("ls -la"),
+            Some("Expected a git command, found 'ls'".to_owned())
+        );
+        assert_eq!(
+            parse("git reset a.txt b.txt"),
+            Some("Put the paths to unstage after `--` in git reset".to_owned())
+        );
+        assert_eq!(
+            parse("git add --"),
+            Some("No paths given to git add".to_owned())
+        );
+        assert_eq!(
+            parse("git rm --cached -f -- file"),
+            Some("Unsupported git command: git rm --cached -f -- file".to_owned())
+        );
+    }
+}
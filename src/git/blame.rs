@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use git2::{BlameOptions, Oid, Repository};
+
+/// One line of a file with the commit which last changed it.
+pub struct BlameLine {
+    pub commit: Oid,
+    pub author: String,
+    /// When the line was authored, in seconds since the epoch.
+    pub time: i64,
+    /// Path of the file in `commit`, which differs from the blamed path when the file was
+    /// renamed since.
+    pub path: String,
+    pub content: String,
+}
+
+/// Blames every line of `path` as it is in the commit `at`.
+pub fn load_blame(repo: &Repository, path: &str, at: Oid) -> Result<Vec<BlameLine>, git2::Error> {
+    let commit = repo.find_commit(at)?;
+    let entry = commit
+        .tree()?
+        .get_path(Path::new(path))
+        .map_err(|_| git2::Error::from_str(&format!("{path} isn't in {}", &at.to_string()[..7])))?;
+    let blob = entry
+        .to_object(repo)?
+        .into_blob()
+        .map_err(|_| git2::Error::from_str(&format!("{path} isn't a file")))?;
+    if blob.is_binary() {
+        return Err(git2::Error::from_str(&format!(
+            "{path} is a binary file, it can't be blamed"
+        )));
+    }
+
+    let mut options = BlameOptions::new();
+    options.newest_commit(at);
+    let blame = repo.blame_file(Path::new(path), Some(&mut options))?;
+
+    let content = String::from_utf8_lossy(blob.content());
+    let mut lines = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        // Lines are counted from 1 by git2.
+        let hunk = blame.get_line(i + 1).ok_or_else(|| {
+            git2::Error::from_str(&format!("Line {} of {path} wasn't blamed", i + 1))
+        })?;
+        let signature = hunk.final_signature();
+
+        lines.push(BlameLine {
+            commit: hunk.final_commit_id(),
+            author: String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+            time: signature.when().seconds(),
+            path: hunk
+                .path()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_owned()),
+            content: line.to_owned(),
+        });
+    }
+
+    Ok(lines)
+}
+
+/// Checks that `path` can be blamed in the commit `at` without blaming it yet.
+pub fn check_path(repo: &Repository, path: &str, at: Oid) -> Result<(), git2::Error> {
+    let tree = repo.find_commit(at)?.tree()?;
+    match tree.get_path(Path::new(path)) {
+        Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => Ok(()),
+        Ok(_) => Err(git2::Error::from_str(&format!("{path} isn't a file"))),
+        Err(_) => Err(git2::Error::from_str(&format!(
+            "{path} isn't in {}",
+            &at.to_string()[..7]
+        ))),
+    }
+}
+
+/// The first parent of the commit which last changed a line, where blaming goes on from to
+/// see the line's earlier history.
+pub fn parent_of(repo: &Repository, line: &BlameLine) -> Result<Oid, git2::Error> {
+    let commit = repo.find_commit(line.commit)?;
+    commit.parent_id(0).map_err(|_| {
+        git2::Error::from_str(&format!(
+            "{} is the first commit, there is nothing before it",
+            &line.commit.to_string()[..7]
+        ))
+    })
+}
+
+/// How long ago `time` was, like "3 days" or "2 years", `now` and `time` being seconds since
+/// the epoch.
+pub fn format_age(now: i64, time: i64) -> String {
+    let seconds = (now - time).max(0);
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_owned(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86_399 => (seconds / 3600, "hour"),
+        86_400..=2_591_999 => (seconds / 86_400, "day"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+
+    match count {
+        1 => format!("1 {unit}"),
+        count => format!("{count} {unit}s"),
+    }
+}
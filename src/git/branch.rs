@@ -0,0 +1,166 @@
+use git2::{build::CheckoutBuilder, Branch, BranchType, Repository};
+
+use super::commit::head_commit;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BranchKind {
+    Local,
+    Remote,
+}
+
+impl BranchKind {
+    fn branch_type(&self) -> BranchType {
+        match self {
+            BranchKind::Local => BranchType::Local,
+            BranchKind::Remote => BranchType::Remote,
+        }
+    }
+}
+
+pub struct BranchEntry {
+    pub name: String,
+    pub kind: BranchKind,
+    pub is_head: bool,
+    pub upstream: Option<String>,
+    /// Commits ahead of and behind the upstream, when there is one.
+    pub ahead_behind: Option<(usize, usize)>,
+    pub short_id: String,
+    pub summary: String,
+}
+
+/// Lists local branches followed by remote-tracking branches, each sorted by name.
+pub fn load_branches(repo: &Repository) -> Result<Vec<BranchEntry>, git2::Error> {
+    let mut entries = Vec::new();
+
+    for branch in repo.branches(None)? {
+        let (branch, branch_type) = branch?;
+        let name = match branch.name()? {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+
+        // `origin/HEAD` is a symbolic ref to the remote's default branch, not a branch itself.
+        if branch.get().symbolic_target().is_some() {
+            continue;
+        }
+
+        let kind = match branch_type {
+            BranchType::Local => BranchKind::Local,
+            BranchType::Remote => BranchKind::Remote,
+        };
+
+        let commit = branch.get().peel_to_commit().ok();
+        let upstream = branch.upstream().ok();
+        let ahead_behind = match (&upstream, branch.get().target()) {
+            (Some(up), Some(local)) => up
+                .get()
+                .target()
+                .and_then(|remote| repo.graph_ahead_behind(local, remote).ok()),
+            _ => None,
+        };
+
+        entries.push(BranchEntry {
+            name,
+            kind,
+            is_head: branch.is_head(),
+            upstream: upstream
+                .as_ref()
+                .and_then(|up| up.name().ok().flatten().map(str::to_owned)),
+            ahead_behind,
+            short_id: commit
+                .as_ref()
+                .map(|c| c.id().to_string()[..7].to_owned())
+                .unwrap_or_default(),
+            summary: commit
+                .as_ref()
+                .and_then(|c| c.summary().map(str::to_owned))
+                .unwrap_or_default(),
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        (a.kind == BranchKind::Remote, &a.name).cmp(&(b.kind == BranchKind::Remote, &b.name))
+    });
+    Ok(entries)
+}
+
+/// Checks out a branch. Checking out a remote-tracking branch creates a local branch of the
+/// same name tracking it, or switches to that local branch if it already exists.
+pub fn checkout_branch(
+    repo: &Repository,
+    name: &str,
+    kind: BranchKind,
+) -> Result<String, git2::Error> {
+    let local = match kind {
+        BranchKind::Local => repo.find_branch(name, BranchType::Local)?,
+        BranchKind::Remote => {
+            let local_name = name.split_once('/').map(|(_, name)| name).unwrap_or(name);
+
+            match repo.find_branch(local_name, BranchType::Local) {
+                Ok(branch) => branch,
+                Err(_) => {
+                    let remote = repo.find_branch(name, BranchType::Remote)?;
+                    let commit = remote.get().peel_to_commit()?;
+                    let mut branch = repo.branch(local_name, &commit, false)?;
+                    branch.set_upstream(Some(name))?;
+                    branch
+                }
+            }
+        }
+    };
+
+    switch_to(repo, &local)?;
+    Ok(local.name()?.unwrap_or_default().to_owned())
+}
+
+fn switch_to(repo: &Repository, branch: &Branch) -> Result<(), git2::Error> {
+    let refname = branch
+        .get()
+        .name()
+        .ok_or_else(|| git2::Error::from_str("Branch name is not valid UTF-8"))?;
+    let tree = branch.get().peel_to_tree()?;
+
+    repo.checkout_tree(tree.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.set_head(refname)
+}
+
+/// Creates a branch at HEAD without checking it out.
+pub fn create_branch(repo: &Repository, name: &str) -> Result<(), git2::Error> {
+    let commit = head_commit(repo)?
+        .ok_or_else(|| git2::Error::from_str("Cannot create a branch before the first commit"))?;
+
+    repo.branch(name, &commit, false)?;
+    Ok(())
+}
+
+pub fn rename_branch(repo: &Repository, old_name: &str, new_name: &str) -> Result<(), git2::Error> {
+    let mut branch = repo.find_branch(old_name, BranchType::Local)?;
+    branch.rename(new_name, false)?;
+    Ok(())
+}
+
+/// Deletes a local branch, or the remote-tracking ref of a remote branch. The remote itself is
+/// left untouched.
+pub fn delete_branch(repo: &Repository, name: &str, kind: BranchKind) -> Result<(), git2::Error> {
+    let mut branch = repo.find_branch(name, kind.branch_type())?;
+
+    if branch.is_head() {
+        return Err(git2::Error::from_str(
+            "Cannot delete the branch which is currently checked out",
+        ));
+    }
+
+    branch.delete()
+}
+
+/// Finds out whether `name` is a local or a remote-tracking branch, preferring local branches
+/// like `git checkout` does.
+pub fn branch_kind(repo: &Repository, name: &str) -> Result<BranchKind, git2::Error> {
+    if repo.find_branch(name, BranchType::Local).is_ok() {
+        Ok(BranchKind::Local)
+    } else if repo.find_branch(name, BranchType::Remote).is_ok() {
+        Ok(BranchKind::Remote)
+    } else {
+        Err(git2::Error::from_str(&format!("No branch named '{name}'")))
+    }
+}
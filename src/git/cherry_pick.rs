@@ -0,0 +1,33 @@
+use git2::{Oid, Repository};
+
+use super::rebase::{self, RebaseAction, RebaseStep, SequenceKind};
+
+/// Cherry-picks `ids` onto HEAD in the given order, keeping their authors and messages. A
+/// conflict stops the cherry-pick until it is continued, skipped or aborted.
+pub fn cherry_pick(repo: &Repository, ids: &[Oid]) -> Result<String, git2::Error> {
+    let mut steps = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        let commit = repo.find_commit(*id)?;
+        if commit.parent_count() > 1 {
+            return Err(git2::Error::from_str(&format!(
+                "{} is a merge commit, which can't be cherry-picked",
+                &id.to_string()[..7]
+            )));
+        }
+
+        steps.push(RebaseStep {
+            action: RebaseAction::Pick,
+            id: id.to_string(),
+            message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+            mainline: None,
+        });
+    }
+
+    if steps.is_empty() {
+        return Err(git2::Error::from_str("No commits to cherry-pick"));
+    }
+
+    let head = repo.head()?.peel_to_commit()?.id();
+    rebase::start_sequence(repo, SequenceKind::CherryPick, head, steps)
+}
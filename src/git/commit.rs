@@ -0,0 +1,163 @@
+use std::fs;
+
+use git2::{Commit, ErrorCode, Oid, Repository, RepositoryState, Signature, Time};
+
+use super::diff::{self, FileDiff};
+
+/// Commits the current index on top of HEAD, signed with the `user.name` and `user.email`
+/// from the repository's git config. Comment lines starting with `#` are stripped from the
+/// message like `git commit` does. During a merge the merged commits become parents too,
+/// which concludes the merge.
+pub fn create_commit(repo: &Repository, message: &str) -> Result<Oid, git2::Error> {
+    let message = git2::message_prettify(message, Some(b'#'))?;
+    if message.trim().is_empty() {
+        return Err(git2::Error::from_str(
+            "Aborting commit due to empty commit message",
+        ));
+    }
+
+    let signature = repo.signature()?;
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Err(git2::Error::from_str(
+            "Resolve the conflicts and stage the files before committing",
+        ));
+    }
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let mut parents: Vec<Commit> = head_commit(repo)?.into_iter().collect();
+    let merging = repo.state() == RepositoryState::Merge;
+
+    if merging {
+        for id in merge_heads(repo)? {
+            parents.push(repo.find_commit(id)?);
+        }
+    } else if let Some(parent) = parents.first() {
+        if parent.tree_id() == tree.id() {
+            return Err(git2::Error::from_str("Nothing staged to commit"));
+        }
+    }
+
+    let parents: Vec<&Commit> = parents.iter().collect();
+    let id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parents,
+    )?;
+    if merging {
+        repo.cleanup_state()?;
+    }
+    Ok(id)
+}
+
+/// The commits being merged, read from `MERGE_HEAD` since git2 only lists them through a
+/// mutable repository.
+fn merge_heads(repo: &Repository) -> Result<Vec<Oid>, git2::Error> {
+    let text = fs::read_to_string(repo.path().join("MERGE_HEAD"))
+        .map_err(|err| git2::Error::from_str(&format!("Failed to read MERGE_HEAD: {err}")))?;
+
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Oid::from_str(line.trim()))
+        .collect()
+}
+
+/// The commit HEAD points to, `None` on a branch without any commits yet.
+pub fn head_commit(repo: &Repository) -> Result<Option<Commit<'_>>, git2::Error> {
+    match repo.head() {
+        Ok(head) => head.peel_to_commit().map(Some),
+        Err(err) if err.code() == ErrorCode::UnbornBranch || err.code() == ErrorCode::NotFound => {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub struct CommitDetail {
+    pub id: Oid,
+    pub author: String,
+    pub author_date: String,
+    pub committer: String,
+    pub commit_date: String,
+    pub parents: Vec<Oid>,
+    pub message: String,
+    /// `Key: value` trailers at the end of the message, like `Signed-off-by`.
+    pub trailers: Vec<(String, String)>,
+    /// Changes against the first parent.
+    pub files: Vec<FileDiff>,
+}
+
+pub fn load_commit_detail(repo: &Repository, id: Oid) -> Result<CommitDetail, git2::Error> {
+    let commit = repo.find_commit(id)?;
+    let message = String::from_utf8_lossy(commit.message_bytes()).into_owned();
+
+    let trailers = match git2::message_trailers_strs(&message) {
+        Ok(trailers) => trailers
+            .iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let author = commit.author();
+    let committer = commit.committer();
+
+    Ok(CommitDetail {
+        id,
+        author: signature_name(&author),
+        author_date: format_time(author.when()),
+        committer: signature_name(&committer),
+        commit_date: format_time(committer.when()),
+        parents: commit.parent_ids().collect(),
+        message,
+        trailers,
+        files: diff::commit_diff(repo, id)?,
+    })
+}
+
+fn signature_name(signature: &Signature) -> String {
+    format!(
+        "{} <{}>",
+        String::from_utf8_lossy(signature.name_bytes()),
+        String::from_utf8_lossy(signature.email_bytes())
+    )
+}
+
+/// Formats a commit time in its own timezone, like `2023-05-01 14:03:12 +0200`.
+pub fn format_time(time: Time) -> String {
+    let offset = time.offset_minutes() as i64;
+    let local = time.seconds() + offset * 60;
+
+    let days = local.div_euclid(86_400);
+    let seconds = local.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} {}{:02}{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60,
+    )
+}
+
+/// Converts days since 1970-01-01 to a date of the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
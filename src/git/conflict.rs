@@ -0,0 +1,222 @@
+use std::{fs, ops::Range};
+
+use git2::{Oid, Repository};
+
+/// A file with conflicting versions in the index.
+pub struct ConflictEntry {
+    pub path: String,
+    /// Blob of the common ancestor, `None` when the file didn't exist in it.
+    pub ancestor: Option<Oid>,
+    /// Blob on the side being committed to, HEAD.
+    pub ours: Option<Oid>,
+    /// Blob on the side being brought in, like the commit being picked.
+    pub theirs: Option<Oid>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+    /// The common ancestor both sides changed.
+    Base,
+}
+
+impl ConflictSide {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConflictSide::Ours => "ours",
+            ConflictSide::Theirs => "theirs",
+            ConflictSide::Base => "base",
+        }
+    }
+}
+
+impl ConflictEntry {
+    /// How the two sides disagree, in the words `git status` uses.
+    pub fn describe(&self) -> &'static str {
+        match (
+            self.ancestor.is_some(),
+            self.ours.is_some(),
+            self.theirs.is_some(),
+        ) {
+            (true, true, true) => "both modified",
+            (false, true, true) => "both added",
+            (true, false, true) => "deleted by us",
+            (true, true, false) => "deleted by them",
+            (false, true, false) => "added by us",
+            (false, false, true) => "added by them",
+            _ => "both deleted",
+        }
+    }
+
+    fn side(&self, side: ConflictSide) -> Option<Oid> {
+        match side {
+            ConflictSide::Ours => self.ours,
+            ConflictSide::Theirs => self.theirs,
+            ConflictSide::Base => self.ancestor,
+        }
+    }
+}
+
+/// Lists the conflicted files of the index.
+pub fn load_conflicts(repo: &Repository) -> Result<Vec<ConflictEntry>, git2::Error> {
+    let index = repo.index()?;
+    let mut entries = Vec::new();
+
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let path = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .next()
+            .unwrap_or_default();
+
+        entries.push(ConflictEntry {
+            path,
+            ancestor: conflict.ancestor.map(|entry| entry.id),
+            ours: conflict.our.map(|entry| entry.id),
+            theirs: conflict.their.map(|entry| entry.id),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Writes one side's version of the whole file to the working tree, deleting the file when
+/// that side deleted it, where git refuses. The file stays conflicted until it is marked
+/// resolved.
+pub fn checkout_side(
+    repo: &Repository,
+    entry: &ConflictEntry,
+    side: ConflictSide,
+) -> Result<(), git2::Error> {
+    let file = workdir(repo)?.join(&entry.path);
+
+    let written = match entry.side(side) {
+        Some(id) => fs::write(&file, repo.find_blob(id)?.content()),
+        None => fs::remove_file(&file),
+    };
+    written.map_err(|err| git2::Error::from_str(&format!("Failed to write {}: {err}", entry.path)))
+}
+
+/// One conflict in a file written with conflict markers, as line numbers counted from 0.
+pub struct ConflictHunk {
+    /// Line of the `<<<<<<<` marker.
+    pub start: usize,
+    /// Line of the `>>>>>>>` marker.
+    pub end: usize,
+    pub ours: Range<usize>,
+    /// Only there when the file was written in the diff3 style, like merges do.
+    pub base: Option<Range<usize>>,
+    pub theirs: Range<usize>,
+}
+
+impl ConflictHunk {
+    fn lines(&self, side: ConflictSide) -> Option<Range<usize>> {
+        match side {
+            ConflictSide::Ours => Some(self.ours.clone()),
+            ConflictSide::Theirs => Some(self.theirs.clone()),
+            ConflictSide::Base => self.base.clone(),
+        }
+    }
+}
+
+/// Finds the conflicts marked in `content`. Unfinished conflicts, like ones edited by hand
+/// halfway, are left out.
+pub fn conflict_hunks(content: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut hunks = Vec::new();
+    let mut section = Section::Outside;
+    // Line of the opening marker, and the first line of the current section.
+    let (mut start, mut from) = (0, 0);
+    let mut ours = 0..0;
+    let mut base = None;
+
+    for (i, line) in content.lines().enumerate() {
+        section = match (line.get(..7), section) {
+            (Some("<<<<<<<"), _) => {
+                (start, from, base) = (i, i + 1, None);
+                Section::Ours
+            }
+            (Some("|||||||"), Section::Ours) => {
+                (ours, from) = (from..i, i + 1);
+                Section::Base
+            }
+            (Some("======="), Section::Ours) => {
+                (ours, from) = (from..i, i + 1);
+                Section::Theirs
+            }
+            (Some("======="), Section::Base) => {
+                (base, from) = (Some(from..i), i + 1);
+                Section::Theirs
+            }
+            (Some(">>>>>>>"), Section::Theirs) => {
+                hunks.push(ConflictHunk {
+                    start,
+                    end: i,
+                    ours: ours.clone(),
+                    base: base.take(),
+                    theirs: from..i,
+                });
+                Section::Outside
+            }
+            (_, section) => section,
+        };
+    }
+
+    hunks
+}
+
+/// Resolves the `index`th conflict marked in the file by keeping one side's lines of it.
+/// Returns how many conflicts are left in the file.
+pub fn take_hunk_side(
+    repo: &Repository,
+    path: &str,
+    index: usize,
+    side: ConflictSide,
+) -> Result<usize, git2::Error> {
+    let file = workdir(repo)?.join(path);
+    let content = fs::read(&file)
+        .map_err(|err| git2::Error::from_str(&format!("Failed to read {path}: {err}")))?;
+    // Only the chosen lines are replaced, any other bytes of the file must be written back
+    // untouched, which a lossy conversion wouldn't do.
+    let text = std::str::from_utf8(&content).map_err(|_| {
+        git2::Error::from_str(&format!(
+            "{path} isn't valid UTF-8, take a side of the whole file instead"
+        ))
+    })?;
+
+    let hunks = conflict_hunks(text);
+    let hunk = hunks
+        .get(index)
+        .ok_or_else(|| git2::Error::from_str(&format!("{path} has no conflict {}", index + 1)))?;
+    let kept = hunk.lines(side).ok_or_else(|| {
+        git2::Error::from_str("This conflict wasn't written with its base version")
+    })?;
+
+    // Split keeping the line endings, so the rest of the file is written back as it was.
+    let lines: Vec<&[u8]> = content.split_inclusive(|&byte| byte == b'\n').collect();
+    let resolved = [
+        &lines[..hunk.start],
+        &lines[kept],
+        &lines[(hunk.end + 1).min(lines.len())..],
+    ]
+    .concat()
+    .concat();
+
+    fs::write(&file, resolved)
+        .map_err(|err| git2::Error::from_str(&format!("Failed to write {path}: {err}")))?;
+    Ok(hunks.len() - 1)
+}
+
+fn workdir(repo: &Repository) -> Result<&std::path::Path, git2::Error> {
+    repo.workdir()
+        .ok_or_else(|| git2::Error::from_str("The repository has no working tree"))
+}
@@ -0,0 +1,439 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use git2::{Config, Cred, CredentialType, Repository};
+
+use crate::traits::transfer_ui::TransferUi;
+
+/// How often the user is asked again after the remote refused what they entered.
+const MAX_PROMPTS: usize = 3;
+
+#[derive(Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// What a credential helper is told about the remote, in the git credential protocol.
+struct CredentialRequest {
+    protocol: String,
+    /// Host with the port, if the URL has one.
+    host: String,
+    path: String,
+    username: Option<String>,
+}
+
+impl CredentialRequest {
+    /// Splits `scheme://[user@]host[:port]/path`. Other URLs, like the scp-like syntax of SSH,
+    /// aren't handled by credential helpers.
+    fn new(url: &str, username: Option<&str>) -> Option<CredentialRequest> {
+        let (protocol, rest) = url.split_once("://")?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user.split(':').next().unwrap_or(user)), host),
+            None => (None, authority),
+        };
+
+        Some(CredentialRequest {
+            protocol: protocol.to_owned(),
+            host: host.to_owned(),
+            path: path.to_owned(),
+            username: username.or(user).map(str::to_owned),
+        })
+    }
+
+    fn url(&self) -> String {
+        format!("{}://{}/{}", self.protocol, self.host, self.path)
+    }
+
+    /// The request as helpers read it on their input, ending with an empty line.
+    fn describe(&self, credentials: Option<&Credentials>, with_path: bool) -> String {
+        let mut lines = vec![
+            format!("protocol={}", self.protocol),
+            format!("host={}", self.host),
+        ];
+        if with_path && !self.path.is_empty() {
+            lines.push(format!("path={}", self.path));
+        }
+        match credentials {
+            Some(credentials) => {
+                lines.push(format!("username={}", credentials.username));
+                lines.push(format!("password={}", credentials.password));
+            }
+            None => lines.extend(self.username.iter().map(|name| format!("username={name}"))),
+        }
+
+        lines.push(String::new());
+        lines.push(String::new());
+        lines.join("\n")
+    }
+}
+
+/// The helpers configured for `request` in `credential.helper` and `credential.<url>.helper`,
+/// in the order git runs them. An empty value clears the helpers configured before it.
+fn helpers(config: &Config, request: &CredentialRequest) -> Vec<String> {
+    let url = request.url();
+    let mut helpers = Vec::new();
+
+    let mut entries = match config.entries(Some(r"^credential\..*helper$")) {
+        Ok(entries) => entries,
+        Err(_) => return helpers,
+    };
+    // The entries of git2 lend each entry out, so they are walked by hand instead of iterated.
+    while let Some(Ok(entry)) = entries.next() {
+        let scope = entry
+            .name()
+            .and_then(|name| name.strip_prefix("credential."))
+            .and_then(|name| name.strip_suffix("helper"))
+            .map(|scope| scope.trim_end_matches('.'));
+        let applies = match scope {
+            Some("") => true,
+            Some(scope) => url.starts_with(scope),
+            None => false,
+        };
+        if !applies {
+            continue;
+        }
+
+        match entry.value() {
+            Some("") => helpers.clear(),
+            Some(helper) => helpers.push(helper.to_owned()),
+            None => {}
+        }
+    }
+
+    helpers
+}
+
+/// Runs `helper` with `action` the way git does: `!` starts a shell command, an absolute path is
+/// run as is and anything else names a `git credential-<helper>` command.
+fn run_helper(helper: &str, action: &str, input: &str) -> Option<String> {
+    let program = helper.split_whitespace().next().unwrap_or_default();
+    let command = match helper.strip_prefix('!') {
+        Some(command) => command.to_owned(),
+        None if Path::new(program).is_absolute() => helper.to_owned(),
+        None => format!("git credential-{helper}"),
+    };
+
+    // Anything the helper prints besides its answer would end up over the interface.
+    let mut child = shell(&format!("{command} {action}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child.stdin.take()?.write_all(input.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Runs `command` through the shell of the platform, as helpers may be given with arguments
+/// or as shell snippets.
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Asks the helpers in turn for the username and password of `request`, the first complete
+/// answer wins.
+fn fill(config: &Config, request: &CredentialRequest) -> Option<Credentials> {
+    let input = request.describe(None, use_http_path(config));
+
+    helpers(config, request).iter().find_map(|helper| {
+        let output = run_helper(helper, "get", &input)?;
+        let value = |key: &str| {
+            output
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(str::to_owned)
+        };
+
+        Some(Credentials {
+            username: value("username").or_else(|| request.username.to_owned())?,
+            password: value("password")?,
+        })
+    })
+}
+
+/// Tells every helper to `store` or `erase` the credentials.
+fn report(config: &Config, request: &CredentialRequest, credentials: &Credentials, action: &str) {
+    let input = request.describe(Some(credentials), use_http_path(config));
+    for helper in helpers(config, request) {
+        run_helper(&helper, action, &input);
+    }
+}
+
+fn use_http_path(config: &Config) -> bool {
+    config.get_bool("credential.useHttpPath").unwrap_or(false)
+}
+
+/// Answers the credential requests of one fetch or push: keys from the SSH agent, passwords from
+/// the credential helpers and else from the user. Whether the last password worked is reported
+/// back to the helpers.
+pub struct Authenticator {
+    config: Option<Config>,
+    /// Password given for the last request, not yet known to work.
+    pending: Option<(CredentialRequest, Credentials)>,
+    tried_agent: bool,
+    tried_helpers: bool,
+    prompts: usize,
+}
+
+impl Authenticator {
+    pub fn new(repo: &Repository) -> Self {
+        Self {
+            config: repo.config().ok(),
+            pending: None,
+            tried_agent: false,
+            tried_helpers: false,
+            prompts: 0,
+        }
+    }
+
+    /// Credentials for `url`, called by git2 again every time the last ones were refused.
+    pub fn credentials(
+        &mut self,
+        url: &str,
+        username: Option<&str>,
+        allowed: CredentialType,
+        ui: &mut dyn TransferUi,
+    ) -> Result<Cred, git2::Error> {
+        // Being asked again means the password given last didn't work.
+        self.reject();
+
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) && !self.tried_agent {
+            self.tried_agent = true;
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            let request = CredentialRequest::new(url, username);
+            let from_helper = match (&self.config, &request, self.tried_helpers) {
+                (Some(config), Some(request), false) => fill(config, request),
+                _ => None,
+            };
+            self.tried_helpers = true;
+
+            let credentials = match from_helper {
+                Some(credentials) => credentials,
+                None if self.prompts < MAX_PROMPTS => {
+                    self.prompts += 1;
+                    let username = request
+                        .as_ref()
+                        .and_then(|request| request.username.as_deref());
+                    ui.credentials(url, username)
+                        .ok_or_else(|| git2::Error::from_str("Authentication was cancelled"))?
+                }
+                None => {
+                    return Err(git2::Error::from_str(&format!(
+                        "Authentication to {url} failed"
+                    )))
+                }
+            };
+
+            let cred = Cred::userpass_plaintext(&credentials.username, &credentials.password);
+            if let Some(request) = request {
+                self.pending = Some((request, credentials));
+            }
+            return cred;
+        }
+
+        if allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+        Err(git2::Error::from_str(&format!(
+            "Volnita can't authenticate to {url}"
+        )))
+    }
+
+    /// Finishes a transfer, asking the helpers to store the password when it went through and
+    /// to forget it when the remote refused it.
+    pub fn finish<T>(&mut self, result: &Result<T, git2::Error>) {
+        match result {
+            Ok(_) => {
+                if let (Some(config), Some((request, credentials))) =
+                    (&self.config, self.pending.take())
+                {
+                    report(config, &request, &credentials, "store");
+                }
+            }
+            Err(err) if err.code() == git2::ErrorCode::Auth => self.reject(),
+            Err(_) => {}
+        }
+    }
+
+    fn reject(&mut self) {
+        if let (Some(config), Some((request, credentials))) = (&self.config, self.pending.take()) {
+            report(config, &request, &credentials, "erase");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use git2::{ErrorClass, ErrorCode};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::transfer::Progress;
+
+    const URL: &str = "https://example.com/volnita.git";
+
+    struct NoUi;
+
+    impl TransferUi for NoUi {
+        fn start(&mut self, _title: &str) {}
+
+        fn progress(&mut self, _progress: &Progress) {}
+
+        fn credentials(&mut self, _url: &str, _username: Option<&str>) -> Option<Credentials> {
+            None
+        }
+    }
+
+    /// A config file with `config` in a temporary directory.
+    fn config(dir: &TempDir, config: &str) -> Config {
+        let path = dir.path().join("config");
+        fs::write(&path, config).unwrap();
+        Config::open(&path).unwrap()
+    }
+
+    /// A helper answering `get` with alice's password and writing down every action it is
+    /// asked for with the request it got, in `log` next to it.
+    #[cfg(unix)]
+    fn helper(dir: &TempDir) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.path().join("helper");
+        let log = dir.path().join("log");
+        let script = format!(
+            "#!/bin/sh\n\
+             echo \"$1\" >> '{}'\n\
+             cat >> '{}'\n\
+             [ \"$1\" = get ] && printf 'username=alice\\npassword=secret\\n'\n\
+             exit 0\n",
+            log.display(),
+            log.display()
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.display().to_string()
+    }
+
+    fn log(dir: &TempDir) -> String {
+        fs::read_to_string(dir.path().join("log")).unwrap_or_default()
+    }
+
+    fn authenticator(config: Config) -> Authenticator {
+        Authenticator {
+            config: Some(config),
+            pending: None,
+            tried_agent: false,
+            tried_helpers: false,
+            prompts: 0,
+        }
+    }
+
+    #[test]
+    fn helpers_follow_resets_and_url_scopes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(
+            &dir,
+            "[credential]\n\
+             \thelper = cleared\n\
+             \thelper =\n\
+             \thelper = store\n\
+             [credential \"https://example.com\"]\n\
+             \thelper = scoped\n\
+             [credential \"https://example.org\"]\n\
+             \thelper = elsewhere\n",
+        );
+
+        let request = CredentialRequest::new(URL, None).unwrap();
+        assert_eq!(helpers(&config, &request), ["store", "scoped"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fill_reads_the_answer_of_a_helper() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(
+            &dir,
+            &format!("[credential]\n\thelper = {}\n", helper(&dir)),
+        );
+
+        let request = CredentialRequest::new(URL, None).unwrap();
+        let credentials = fill(&config, &request).unwrap();
+        assert_eq!(credentials.username, "alice");
+        assert_eq!(credentials.password, "secret");
+        assert_eq!(log(&dir), "get\nprotocol=https\nhost=example.com\n\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finish_stores_credentials_which_worked() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(
+            &dir,
+            &format!("[credential]\n\thelper = {}\n", helper(&dir)),
+        );
+
+        let mut authenticator = authenticator(config);
+        authenticator
+            .credentials(URL, None, CredentialType::USER_PASS_PLAINTEXT, &mut NoUi)
+            .unwrap();
+        authenticator.finish(&Ok(()));
+
+        assert!(log(&dir).ends_with(
+            "store\nprotocol=https\nhost=example.com\nusername=alice\npassword=secret\n\n"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finish_erases_credentials_which_were_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(
+            &dir,
+            &format!("[credential]\n\thelper = {}\n", helper(&dir)),
+        );
+
+        let mut authenticator = authenticator(config);
+        authenticator
+            .credentials(URL, None, CredentialType::USER_PASS_PLAINTEXT, &mut NoUi)
+            .unwrap();
+        let refused: Result<(), git2::Error> = Err(git2::Error::new(
+            ErrorCode::Auth,
+            ErrorClass::Http,
+            "authentication failed",
+        ));
+        authenticator.finish(&refused);
+
+        let log = log(&dir);
+        assert!(log.ends_with(
+            "erase\nprotocol=https\nhost=example.com\nusername=alice\npassword=secret\n\n"
+        ));
+        assert!(!log.contains("store"));
+    }
+}
@@ -0,0 +1,404 @@
+use git2::{ApplyLocation, Delta, Diff, DiffFindOptions, DiffOptions, Oid, Patch, Repository};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+    /// Notes like "\ No newline at end of file".
+    Info,
+}
+
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The line as shown, without its line ending.
+    pub content: String,
+    /// The line as git found it, without the `\n` but with any `\r` and whatever bytes
+    /// aren't UTF-8, so patches built from it still match the file.
+    pub raw: Vec<u8>,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+}
+
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@` line, with any function context git found.
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+pub struct FileDiff {
+    /// Path after the change, or before it for deleted files.
+    pub path: String,
+    /// Path before the change when the file was renamed or copied.
+    pub old_path: Option<String>,
+    pub status: Delta,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl FileDiff {
+    /// Letter `git status --short` uses for the change.
+    pub fn status_char(&self) -> char {
+        match self.status {
+            Delta::Added | Delta::Untracked => 'A',
+            Delta::Deleted => 'D',
+            Delta::Renamed => 'R',
+            Delta::Copied => 'C',
+            Delta::Typechange => 'T',
+            _ => 'M',
+        }
+    }
+
+    /// Number of added and removed lines.
+    pub fn line_stats(&self) -> (usize, usize) {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .fold((0, 0), |(added, removed), line| match line.kind {
+                DiffLineKind::Added => (added + 1, removed),
+                DiffLineKind::Removed => (added, removed + 1),
+                _ => (added, removed),
+            })
+    }
+}
+
+/// Diff of a commit against its first parent, or against nothing for a root commit, with
+/// renames detected.
+pub fn commit_diff(repo: &Repository, id: Oid) -> Result<Vec<FileDiff>, git2::Error> {
+    let commit = repo.find_commit(id)?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&commit.tree()?),
+        Some(&mut DiffOptions::new()),
+    )?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    file_diffs(&diff)
+}
+
+/// Changes to `path` staged in the index, HEAD against the index.
+pub fn staged_diff(repo: &Repository, path: &str) -> Result<Option<FileDiff>, git2::Error> {
+    let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+    let diff = repo.diff_tree_to_index(
+        head_tree.as_ref(),
+        None,
+        Some(DiffOptions::new().pathspec(path)),
+    )?;
+
+    path_diff(&diff, path)
+}
+
+/// Changes to `path` which aren't staged yet, the index against the working tree. An untracked
+/// file shows its whole content as added.
+pub fn unstaged_diff(repo: &Repository, path: &str) -> Result<Option<FileDiff>, git2::Error> {
+    let diff = repo.diff_index_to_workdir(
+        None,
+        Some(
+            DiffOptions::new()
+                .pathspec(path)
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true),
+        ),
+    )?;
+
+    path_diff(&diff, path)
+}
+
+/// The diff of exactly `path`, as a pathspec may match other files too.
+fn path_diff(diff: &Diff, path: &str) -> Result<Option<FileDiff>, git2::Error> {
+    Ok(file_diffs(diff)?.into_iter().find(|file| file.path == path))
+}
+
+/// Builds a patch of the added and removed lines of `file` for which `selected(hunk, line)`
+/// is true, like `git add -p` does when editing a hunk. Unselected removed lines are kept as
+/// context and unselected added lines left out, so the patch applies to the old side of the
+/// diff. With `reverse` the patch undoes the selected lines instead and applies to the new
+/// side, which is how changes are unstaged or discarded.
+///
+/// Returns `None` when no changed line is selected.
+pub fn selection_patch(
+    file: &FileDiff,
+    reverse: bool,
+    selected: impl Fn(usize, usize) -> bool,
+) -> Result<Option<Vec<u8>>, git2::Error> {
+    if file.status != Delta::Modified || file.binary {
+        return Err(git2::Error::from_str(
+            "Hunks and lines can only be picked from modified text files",
+        ));
+    }
+
+    // The kind of line found on the side the patch applies to.
+    let kept = match reverse {
+        false => DiffLineKind::Removed,
+        true => DiffLineKind::Added,
+    };
+
+    let mut hunks = Vec::new();
+    // Lines the hunks written so far add to the file, moving the start of later hunks.
+    let mut offset: i64 = 0;
+
+    for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+        let mut body = Vec::new();
+        let mut changed = false;
+        let (mut old_lines, mut new_lines) = (0, 0);
+        // Whether the line before was written, "\ No newline" notes belong to it.
+        let mut written = false;
+
+        for (line_index, line) in hunk.lines.iter().enumerate() {
+            let is_selected = selected(hunk_index, line_index);
+            let origin = match line.kind {
+                DiffLineKind::Info => {
+                    if written {
+                        body.extend_from_slice(b"\\ No newline at end of file\n");
+                    }
+                    continue;
+                }
+                DiffLineKind::Context => ' ',
+                kind if is_selected => {
+                    changed = true;
+                    if kind == kept {
+                        '-'
+                    } else {
+                        '+'
+                    }
+                }
+                kind if kind == kept => ' ',
+                _ => {
+                    written = false;
+                    continue;
+                }
+            };
+
+            match origin {
+                ' ' => {
+                    old_lines += 1;
+                    new_lines += 1;
+                }
+                '-' => old_lines += 1,
+                _ => new_lines += 1,
+            }
+            written = true;
+            body.push(origin as u8);
+            body.extend_from_slice(&line.raw);
+            body.push(b'\n');
+        }
+
+        if !changed {
+            continue;
+        }
+
+        // Empty ranges start at the line before them, as in any unified diff.
+        let old_start = i64::from(match reverse {
+            false => hunk.old_start,
+            true => hunk.new_start,
+        });
+        let new_start = old_start
+            + offset
+            + match (old_lines, new_lines) {
+                (0, n) if n > 0 => 1,
+                (o, 0) if o > 0 => -1,
+                _ => 0,
+            };
+        offset += new_lines - old_lines;
+
+        hunks.extend_from_slice(
+            format!("@@ -{old_start},{old_lines} +{new_start},{new_lines} @@\n").as_bytes(),
+        );
+        hunks.append(&mut body);
+    }
+
+    if hunks.is_empty() {
+        return Ok(None);
+    }
+
+    let path = &file.path;
+    let mut patch =
+        format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n").into_bytes();
+    patch.append(&mut hunks);
+    Ok(Some(patch))
+}
+
+/// Applies a patch made by [`selection_patch`] to the index or the working tree.
+pub fn apply_patch(
+    repo: &Repository,
+    patch: &[u8],
+    location: ApplyLocation,
+) -> Result<(), git2::Error> {
+    let diff = Diff::from_buffer(patch)?;
+    repo.apply(&diff, location, None)
+}
+
+/// Splits `diff` into files, hunks and lines.
+pub fn file_diffs(diff: &Diff) -> Result<Vec<FileDiff>, git2::Error> {
+    let mut files = Vec::new();
+
+    for (index, delta) in diff.deltas().enumerate() {
+        let new_path = delta
+            .new_file()
+            .path()
+            .map(|p| p.to_string_lossy().into_owned());
+        let old_path = delta
+            .old_file()
+            .path()
+            .map(|p| p.to_string_lossy().into_owned());
+        let path = new_path.clone().or(old_path.clone()).unwrap_or_default();
+
+        let mut file = FileDiff {
+            old_path: old_path.filter(|old| *old != path),
+            path,
+            status: delta.status(),
+            binary: delta.flags().is_binary(),
+            hunks: Vec::new(),
+        };
+
+        if let Some(patch) = Patch::from_diff(diff, index)? {
+            for hunk_index in 0..patch.num_hunks() {
+                let (hunk, line_count) = patch.hunk(hunk_index)?;
+                let mut lines = Vec::with_capacity(line_count);
+
+                for line_index in 0..line_count {
+                    let line = patch.line_in_hunk(hunk_index, line_index)?;
+                    let kind = match line.origin() {
+                        '+' => DiffLineKind::Added,
+                        '-' => DiffLineKind::Removed,
+                        ' ' => DiffLineKind::Context,
+                        _ => DiffLineKind::Info,
+                    };
+
+                    let raw = line.content();
+                    let raw = raw.strip_suffix(b"\n").unwrap_or(raw);
+
+                    lines.push(DiffLine {
+                        kind,
+                        content: match kind {
+                            DiffLineKind::Info => String::from_utf8_lossy(line.content())
+                                .trim()
+                                .trim_start_matches('\\')
+                                .trim_start()
+                                .to_owned(),
+                            _ => String::from_utf8_lossy(raw)
+                                .trim_end_matches('\r')
+                                .to_owned(),
+                        },
+                        raw: raw.to_vec(),
+                        old_lineno: line.old_lineno(),
+                        new_lineno: line.new_lineno(),
+                    });
+                }
+
+                file.hunks.push(DiffHunk {
+                    header: String::from_utf8_lossy(hunk.header()).trim_end().to_owned(),
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines,
+                });
+            }
+        }
+
+        files.push(file);
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const OLD: &[u8] = b"one\r\ncaf\xe9\r\n\
+        three\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\nten\r\neleven\r\n";
+    const NEW: &[u8] = b"one\r\ncaf\xe9!\r\n\
+        three\r\nfour\r\nfive\r\nsix\r\nseven\r\neight\r\nnine\r\nten\r\nelf\r\n";
+
+    /// A repository with `file.txt` committed as `OLD` and changed to `NEW` in the working tree.
+    fn init() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("file.txt"), OLD).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        index.write().unwrap();
+        {
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = Signature::now("Test", "test@example.com").unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])
+                .unwrap();
+        }
+
+        fs::write(dir.path().join("file.txt"), NEW).unwrap();
+        (dir, repo)
+    }
+
+    fn staged_content(repo: &Repository) -> Vec<u8> {
+        let index = repo.index().unwrap();
+        let entry = index.get_path(Path::new("file.txt"), 0).unwrap();
+        repo.find_blob(entry.id).unwrap().content().to_vec()
+    }
+
+    #[test]
+    fn shows_lines_without_their_line_ending() {
+        let (_dir, repo) = init();
+        let file = unstaged_diff(&repo, "file.txt").unwrap().unwrap();
+
+        let added: Vec<_> = file.hunks[0]
+            .lines
+            .iter()
+            .filter(|line| line.kind == DiffLineKind::Added)
+            .collect();
+        assert_eq!(added[0].content, "caf\u{fffd}!");
+        assert_eq!(added[0].raw, b"caf\xe9!\r");
+    }
+
+    #[test]
+    fn stages_a_hunk_of_a_crlf_file_which_is_not_utf8() {
+        let (_dir, repo) = init();
+        let file = unstaged_diff(&repo, "file.txt").unwrap().unwrap();
+        assert_eq!(file.hunks.len(), 2);
+
+        let patch = selection_patch(&file, false, |hunk, _| hunk == 0)
+            .unwrap()
+            .unwrap();
+        apply_patch(&repo, &patch, ApplyLocation::Index).unwrap();
+
+        let expected = [NEW.strip_suffix(b"elf\r\n").unwrap(), b"eleven\r\n"].concat();
+        assert_eq!(staged_content(&repo), expected);
+    }
+
+    #[test]
+    fn discards_a_line_of_a_crlf_file() {
+        let (dir, repo) = init();
+        let file = unstaged_diff(&repo, "file.txt").unwrap().unwrap();
+
+        let hunk = &file.hunks[1];
+        let removed = hunk
+            .lines
+            .iter()
+            .position(|line| line.kind == DiffLineKind::Removed)
+            .unwrap();
+        let patch = selection_patch(&file, true, |h, l| h == 1 && l == removed)
+            .unwrap()
+            .unwrap();
+        apply_patch(&repo, &patch, ApplyLocation::WorkDir).unwrap();
+
+        let expected = [NEW.strip_suffix(b"elf\r\n").unwrap(), b"eleven\r\nelf\r\n"].concat();
+        assert_eq!(fs::read(dir.path().join("file.txt")).unwrap(), expected);
+    }
+}
@@ -0,0 +1,321 @@
+use git2::Oid;
+
+/// One character of the graph column. `color` is an index into the palette of the view
+/// drawing the graph, lanes keep their color for as long as they stay open.
+#[derive(Clone, Copy)]
+pub struct GraphCell {
+    pub symbol: char,
+    pub color: usize,
+}
+
+/// Graph column of a single commit row, the line holding the commit node and the line used to
+/// continue the open lanes when the row is taller than one line.
+pub struct GraphRow {
+    pub node_line: Vec<GraphCell>,
+    pub continuation_line: Vec<GraphCell>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Edges {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+}
+
+impl Edges {
+    fn symbol(&self) -> char {
+        match (self.up, self.down, self.left, self.right) {
+            (true, true, true, true) => '┼',
+            (true, true, true, false) => '┤',
+            (true, true, false, true) => '├',
+            (true, false, true, true) => '┴',
+            (false, true, true, true) => '┬',
+            (true, false, true, false) => '╯',
+            (true, false, false, true) => '╰',
+            (false, true, true, false) => '╮',
+            (false, true, false, true) => '╭',
+            (true, _, false, false) | (_, true, false, false) => '│',
+            (false, false, true, _) | (false, false, _, true) => '─',
+            (false, false, false, false) => ' ',
+        }
+    }
+}
+
+/// Assigns commits to lanes and draws the edges between them. Commits have to be fed in
+/// topological order, children before their parents.
+#[derive(Default)]
+pub struct GraphBuilder {
+    /// The commit each lane is waiting for along with the lane's color.
+    lanes: Vec<Option<(Oid, usize)>>,
+    next_color: usize,
+}
+
+impl GraphBuilder {
+    pub fn add_commit(&mut self, id: Oid, parents: &[Oid]) -> GraphRow {
+        let before = self.lanes.clone();
+
+        let col = match self.lanes.iter().position(|lane| lane_is(lane, id)) {
+            Some(col) => col,
+            None => self.open_lane(id),
+        };
+        let color = self.lanes[col].map(|(_, color)| color).unwrap_or_default();
+
+        // Other lanes waiting for this commit are children branching off here, they end now.
+        let mut edges_from = Vec::new();
+        for (i, lane) in self.lanes.iter_mut().enumerate() {
+            if i != col && lane_is(lane, id) {
+                edges_from.push((i, lane.map(|(_, color)| color).unwrap_or_default()));
+                *lane = None;
+            }
+        }
+
+        self.lanes[col] = parents.first().map(|parent| (*parent, color));
+
+        // Merge parents either join a lane already waiting for them or open a new lane.
+        let mut edges_to = Vec::new();
+        for parent in parents.iter().skip(1) {
+            let target = match self.lanes.iter().position(|lane| lane_is(lane, *parent)) {
+                Some(target) => target,
+                None => self.open_lane(*parent),
+            };
+            let target_color = self.lanes[target].map(|(_, c)| c).unwrap_or_default();
+            edges_to.push((target, target_color));
+        }
+
+        let width = before.len().max(self.lanes.len());
+        let mut edges = vec![Edges::default(); width];
+        let mut cell_colors: Vec<usize> = (0..width)
+            .map(|i| {
+                before
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .or_else(|| self.lanes.get(i).copied().flatten())
+                    .map(|(_, color)| color)
+                    .unwrap_or_default()
+            })
+            .collect();
+        let mut spacers: Vec<Option<usize>> = vec![None; width];
+
+        for (i, edge) in edges.iter_mut().enumerate() {
+            edge.up = before.get(i).map(Option::is_some).unwrap_or(false);
+            edge.down = self.lanes.get(i).map(Option::is_some).unwrap_or(false);
+        }
+
+        for (target, target_color) in edges_from.iter().chain(edges_to.iter()) {
+            let (start, end) = if *target > col {
+                (col, *target)
+            } else {
+                (*target, col)
+            };
+
+            for (i, edge) in edges.iter_mut().enumerate().take(end + 1).skip(start) {
+                if i != start {
+                    edge.left = true;
+                }
+                if i != end {
+                    edge.right = true;
+                }
+            }
+            for spacer in spacers.iter_mut().take(end).skip(start) {
+                *spacer = Some(*target_color);
+            }
+            if *target != col {
+                cell_colors[*target] = *target_color;
+            }
+        }
+
+        let mut node_line = Vec::with_capacity(width * 2);
+        for i in 0..width {
+            let symbol = if i == col { '●' } else { edges[i].symbol() };
+            let cell_color = if i == col { color } else { cell_colors[i] };
+            node_line.push(GraphCell {
+                symbol,
+                color: cell_color,
+            });
+
+            node_line.push(match spacers[i] {
+                Some(spacer_color) => GraphCell {
+                    symbol: '─',
+                    color: spacer_color,
+                },
+                None => GraphCell {
+                    symbol: ' ',
+                    color: 0,
+                },
+            });
+        }
+
+        while let Some(None) = self.lanes.last() {
+            self.lanes.pop();
+        }
+
+        let continuation_line = self
+            .lanes
+            .iter()
+            .flat_map(|lane| {
+                let cell = match lane {
+                    Some((_, color)) => GraphCell {
+                        symbol: '│',
+                        color: *color,
+                    },
+                    None => GraphCell {
+                        symbol: ' ',
+                        color: 0,
+                    },
+                };
+                [
+                    cell,
+                    GraphCell {
+                        symbol: ' ',
+                        color: 0,
+                    },
+                ]
+            })
+            .collect();
+
+        GraphRow {
+            node_line,
+            continuation_line,
+        }
+    }
+
+    /// Puts `id` in the first free lane, or a new lane on the right, with a fresh color.
+    fn open_lane(&mut self, id: Oid) -> usize {
+        let lane = Some((id, self.next_color));
+        self.next_color += 1;
+
+        match self.lanes.iter().position(Option::is_none) {
+            Some(free) => {
+                self.lanes[free] = lane;
+                free
+            }
+            None => {
+                self.lanes.push(lane);
+                self.lanes.len() - 1
+            }
+        }
+    }
+}
+
+fn lane_is(lane: &Option<(Oid, usize)>, id: Oid) -> bool {
+    matches!(lane, Some((lane_id, _)) if *lane_id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Commit, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::log;
+
+    fn init() -> (TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        (dir, repo)
+    }
+
+    /// Commits an empty tree on `parents` without moving any ref, `time` orders the commits
+    /// the walk can't tell apart by topology.
+    fn commit(repo: &Repository, message: &str, parents: &[Oid], time: i64) -> Oid {
+        let signature =
+            Signature::new("Volnita", "volnita@example.com", &Time::new(time, 0)).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<Commit> = parents
+            .iter()
+            .map(|id| repo.find_commit(*id).unwrap())
+            .collect();
+        let parents: Vec<&Commit> = parents.iter().collect();
+        repo.commit(None, &signature, &signature, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn branch(repo: &Repository, name: &str, id: Oid) {
+        repo.reference(&format!("refs/heads/{name}"), id, true, "")
+            .unwrap();
+    }
+
+    /// The message and graph column of every row of the log.
+    fn graph(repo: &Repository) -> Vec<(String, String)> {
+        log::load_log(repo)
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                let line: String = entry
+                    .graph
+                    .node_line
+                    .iter()
+                    .map(|cell| cell.symbol)
+                    .collect();
+                (entry.message, line.trim_end().to_owned())
+            })
+            .collect()
+    }
+
+    fn rows(rows: &[(&str, &str)]) -> Vec<(String, String)> {
+        rows.iter()
+            .map(|(message, line)| (message.to_string(), line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn fork_opens_a_lane_which_joins_at_the_base() {
+        let (_dir, repo) = init();
+        let base = commit(&repo, "base", &[], 1);
+        branch(&repo, "master", commit(&repo, "main", &[base], 2));
+        branch(&repo, "side", commit(&repo, "side", &[base], 3));
+
+        assert_eq!(
+            graph(&repo),
+            rows(&[("side", "●"), ("main", "│ ●"), ("base", "●─╯")])
+        );
+    }
+
+    #[test]
+    fn merge_opens_a_lane_for_the_second_parent() {
+        let (_dir, repo) = init();
+        let base = commit(&repo, "base", &[], 1);
+        let main = commit(&repo, "main", &[base], 2);
+        let side = commit(&repo, "side", &[base], 3);
+        branch(&repo, "master", commit(&repo, "merge", &[main, side], 4));
+
+        assert_eq!(
+            graph(&repo),
+            rows(&[
+                ("merge", "●─╮"),
+                ("side", "│ ●"),
+                ("main", "● │"),
+                ("base", "●─╯"),
+            ])
+        );
+    }
+
+    #[test]
+    fn commit_only_reachable_from_a_tag_gets_a_lane() {
+        let (_dir, repo) = init();
+        let base = commit(&repo, "base", &[], 1);
+        branch(&repo, "master", base);
+        let release = commit(&repo, "release", &[base], 2);
+        let signature = Signature::new("Volnita", "volnita@example.com", &Time::new(2, 0)).unwrap();
+        repo.tag(
+            "v1",
+            &repo.find_object(release, None).unwrap(),
+            &signature,
+            "v1",
+            false,
+        )
+        .unwrap();
+        // Tags of anything but commits have no place in the graph.
+        let blob = repo.blob(b"notes").unwrap();
+        repo.tag_lightweight("notes", &repo.find_object(blob, None).unwrap(), false)
+            .unwrap();
+
+        let entries = log::load_log(&repo).unwrap();
+        assert_eq!(entries[0].tags, vec!["v1".to_owned()]);
+        assert_eq!(graph(&repo), rows(&[("release", "●"), ("base", "●")]));
+    }
+}
@@ -0,0 +1,52 @@
+use git2::{Commit, Oid, Repository, Sort};
+
+use super::{
+    graph::{GraphBuilder, GraphRow},
+    tag,
+};
+
+pub struct LogEntry {
+    pub id: Oid,
+    pub message: String,
+    pub author: String,
+    pub graph: GraphRow,
+    /// Tags pointing at the commit.
+    pub tags: Vec<String>,
+}
+
+/// Walks the history reachable from HEAD, every local and remote-tracking branch and every tag in
+/// topological order, laying out the commit graph along the way.
+pub fn load_log(repo: &Repository) -> Result<Vec<LogEntry>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    // An unborn HEAD has nothing to walk, the branch globs still cover the rest.
+    let _ = walk.push_head();
+    walk.push_glob("refs/heads")?;
+    walk.push_glob("refs/remotes")?;
+    // Tags of commits no branch reaches still get a row, tags of trees or blobs are skipped.
+    walk.push_glob("refs/tags")?;
+
+    let mut tags = tag::tag_names_by_commit(repo)?;
+    let mut graph = GraphBuilder::default();
+    let mut entries = Vec::new();
+
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        let parents: Vec<Oid> = commit.parent_ids().collect();
+
+        entries.push(LogEntry {
+            id: commit.id(),
+            message: commit_message(&commit),
+            author: commit.author().name().unwrap_or_default().to_owned(),
+            graph: graph.add_commit(commit.id(), &parents),
+            tags: tags.remove(&commit.id()).unwrap_or_default(),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn commit_message(commit: &Commit) -> String {
+    String::from_utf8_lossy(commit.message_bytes()).into_owned()
+}
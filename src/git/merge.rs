@@ -0,0 +1,195 @@
+use git2::{
+    build::CheckoutBuilder, AnnotatedCommit, MergePreference, Repository, RepositoryState,
+    ResetType,
+};
+
+use super::{
+    commit,
+    rebase::RebaseState,
+    status::{self, FileState},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Fast-forwards when the branch is behind, creates a merge commit otherwise.
+    FastForward,
+    /// Always creates a merge commit.
+    NoFastForward,
+    /// Only fast-forwards, refusing branches which have diverged.
+    FastForwardOnly,
+}
+
+impl MergeMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MergeMode::FastForward => "ff",
+            MergeMode::NoFastForward => "no-ff",
+            MergeMode::FastForwardOnly => "ff-only",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MergeMode> {
+        match name {
+            "ff" => Some(MergeMode::FastForward),
+            "no-ff" => Some(MergeMode::NoFastForward),
+            "ff-only" => Some(MergeMode::FastForwardOnly),
+            _ => None,
+        }
+    }
+
+    /// The mode `merge.ff` asks for.
+    fn configured(preference: MergePreference) -> MergeMode {
+        if preference.is_no_fast_forward() {
+            MergeMode::NoFastForward
+        } else if preference.is_fastforward_only() {
+            MergeMode::FastForwardOnly
+        } else {
+            MergeMode::FastForward
+        }
+    }
+}
+
+/// Merges `revision` into the current branch, following `merge.ff` when no mode is given.
+/// Conflicts leave the merge in progress until it is committed with [`commit_merge`] or
+/// aborted.
+pub fn merge(
+    repo: &Repository,
+    revision: &str,
+    mode: Option<MergeMode>,
+) -> Result<String, git2::Error> {
+    if let Some(state) = RebaseState::load(repo)? {
+        return Err(git2::Error::from_str(&state.describe()));
+    }
+    if repo.state() != RepositoryState::Clean {
+        return Err(git2::Error::from_str(&format!(
+            "Finish the {:?} in progress before merging",
+            repo.state()
+        )));
+    }
+    if status::load_status(repo)?
+        .iter()
+        .any(|entry| entry.state != FileState::Untracked)
+    {
+        return Err(git2::Error::from_str(
+            "Commit or stash your changes before merging",
+        ));
+    }
+
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(git2::Error::from_str(
+            "HEAD is detached, check out the branch to merge into",
+        ));
+    }
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+
+    let (theirs, name) = annotated_commit(repo, revision)?;
+    let (analysis, preference) = repo.merge_analysis(&[&theirs])?;
+    let mode = mode.unwrap_or_else(|| MergeMode::configured(preference));
+
+    if analysis.is_up_to_date() {
+        return Ok(format!("'{branch}' is already up to date with {name}"));
+    }
+    if analysis.is_fast_forward() && mode != MergeMode::NoFastForward {
+        let commit = repo.find_commit(theirs.id())?;
+        repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+        repo.head()?
+            .set_target(commit.id(), &format!("merge {name}: Fast-forward"))?;
+        return Ok(format!(
+            "Fast-forwarded '{branch}' to {}",
+            &commit.id().to_string()[..7]
+        ));
+    }
+    if mode == MergeMode::FastForwardOnly {
+        return Err(git2::Error::from_str(&format!(
+            "'{branch}' and {name} have diverged, they can't be merged with --ff-only"
+        )));
+    }
+
+    // The base version is written into the conflicts, so it can be picked per conflict.
+    let mut checkout = CheckoutBuilder::new();
+    checkout
+        .safe()
+        .allow_conflicts(true)
+        .conflict_style_diff3(true);
+    repo.merge(&[&theirs], None, Some(&mut checkout))?;
+
+    let conflicts = repo.index()?.conflicts()?.count();
+    if conflicts > 0 {
+        return Ok(format!(
+            "Merging {name} stopped with conflicts in {conflicts} file(s)"
+        ));
+    }
+    commit_merge(repo)?;
+    Ok(format!("Merged {name} into '{branch}'"))
+}
+
+/// The commit `revision` points to, keeping the name of a branch for the merge message.
+fn annotated_commit<'r>(
+    repo: &'r Repository,
+    revision: &str,
+) -> Result<(AnnotatedCommit<'r>, String), git2::Error> {
+    if let Ok(reference) = repo.resolve_reference_from_short_name(revision) {
+        if reference.is_branch() || reference.is_remote() {
+            let name = format!("'{}'", reference.shorthand().unwrap_or(revision));
+            return Ok((repo.reference_to_annotated_commit(&reference)?, name));
+        }
+    }
+
+    let id = repo.revparse_single(revision)?.peel_to_commit()?.id();
+    Ok((
+        repo.find_annotated_commit(id)?,
+        id.to_string()[..7].to_owned(),
+    ))
+}
+
+/// Commits the merge in progress with the message git prepared for it, once its conflicts
+/// are resolved.
+pub fn commit_merge(repo: &Repository) -> Result<String, git2::Error> {
+    if repo.state() != RepositoryState::Merge {
+        return Err(git2::Error::from_str("No merge in progress"));
+    }
+    if repo.index()?.has_conflicts() {
+        return Err(git2::Error::from_str(
+            "Resolve the conflicts and stage the files before committing the merge",
+        ));
+    }
+
+    let id = commit::create_commit(repo, &repo.message()?)?;
+    Ok(format!("Committed the merge as {}", &id.to_string()[..7]))
+}
+
+/// Throws away the merge in progress, resolved conflicts included.
+pub fn abort_merge(repo: &Repository) -> Result<String, git2::Error> {
+    if repo.state() != RepositoryState::Merge {
+        return Err(git2::Error::from_str("No merge in progress"));
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+    Ok("Aborted the merge".to_owned())
+}
+
+/// The first line of the message of the merge in progress, like "Merge branch 'topic'", or
+/// `None` when there is no merge in progress.
+pub fn in_progress(repo: &Repository) -> Option<String> {
+    if repo.state() != RepositoryState::Merge {
+        return None;
+    }
+
+    let message = repo.message().unwrap_or_default();
+    match message.lines().next() {
+        Some(title) if !title.trim().is_empty() => Some(title.trim().to_owned()),
+        _ => Some("Merge".to_owned()),
+    }
+}
+
+/// What the merge in progress is waiting for, shown until it is committed or aborted.
+pub fn describe(repo: &Repository) -> Option<String> {
+    in_progress(repo).map(|title| {
+        format!(
+            "{title} in progress: resolve the conflicts, then `merge continue` or `merge abort`"
+        )
+    })
+}
@@ -1,4 +1,25 @@
-use std::path::Path;
+pub mod action;
+pub mod blame;
+pub mod branch;
+pub mod cherry_pick;
+pub mod commit;
+pub mod conflict;
+pub mod credentials;
+pub mod diff;
+pub mod graph;
+pub mod log;
+pub mod merge;
+pub mod names;
+pub mod rebase;
+pub mod remote;
+pub mod reset;
+pub mod revert;
+pub mod stash;
+pub mod status;
+pub mod tag;
+pub mod transfer;
+
+use std::path::{Path, PathBuf};
 
 use git2::Repository;
 
@@ -22,6 +43,8 @@ impl GitRepo {
                 path: repo.path().to_path_buf(),
                 name: get_repo_name(repo.path()),
                 repo_url: get_repo_url(&repo).unwrap_or_default(),
+                // Only looked up in the saved repositories, see `remote::default_remote`.
+                preferred_remote: None,
             },
             git2_repository: repo,
         };
@@ -51,8 +74,15 @@ fn get_repo_name(path: &Path) -> String {
     name
 }
 
-fn get_repo_url(repo: &Repository) -> Option<String> {
-    let remote = repo.find_remote("origin").ok()?;
+/// Path the repository is saved under in the recent repositories, its working directory.
+pub fn saved_path(repo: &Repository) -> PathBuf {
+    let path = repo.path().to_string_lossy();
+    PathBuf::from(path.split(".git/").next().unwrap_or_default())
+}
+
+/// URL of the remote the repository uses by default, see [`remote::default_remote`].
+pub fn get_repo_url(repo: &Repository) -> Option<String> {
+    let remote = repo.find_remote(&remote::default_remote(repo)?).ok()?;
 
     let url = remote.url()?.to_owned();
 
@@ -0,0 +1,58 @@
+use git2::{BranchType, Repository};
+
+/// Names of local or remote-tracking branches, skipping symbolic refs like `origin/HEAD`.
+pub fn branch_names(repo: &Repository, branch_type: BranchType) -> Vec<String> {
+    let branches = match repo.branches(Some(branch_type)) {
+        Ok(branches) => branches,
+        Err(_) => return Vec::new(),
+    };
+
+    branches
+        .filter_map(|branch| branch.ok())
+        .filter(|(branch, _)| branch.get().symbolic_target().is_none())
+        .filter_map(|(branch, _)| branch.name().ok().flatten().map(str::to_owned))
+        .collect()
+}
+
+pub fn tag_names(repo: &Repository) -> Vec<String> {
+    repo.tag_names(None)
+        .map(|tags| tags.iter().flatten().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+pub fn remote_names(repo: &Repository) -> Vec<String> {
+    repo.remotes()
+        .map(|remotes| remotes.iter().flatten().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+/// Paths of every file in the index.
+pub fn index_paths(repo: &Repository) -> Vec<String> {
+    let index = match repo.index() {
+        Ok(index) => index,
+        Err(_) => return Vec::new(),
+    };
+
+    index
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .collect()
+}
+
+/// Special refs like `HEAD` and `ORIG_HEAD` which currently exist, with the usual relative
+/// revisions of `HEAD`.
+pub fn special_revisions(repo: &Repository) -> Vec<String> {
+    let mut revisions = Vec::new();
+
+    if repo.head().is_ok() {
+        revisions.extend(["HEAD", "HEAD^", "HEAD~1"].map(str::to_owned));
+    }
+
+    for name in ["ORIG_HEAD", "FETCH_HEAD", "MERGE_HEAD", "CHERRY_PICK_HEAD"] {
+        if repo.refname_to_id(name).is_ok() {
+            revisions.push(name.to_owned());
+        }
+    }
+
+    revisions
+}
@@ -0,0 +1,499 @@
+use std::{fs, path::PathBuf};
+
+use git2::{
+    build::CheckoutBuilder, Commit, Oid, Repository, RepositoryState, ResetType, RevertOptions,
+    Sort,
+};
+use serde::{Deserialize, Serialize};
+
+use super::status;
+
+/// Where a stopped rebase, cherry-pick or revert is kept, next to git's own state in the `.git`
+/// directory.
+const STATE_FILE: &str = "volnita-rebase.toml";
+
+/// Operations running their steps through the sequencer of this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SequenceKind {
+    #[default]
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+impl SequenceKind {
+    /// Name of the operation, which is also the command continuing it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SequenceKind::Rebase => "rebase",
+            SequenceKind::CherryPick => "cherry-pick",
+            SequenceKind::Revert => "revert",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Edit => "edit",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+
+    /// Whether the commit is folded into the one before it.
+    pub fn melds(&self) -> bool {
+        matches!(self, RebaseAction::Squash | RebaseAction::Fixup)
+    }
+}
+
+/// One line of the rebase plan, like a line of git's todo list.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RebaseStep {
+    pub action: RebaseAction,
+    pub id: String,
+    /// Message of the new commit, the original one unless it was reworded.
+    pub message: String,
+    /// Parent of a reverted merge whose side is kept, counting from 1.
+    #[serde(default)]
+    pub mainline: Option<u32>,
+}
+
+impl RebaseStep {
+    pub fn oid(&self) -> Result<Oid, git2::Error> {
+        Oid::from_str(&self.id)
+    }
+
+    pub fn short_id(&self) -> &str {
+        &self.id[..7.min(self.id.len())]
+    }
+
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// A rebase, cherry-pick or revert which stopped for conflicts or to edit a commit.
+#[derive(Serialize, Deserialize)]
+pub struct RebaseState {
+    #[serde(default)]
+    pub kind: SequenceKind,
+    /// Full name of the branch being rebased, `None` when HEAD was detached.
+    pub branch: Option<String>,
+    pub orig_head: String,
+    pub onto: String,
+    /// Steps still to run.
+    pub todo: Vec<RebaseStep>,
+    /// Whether the first step of `todo` stopped with conflicts.
+    pub conflicted: bool,
+}
+
+impl RebaseState {
+    fn path(repo: &Repository) -> PathBuf {
+        repo.path().join(STATE_FILE)
+    }
+
+    /// The stopped rebase, cherry-pick or revert of `repo`, if there is one.
+    pub fn load(repo: &Repository) -> Result<Option<RebaseState>, git2::Error> {
+        let text = match fs::read_to_string(Self::path(repo)) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(state_error("read", err)),
+        };
+
+        toml::from_str(&text)
+            .map(Some)
+            .map_err(|err| state_error("read", err))
+    }
+
+    fn save(&self, repo: &Repository) -> Result<(), git2::Error> {
+        let text = toml::to_string_pretty(self).map_err(|err| state_error("save", err))?;
+        fs::write(Self::path(repo), text).map_err(|err| state_error("save", err))
+    }
+
+    fn remove(repo: &Repository) -> Result<(), git2::Error> {
+        match fs::remove_file(Self::path(repo)) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(state_error("remove", err)),
+        }
+    }
+
+    /// What the operation is waiting for, shown until it is continued or aborted.
+    pub fn describe(&self) -> String {
+        let name = self.kind.name();
+        let title = match self.kind {
+            SequenceKind::Rebase => "Rebase",
+            SequenceKind::CherryPick => "Cherry-pick",
+            SequenceKind::Revert => "Revert",
+        };
+        match (self.conflicted, self.todo.first()) {
+            (true, Some(step)) => format!(
+                "{title} stopped at {} with conflicts: resolve and stage them, then \
+                 `{name} continue`, `{name} skip` or `{name} abort`",
+                step.short_id()
+            ),
+            _ => format!(
+                "{title} stopped to edit a commit: `{name} continue` when done, or `{name} abort`"
+            ),
+        }
+    }
+}
+
+fn state_error(action: &str, err: impl std::fmt::Display) -> git2::Error {
+    git2::Error::from_str(&format!("Failed to {action} the rebase state: {err}"))
+}
+
+/// Plan picking every commit between `base` and HEAD, oldest first. Merge commits are left
+/// out, like `git rebase -i` does.
+pub fn plan(repo: &Repository, base: Oid) -> Result<Vec<RebaseStep>, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?;
+    if head.id() == base {
+        return Err(git2::Error::from_str("Nothing to rebase, HEAD is the base"));
+    }
+    if !repo.graph_descendant_of(head.id(), base)? {
+        return Err(git2::Error::from_str(&format!(
+            "{} is not an ancestor of HEAD",
+            &base.to_string()[..7]
+        )));
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    walk.push(head.id())?;
+    walk.hide(base)?;
+
+    let mut steps = Vec::new();
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+
+        steps.push(RebaseStep {
+            action: RebaseAction::Pick,
+            id: commit.id().to_string(),
+            message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+            mainline: None,
+        });
+    }
+
+    Ok(steps)
+}
+
+/// Checks that squashed and fixed up commits have a commit before them to go into.
+pub fn validate(steps: &[RebaseStep]) -> Result<(), git2::Error> {
+    match steps.iter().find(|step| step.action != RebaseAction::Drop) {
+        Some(step) if step.action.melds() => Err(git2::Error::from_str(&format!(
+            "Can't {} {}, there is no commit before it",
+            step.action.label(),
+            step.short_id()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Rebases HEAD onto `onto` following `steps`, returning a summary of what happened.
+pub fn start(repo: &Repository, onto: Oid, steps: Vec<RebaseStep>) -> Result<String, git2::Error> {
+    validate(&steps)?;
+    start_sequence(repo, SequenceKind::Rebase, onto, steps)
+}
+
+/// Runs `steps` on top of `onto`. The commits are cherry-picked one by one on a detached HEAD,
+/// and the branch is only moved once every step ran.
+pub(super) fn start_sequence(
+    repo: &Repository,
+    kind: SequenceKind,
+    onto: Oid,
+    steps: Vec<RebaseStep>,
+) -> Result<String, git2::Error> {
+    if let Some(state) = RebaseState::load(repo)? {
+        let name = state.kind.name();
+        return Err(git2::Error::from_str(&format!(
+            "A {name} is already in progress, run `{name} continue`, `{name} skip` or \
+             `{name} abort`"
+        )));
+    }
+    if repo.state() != RepositoryState::Clean {
+        return Err(git2::Error::from_str(&format!(
+            "Finish the {:?} in progress before starting a {}",
+            repo.state(),
+            kind.name()
+        )));
+    }
+    if status::load_status(repo)?
+        .iter()
+        .any(|entry| entry.state != status::FileState::Untracked)
+    {
+        return Err(git2::Error::from_str(&format!(
+            "Commit or stash your changes before starting a {}",
+            kind.name()
+        )));
+    }
+
+    let head = repo.head()?;
+    let state = RebaseState {
+        kind,
+        branch: match head.is_branch() {
+            true => head.name().map(str::to_owned),
+            false => None,
+        },
+        orig_head: head.peel_to_commit()?.id().to_string(),
+        onto: onto.to_string(),
+        todo: steps,
+        conflicted: false,
+    };
+
+    checkout_detached(repo, onto)?;
+    run(repo, state)
+}
+
+/// Goes on with a stopped rebase, cherry-pick or revert. After conflicts the resolved step is
+/// committed, after an edit stop staged changes are amended into the edited commit.
+pub fn continue_sequence(repo: &Repository, kind: SequenceKind) -> Result<String, git2::Error> {
+    let mut state = stopped(repo, kind)?;
+    let mut index = repo.index()?;
+
+    if index.has_conflicts() {
+        return Err(git2::Error::from_str(
+            "Resolve the conflicts and stage the files before continuing",
+        ));
+    }
+
+    if state.conflicted {
+        let step = state.todo[0].clone();
+        let commit = repo.find_commit(step.oid()?)?;
+        commit_step(repo, state.kind, &step, &commit)?;
+
+        state.todo.remove(0);
+        state.conflicted = false;
+        if step.action == RebaseAction::Edit {
+            state.save(repo)?;
+            return Ok(format!("Stopped to edit {}", step.short_id()));
+        }
+    } else {
+        let head = repo.head()?.peel_to_commit()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        if tree.id() != head.tree_id() {
+            let committer = repo.signature()?;
+            let id = head.amend(None, None, Some(&committer), None, None, Some(&tree))?;
+            repo.set_head_detached(id)?;
+        }
+    }
+
+    run(repo, state)
+}
+
+/// Drops the step which stopped with conflicts and goes on with the next one.
+pub fn skip_step(repo: &Repository, kind: SequenceKind) -> Result<String, git2::Error> {
+    let mut state = stopped(repo, kind)?;
+    if !state.conflicted {
+        return Err(git2::Error::from_str(&format!(
+            "Nothing to skip, the {} stopped to edit a commit",
+            kind.name()
+        )));
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+
+    state.todo.remove(0);
+    state.conflicted = false;
+    run(repo, state)
+}
+
+/// Throws away the new commits and puts the branch back where it was.
+pub fn abort_sequence(repo: &Repository, kind: SequenceKind) -> Result<String, git2::Error> {
+    let state = stopped(repo, kind)?;
+    let orig_head = repo.find_commit(Oid::from_str(&state.orig_head)?)?;
+
+    repo.reset(orig_head.as_object(), ResetType::Hard, None)?;
+    if let Some(branch) = &state.branch {
+        repo.set_head(branch)?;
+    }
+    repo.cleanup_state()?;
+    RebaseState::remove(repo)?;
+
+    Ok(format!("Aborted the {}", kind.name()))
+}
+
+/// Whether a rebase, cherry-pick or revert is stopped waiting for conflicts to be resolved.
+pub fn stopped_on_conflict(repo: &Repository) -> bool {
+    matches!(RebaseState::load(repo), Ok(Some(state)) if state.conflicted)
+}
+
+/// The stopped operation, which has to be of `kind`.
+fn stopped(repo: &Repository, kind: SequenceKind) -> Result<RebaseState, git2::Error> {
+    match RebaseState::load(repo)? {
+        Some(state) if state.kind == kind => Ok(state),
+        Some(state) => Err(git2::Error::from_str(&format!(
+            "A {} is in progress, not a {}",
+            state.kind.name(),
+            kind.name()
+        ))),
+        None => Err(git2::Error::from_str(&format!(
+            "No {} in progress",
+            kind.name()
+        ))),
+    }
+}
+
+/// Runs the remaining steps, saving the state when the operation stops or fails so it can be
+/// continued or aborted later.
+fn run(repo: &Repository, mut state: RebaseState) -> Result<String, git2::Error> {
+    match run_steps(repo, &mut state) {
+        Ok(Some(stop)) => {
+            state.save(repo)?;
+            Ok(stop)
+        }
+        Ok(None) => finish(repo, &state),
+        Err(err) => {
+            // The step's error matters more than failing to save.
+            let _ = state.save(repo);
+            Err(err)
+        }
+    }
+}
+
+/// Returns why the operation stopped, or `None` once every step ran.
+fn run_steps(repo: &Repository, state: &mut RebaseState) -> Result<Option<String>, git2::Error> {
+    while let Some(step) = state.todo.first().cloned() {
+        if step.action != RebaseAction::Drop {
+            let commit = repo.find_commit(step.oid()?)?;
+            let head = repo.head()?.peel_to_commit()?;
+            let unchanged = state.kind != SequenceKind::Revert
+                && matches!(step.action, RebaseAction::Pick | RebaseAction::Edit)
+                && commit.parent_id(0).ok() == Some(head.id());
+
+            if unchanged {
+                // The commit would be recreated as it is, keep it like git does.
+                checkout_detached(repo, commit.id())?;
+            } else {
+                match state.kind {
+                    SequenceKind::Revert => {
+                        let mut options = RevertOptions::new();
+                        if let Some(mainline) = step.mainline {
+                            options.mainline(mainline);
+                        }
+                        repo.revert(&commit, Some(&mut options))?;
+                    }
+                    _ => repo.cherrypick(&commit, None)?,
+                }
+                if repo.index()?.has_conflicts() {
+                    state.conflicted = true;
+                    return Ok(Some(format!(
+                        "Stopped at {} with conflicts",
+                        step.short_id()
+                    )));
+                }
+                commit_step(repo, state.kind, &step, &commit)?;
+            }
+        }
+
+        state.todo.remove(0);
+        if step.action == RebaseAction::Edit {
+            return Ok(Some(format!("Stopped to edit {}", step.short_id())));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Commits the index for `step`, `original` being the commit it picks or reverts. Squashed and
+/// fixed up commits amend HEAD, other commits which became empty are dropped. Reverts are
+/// authored by the committer like git does.
+fn commit_step(
+    repo: &Repository,
+    kind: SequenceKind,
+    step: &RebaseStep,
+    original: &Commit,
+) -> Result<(), git2::Error> {
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let head = repo.head()?.peel_to_commit()?;
+    let committer = repo.signature()?;
+
+    let id = match step.action {
+        RebaseAction::Squash | RebaseAction::Fixup => {
+            let head_message = String::from_utf8_lossy(head.message_bytes()).into_owned();
+            let message = match step.action {
+                RebaseAction::Squash => {
+                    format!(
+                        "{}\n\n{}\n",
+                        head_message.trim_end(),
+                        step.message.trim_end()
+                    )
+                }
+                _ => head_message,
+            };
+            Some(head.amend(
+                None,
+                None,
+                Some(&committer),
+                None,
+                Some(&message),
+                Some(&tree),
+            )?)
+        }
+        _ if tree.id() == head.tree_id() => None,
+        _ if kind == SequenceKind::Revert => {
+            Some(repo.commit(None, &committer, &committer, &step.message, &tree, &[&head])?)
+        }
+        _ => Some(repo.commit(
+            None,
+            &original.author(),
+            &committer,
+            &step.message,
+            &tree,
+            &[&head],
+        )?),
+    };
+
+    if let Some(id) = id {
+        repo.set_head_detached(id)?;
+    }
+    repo.cleanup_state()
+}
+
+/// Moves the branch to the new commits and checks it out again.
+fn finish(repo: &Repository, state: &RebaseState) -> Result<String, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let onto = &state.onto[..7];
+
+    let name = match &state.branch {
+        Some(branch) => {
+            let log_message = format!("{} (finish): onto {onto}", state.kind.name());
+            repo.reference(branch, head, true, &log_message)?;
+            repo.set_head(branch)?;
+            branch.strip_prefix("refs/heads/").unwrap_or(branch)
+        }
+        None => "HEAD",
+    };
+    let summary = match state.kind {
+        SequenceKind::Rebase => format!("Rebased '{name}' onto {onto}"),
+        SequenceKind::CherryPick => format!("Cherry-picked onto '{name}'"),
+        SequenceKind::Revert => format!("Reverted on '{name}'"),
+    };
+
+    RebaseState::remove(repo)?;
+    Ok(summary)
+}
+
+fn checkout_detached(repo: &Repository, id: Oid) -> Result<(), git2::Error> {
+    let commit = repo.find_commit(id)?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.set_head_detached(id)
+}
@@ -0,0 +1,145 @@
+use git2::Repository;
+
+use crate::config::{repo::SavedRepositories, Config};
+
+pub struct RemoteEntry {
+    pub name: String,
+    pub fetch_url: String,
+    /// Separate URL pushes go to, pushes use `fetch_url` when there is none.
+    pub push_url: Option<String>,
+    pub fetch_refspecs: Vec<String>,
+    pub push_refspecs: Vec<String>,
+}
+
+/// Lists the remotes configured in the repository, sorted by name.
+pub fn load_remotes(repo: &Repository) -> Result<Vec<RemoteEntry>, git2::Error> {
+    let mut remotes = Vec::new();
+
+    for name in repo.remotes()?.iter().flatten() {
+        let remote = repo.find_remote(name)?;
+        remotes.push(RemoteEntry {
+            name: name.to_owned(),
+            fetch_url: remote.url().unwrap_or_default().to_owned(),
+            push_url: remote.pushurl().map(str::to_owned),
+            fetch_refspecs: remote
+                .fetch_refspecs()?
+                .iter()
+                .flatten()
+                .map(str::to_owned)
+                .collect(),
+            push_refspecs: remote
+                .push_refspecs()?
+                .iter()
+                .flatten()
+                .map(str::to_owned)
+                .collect(),
+        });
+    }
+
+    remotes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(remotes)
+}
+
+/// The remote used when none is given: the preferred remote saved for the repository while it
+/// still exists, `origin`, or else the first remote.
+pub fn default_remote(repo: &Repository) -> Option<String> {
+    let names = repo.remotes().ok()?;
+    let names: Vec<&str> = names.iter().flatten().collect();
+    let preferred =
+        SavedRepositories::load_or_create_config().preferred_remote(&super::saved_path(repo));
+
+    preferred
+        .filter(|preferred| names.contains(&preferred.as_str()))
+        .or_else(|| {
+            names
+                .iter()
+                .find(|name| **name == "origin")
+                .or_else(|| names.first())
+                .map(|name| (*name).to_owned())
+        })
+}
+
+/// Remembers `name` as the remote of the repository, or forgets the choice for `None`.
+pub fn set_preferred(repo: &Repository, name: Option<&str>) -> Result<String, git2::Error> {
+    if let Some(name) = name {
+        repo.find_remote(name)?;
+    }
+
+    let mut saved = SavedRepositories::load_or_create_config();
+    saved
+        .set_preferred_remote(&super::saved_path(repo), name)
+        .and_then(|_| saved.save_config())
+        .map_err(|err| git2::Error::from_str(&err.to_string()))?;
+
+    match name {
+        Some(name) => Ok(format!("'{name}' is now the preferred remote")),
+        None => Ok("Forgot the preferred remote".to_owned()),
+    }
+}
+
+fn preferred_is(repo: &Repository, name: &str) -> bool {
+    SavedRepositories::load_or_create_config()
+        .preferred_remote(&super::saved_path(repo))
+        .is_some_and(|preferred| preferred == name)
+}
+
+pub fn add(repo: &Repository, name: &str, url: &str) -> Result<String, git2::Error> {
+    repo.remote(name, url)?;
+    Ok(format!("Added the remote '{name}'"))
+}
+
+/// Renames the remote along with its remote-tracking branches, keeping it preferred.
+pub fn rename(repo: &Repository, name: &str, new_name: &str) -> Result<String, git2::Error> {
+    let preferred = preferred_is(repo, name);
+    let problems = repo.remote_rename(name, new_name)?;
+    if preferred {
+        set_preferred(repo, Some(new_name))?;
+    }
+
+    // Refspecs which don't follow the default layout are left for the user to update.
+    let problems: Vec<&str> = problems.iter().flatten().collect();
+    match problems.is_empty() {
+        true => Ok(format!("Renamed the remote '{name}' to '{new_name}'")),
+        false => Ok(format!(
+            "Renamed the remote '{name}' to '{new_name}', update these refspecs by hand: {}",
+            problems.join(", ")
+        )),
+    }
+}
+
+/// Removes the remote with its remote-tracking branches.
+pub fn remove(repo: &Repository, name: &str) -> Result<String, git2::Error> {
+    let preferred = preferred_is(repo, name);
+    repo.remote_delete(name)?;
+    if preferred {
+        set_preferred(repo, None)?;
+    }
+    Ok(format!("Removed the remote '{name}'"))
+}
+
+/// Sets the URL the remote fetches from, or with `push` the URL it pushes to, which an empty
+/// URL removes.
+pub fn set_url(
+    repo: &Repository,
+    name: &str,
+    url: &str,
+    push: bool,
+) -> Result<String, git2::Error> {
+    repo.find_remote(name)?;
+
+    match (push, url.is_empty()) {
+        (false, true) => Err(git2::Error::from_str("The URL can't be empty")),
+        (false, false) => {
+            repo.remote_set_url(name, url)?;
+            Ok(format!("'{name}' now fetches from {url}"))
+        }
+        (true, true) => {
+            repo.remote_set_pushurl(name, None)?;
+            Ok(format!("'{name}' now pushes to its fetch URL"))
+        }
+        (true, false) => {
+            repo.remote_set_pushurl(name, Some(url))?;
+            Ok(format!("'{name}' now pushes to {url}"))
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use git2::{Oid, Repository, ResetType};
+
+use super::{
+    rebase::RebaseState,
+    status::{self, FileState, StatusEntry},
+};
+
+/// Where the branch position is saved before every reset, so the reset can be undone.
+pub const BACKUP_REF: &str = "refs/volnita/reset-backup";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+impl ResetMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResetMode::Soft => "soft",
+            ResetMode::Mixed => "mixed",
+            ResetMode::Hard => "hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ResetMode> {
+        match name {
+            "soft" => Some(ResetMode::Soft),
+            "mixed" => Some(ResetMode::Mixed),
+            "hard" => Some(ResetMode::Hard),
+            _ => None,
+        }
+    }
+
+    fn reset_type(&self) -> ResetType {
+        match self {
+            ResetMode::Soft => ResetType::Soft,
+            ResetMode::Mixed => ResetType::Mixed,
+            ResetMode::Hard => ResetType::Hard,
+        }
+    }
+}
+
+/// What resetting the current branch to a commit changes.
+pub struct ResetPlan {
+    /// Full name of the branch being reset.
+    pub branch: String,
+    pub head: Oid,
+    /// Commits which are no longer on the branch afterwards, newest first, with their summary.
+    pub leaving: Vec<(Oid, String)>,
+    /// Commits the branch gains when the target isn't an ancestor of HEAD.
+    pub joining: usize,
+    /// Uncommitted changes a hard reset throws away, untracked files are kept.
+    pub lost: Vec<StatusEntry>,
+}
+
+impl ResetPlan {
+    pub fn branch_name(&self) -> &str {
+        self.branch
+            .strip_prefix("refs/heads/")
+            .unwrap_or(&self.branch)
+    }
+}
+
+/// Works out what resetting the current branch to `target` with `mode` would do.
+pub fn plan_reset(
+    repo: &Repository,
+    target: Oid,
+    mode: ResetMode,
+) -> Result<ResetPlan, git2::Error> {
+    if let Some(state) = RebaseState::load(repo)? {
+        return Err(git2::Error::from_str(&state.describe()));
+    }
+
+    let head = repo.head()?;
+    let branch = match head.is_branch() {
+        true => head.name().unwrap_or_default().to_owned(),
+        false => {
+            return Err(git2::Error::from_str(
+                "HEAD is detached, check out the branch to reset",
+            ))
+        }
+    };
+    let head = head.peel_to_commit()?.id();
+    repo.find_commit(target)?;
+
+    let mut walk = repo.revwalk()?;
+    walk.push(head)?;
+    walk.hide(target)?;
+    let mut leaving = Vec::new();
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+        leaving.push((commit.id(), commit.summary().unwrap_or_default().to_owned()));
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.push(target)?;
+    walk.hide(head)?;
+    let joining = walk.count();
+
+    let lost = match mode {
+        ResetMode::Hard => {
+            // Files changed in the index and the working tree are listed once.
+            let mut seen = HashSet::new();
+            status::load_status(repo)?
+                .into_iter()
+                .filter(|entry| entry.state != FileState::Untracked)
+                .filter(|entry| seen.insert(entry.path.to_owned()))
+                .collect()
+        }
+        ResetMode::Soft | ResetMode::Mixed => Vec::new(),
+    };
+
+    Ok(ResetPlan {
+        branch,
+        head,
+        leaving,
+        joining,
+        lost,
+    })
+}
+
+/// Moves the current branch to `target`, saving where it was in [`BACKUP_REF`] first.
+pub fn reset(repo: &Repository, target: Oid, mode: ResetMode) -> Result<String, git2::Error> {
+    let plan = plan_reset(repo, target, mode)?;
+    let short = &target.to_string()[..7];
+
+    // Refs outside of refs/heads only get a reflog when asked for, it records the mode.
+    repo.reference_ensure_log(BACKUP_REF)?;
+    repo.reference(
+        BACKUP_REF,
+        plan.head,
+        true,
+        &format!("reset --{}: moving to {short}", mode.name()),
+    )?;
+    repo.reset(&repo.find_object(target, None)?, mode.reset_type(), None)?;
+
+    Ok(format!(
+        "Reset '{}' to {short} (--{}), `reset undo` goes back",
+        plan.branch_name(),
+        mode.name()
+    ))
+}
+
+/// Where the last reset moved the branch from, and the mode it used, which undoes it again.
+pub fn backup(repo: &Repository) -> Result<(Oid, ResetMode), git2::Error> {
+    let target = repo
+        .find_reference(BACKUP_REF)
+        .map_err(|_| git2::Error::from_str("There is no reset to undo"))?
+        .peel_to_commit()?
+        .id();
+
+    let reflog = repo.reflog(BACKUP_REF)?;
+    let mode = reflog
+        .get(0)
+        .and_then(|entry| entry.message().map(str::to_owned))
+        .and_then(|message| {
+            let name = message
+                .strip_prefix("reset --")?
+                .split(':')
+                .next()?
+                .to_owned();
+            ResetMode::from_name(&name)
+        })
+        .unwrap_or(ResetMode::Mixed);
+
+    Ok((target, mode))
+}
@@ -0,0 +1,68 @@
+use git2::{Commit, Oid, Repository};
+
+use super::rebase::{self, RebaseAction, RebaseStep, SequenceKind};
+
+/// The message `git revert` starts the revert of `commit` with. Merges name the parent whose
+/// side is kept.
+pub fn revert_message(commit: &Commit, mainline: Option<u32>) -> String {
+    let summary = commit.summary().unwrap_or_default();
+    let mut message = format!(
+        "Revert \"{summary}\"\n\nThis reverts commit {}",
+        commit.id()
+    );
+
+    let parent = mainline.and_then(|mainline| commit.parent_id(mainline as usize - 1).ok());
+    match parent {
+        Some(parent) => message.push_str(&format!(", reversing\nchanges made to {parent}.\n")),
+        None => message.push_str(".\n"),
+    }
+    message
+}
+
+/// A step reverting `id` with the default message, keeping the side of `mainline` for merges.
+pub fn revert_step(
+    repo: &Repository,
+    id: Oid,
+    mainline: Option<u32>,
+) -> Result<RebaseStep, git2::Error> {
+    let commit = repo.find_commit(id)?;
+
+    Ok(RebaseStep {
+        // Steps of a revert sequence revert their commit instead of picking it.
+        action: RebaseAction::Pick,
+        id: id.to_string(),
+        message: revert_message(&commit, mainline),
+        mainline,
+    })
+}
+
+/// Reverts the commits of `steps` on top of HEAD in the given order, committing each revert.
+/// A conflict stops the revert until it is continued, skipped or aborted.
+pub fn revert(repo: &Repository, steps: Vec<RebaseStep>) -> Result<String, git2::Error> {
+    if steps.is_empty() {
+        return Err(git2::Error::from_str("No commits to revert"));
+    }
+
+    for step in steps.iter() {
+        let parents = repo.find_commit(step.oid()?)?.parent_count() as u32;
+
+        match step.mainline {
+            None if parents > 1 => {
+                return Err(git2::Error::from_str(&format!(
+                    "{} is a merge, choose the parent whose side is kept",
+                    step.short_id()
+                )))
+            }
+            Some(mainline) if parents < 2 || mainline == 0 || mainline > parents => {
+                return Err(git2::Error::from_str(&format!(
+                    "{} has no parent {mainline} to keep",
+                    step.short_id()
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    let head = repo.head()?.peel_to_commit()?.id();
+    rebase::start_sequence(repo, SequenceKind::Revert, head, steps)
+}
@@ -0,0 +1,182 @@
+use git2::{build::CheckoutBuilder, DiffOptions, Oid, Repository, StashApplyOptions, StashFlags};
+
+use super::{
+    commit,
+    diff::{self, FileDiff},
+};
+
+pub struct StashEntry {
+    /// Position in the stash list, `stash@{index}`.
+    pub index: usize,
+    pub id: Oid,
+    pub message: String,
+    /// Commit the stash was made on.
+    pub base: Oid,
+    pub base_summary: String,
+    pub date: String,
+}
+
+impl StashEntry {
+    pub fn name(&self) -> String {
+        stash_name(self.index)
+    }
+}
+
+pub fn stash_name(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+/// Index of the stash given as `n` or `stash@{n}`.
+pub fn parse_name(name: &str) -> Option<usize> {
+    name.strip_prefix("stash@{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .unwrap_or(name)
+        .parse()
+        .ok()
+}
+
+/// The stash functions of git2 need a mutable repository, which the views only borrow, so they
+/// run on a second handle to the same repository.
+fn reopen(repo: &Repository) -> Result<Repository, git2::Error> {
+    Repository::open(repo.path())
+}
+
+/// Lists the stashes, newest first.
+pub fn load_stashes(repo: &Repository) -> Result<Vec<StashEntry>, git2::Error> {
+    let mut found = Vec::new();
+    reopen(repo)?.stash_foreach(|index, message, id| {
+        found.push((index, message.to_owned(), *id));
+        true
+    })?;
+
+    let mut stashes = Vec::with_capacity(found.len());
+    for (index, message, id) in found {
+        let commit = repo.find_commit(id)?;
+        let base = commit.parent(0)?;
+
+        stashes.push(StashEntry {
+            index,
+            id,
+            message,
+            base: base.id(),
+            base_summary: base.summary().unwrap_or_default().to_owned(),
+            date: commit::format_time(commit.time()),
+        });
+    }
+
+    Ok(stashes)
+}
+
+/// Changes kept in the stash against the commit it was made on, followed by the untracked
+/// files it saved.
+pub fn stash_diff(repo: &Repository, id: Oid) -> Result<Vec<FileDiff>, git2::Error> {
+    let mut files = diff::commit_diff(repo, id)?;
+
+    if let Ok(untracked) = repo.find_commit(id)?.parent(2) {
+        let diff = repo.diff_tree_to_tree(
+            None,
+            Some(&untracked.tree()?),
+            Some(&mut DiffOptions::new()),
+        )?;
+        files.extend(diff::file_diffs(&diff)?);
+    }
+
+    Ok(files)
+}
+
+/// Stashes the changes of the working tree, like `git stash push`.
+pub fn save(
+    repo: &Repository,
+    message: Option<&str>,
+    include_untracked: bool,
+    keep_index: bool,
+) -> Result<String, git2::Error> {
+    let mut flags = StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    if keep_index {
+        flags |= StashFlags::KEEP_INDEX;
+    }
+
+    let signature = repo.signature()?;
+    let id = reopen(repo)?.stash_save2(&signature, message, Some(flags))?;
+    Ok(format!("Saved the changes as {}", &id.to_string()[..7]))
+}
+
+fn apply_options() -> StashApplyOptions<'static> {
+    let mut options = StashApplyOptions::new();
+    options.reinstantiate_index();
+    options
+}
+
+/// Whether applying a stash left conflicts. The stash was applied through another handle, so
+/// the index is read again from disk.
+fn has_conflicts(repo: &Repository) -> Result<bool, git2::Error> {
+    let mut index = repo.index()?;
+    index.read(true)?;
+    Ok(index.has_conflicts())
+}
+
+fn conflicts_message(index: usize) -> String {
+    format!(
+        "{} applied with conflicts, resolve them in `conflicts`, the stash was kept",
+        stash_name(index)
+    )
+}
+
+/// Applies the stash at `index` to the working tree and index, keeping the stash.
+pub fn apply(repo: &Repository, index: usize) -> Result<String, git2::Error> {
+    reopen(repo)?.stash_apply(index, Some(&mut apply_options()))?;
+
+    match has_conflicts(repo)? {
+        true => Ok(conflicts_message(index)),
+        false => Ok(format!("Applied {}", stash_name(index))),
+    }
+}
+
+/// Applies the stash at `index` and drops it, unless applying it conflicted.
+pub fn pop(repo: &Repository, index: usize) -> Result<String, git2::Error> {
+    let mut stash_repo = reopen(repo)?;
+    stash_repo.stash_apply(index, Some(&mut apply_options()))?;
+
+    if has_conflicts(repo)? {
+        return Ok(conflicts_message(index));
+    }
+    stash_repo.stash_drop(index)?;
+    Ok(format!("Popped {}", stash_name(index)))
+}
+
+pub fn drop(repo: &Repository, index: usize) -> Result<String, git2::Error> {
+    reopen(repo)?.stash_drop(index)?;
+    Ok(format!("Dropped {}", stash_name(index)))
+}
+
+/// Creates the branch `name` on the commit the stash was made on, checks it out and pops the
+/// stash onto it, like `git stash branch`.
+pub fn branch(repo: &Repository, index: usize, name: &str) -> Result<String, git2::Error> {
+    let stash = load_stashes(repo)?
+        .into_iter()
+        .find(|stash| stash.index == index)
+        .ok_or_else(|| git2::Error::from_str(&format!("There is no {}", stash_name(index))))?;
+
+    let base = repo.find_commit(stash.base)?;
+    let branch = repo.branch(name, &base, false)?;
+    let refname = branch
+        .get()
+        .name()
+        .ok_or_else(|| git2::Error::from_str("The branch name is not valid UTF-8"))?
+        .to_owned();
+
+    repo.checkout_tree(base.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.set_head(&refname)?;
+
+    let mut stash_repo = reopen(repo)?;
+    stash_repo.stash_apply(index, Some(&mut apply_options()))?;
+    stash_repo.stash_drop(index)?;
+
+    Ok(format!(
+        "Switched to the new branch '{name}' with {}",
+        stash.name()
+    ))
+}
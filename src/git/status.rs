@@ -0,0 +1,232 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use git2::{build::CheckoutBuilder, Repository, Status, StatusOptions};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+impl FileState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileState::Staged => "Staged",
+            FileState::Unstaged => "Unstaged",
+            FileState::Untracked => "Untracked",
+        }
+    }
+}
+
+pub struct StatusEntry {
+    pub path: String,
+    pub state: FileState,
+    pub change: &'static str,
+}
+
+/// Lists the working tree status of the repository. A file that has changes both in the index
+/// and in the working tree shows up once for each.
+pub fn load_status(repo: &Repository) -> Result<Vec<StatusEntry>, git2::Error> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+
+    let statuses = repo.statuses(Some(&mut options))?;
+
+    let mut staged = Vec::new();
+    let mut unstaged = Vec::new();
+    let mut untracked = Vec::new();
+
+    for entry in statuses.iter() {
+        let path = match entry.path() {
+            Some(p) => p.to_owned(),
+            None => continue,
+        };
+        let status = entry.status();
+
+        if let Some(change) = index_change(status) {
+            staged.push(StatusEntry {
+                path: path.clone(),
+                state: FileState::Staged,
+                change,
+            });
+        }
+
+        if status.contains(Status::WT_NEW) {
+            untracked.push(StatusEntry {
+                path,
+                state: FileState::Untracked,
+                change: "new",
+            });
+        } else if let Some(change) = worktree_change(status) {
+            unstaged.push(StatusEntry {
+                path,
+                state: FileState::Unstaged,
+                change,
+            });
+        }
+    }
+
+    staged.append(&mut unstaged);
+    staged.append(&mut untracked);
+    Ok(staged)
+}
+
+fn index_change(status: Status) -> Option<&'static str> {
+    if status.contains(Status::INDEX_NEW) {
+        Some("new")
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        Some("modified")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("deleted")
+    } else if status.contains(Status::INDEX_RENAMED) {
+        Some("renamed")
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        Some("typechange")
+    } else {
+        None
+    }
+}
+
+fn worktree_change(status: Status) -> Option<&'static str> {
+    if status.contains(Status::WT_MODIFIED) {
+        Some("modified")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("deleted")
+    } else if status.contains(Status::WT_RENAMED) {
+        Some("renamed")
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        Some("typechange")
+    } else if status.contains(Status::CONFLICTED) {
+        Some("conflicted")
+    } else {
+        None
+    }
+}
+
+/// Equivalent of `git add <path>`, also handles files removed from the working tree.
+pub fn stage_path(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    let mut index = repo.index()?;
+    let workdir = workdir(repo)?;
+
+    if workdir.join(path).exists() {
+        index.add_path(Path::new(path))?;
+    } else {
+        index.remove_path(Path::new(path))?;
+    }
+
+    index.write()
+}
+
+/// Equivalent of `git reset -- <path>`. Before the first commit there is no HEAD to reset to,
+/// so the entry is removed from the index instead.
+pub fn unstage_path(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => repo.reset_default(Some(commit.as_object()), [path]),
+        Err(_) => {
+            let mut index = repo.index()?;
+            index.remove_path(Path::new(path))?;
+            index.write()
+        }
+    }
+}
+
+/// Throws away the changes made to `paths` since the index, or since HEAD when `from_head` is
+/// set, in which case the index is reset as well (`git checkout [HEAD] -- <paths>`).
+pub fn restore_paths(
+    repo: &Repository,
+    paths: &[String],
+    from_head: bool,
+) -> Result<(), git2::Error> {
+    let workdir = workdir(repo)?;
+
+    if from_head {
+        let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+
+        for path in paths {
+            unstage_path(repo, path)?;
+
+            // Files added since HEAD are untracked once unstaged and can't be checked out.
+            let in_head = head_tree
+                .as_ref()
+                .map(|tree| tree.get_path(Path::new(path)).is_ok())
+                .unwrap_or(false);
+
+            if !in_head {
+                remove_file(&workdir.join(path))?;
+            }
+        }
+    }
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    for path in paths {
+        checkout.path(path);
+    }
+
+    repo.checkout_index(None, Some(&mut checkout))
+}
+
+/// Deletes files from the working tree and the index, `git rm -f -- <paths>`. Nothing is
+/// deleted unless every path is a file of the index inside the working tree.
+pub fn remove_paths(repo: &Repository, paths: &[String]) -> Result<(), git2::Error> {
+    let workdir = workdir(repo)?;
+    let mut index = repo.index()?;
+
+    for path in paths {
+        let inside = Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !inside || index.get_path(Path::new(path), 0).is_none() {
+            return Err(git2::Error::from_str(&format!(
+                "{path} is not a file of the index"
+            )));
+        }
+    }
+
+    for path in paths {
+        index.remove_path(Path::new(path))?;
+        remove_file(&workdir.join(path))?;
+    }
+
+    index.write()
+}
+
+/// Deletes untracked files, `git clean -f -- <paths>`.
+pub fn clean_paths(repo: &Repository, paths: &[String]) -> Result<(), git2::Error> {
+    let workdir = workdir(repo)?;
+
+    for path in paths {
+        if repo.status_file(Path::new(path))? != Status::WT_NEW {
+            return Err(git2::Error::from_str(&format!(
+                "{path} is not an untracked file"
+            )));
+        }
+        remove_file(&workdir.join(path))?;
+    }
+
+    Ok(())
+}
+
+fn workdir(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    repo.workdir().map(Path::to_path_buf).ok_or_else(|| {
+        git2::Error::from_str("This operation needs a working tree, the repository is bare")
+    })
+}
+
+fn remove_file(path: &Path) -> Result<(), git2::Error> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(git2::Error::from_str(&format!(
+            "Failed to delete {}: {err}",
+            path.display()
+        ))),
+    }
+}
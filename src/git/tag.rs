@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use git2::{Oid, Repository};
+
+use super::commit;
+
+pub struct TagEntry {
+    pub name: String,
+    /// Commit the tag points to, through the tag object for annotated tags.
+    pub target: Oid,
+    pub target_summary: String,
+    pub annotated: bool,
+    /// Tagger, date and message of annotated tags.
+    pub tagger: Option<String>,
+    pub date: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Lists the tags pointing at commits, sorted by name.
+pub fn load_tags(repo: &Repository) -> Result<Vec<TagEntry>, git2::Error> {
+    let mut tags = Vec::new();
+
+    for reference in repo.references_glob("refs/tags/*")? {
+        let reference = reference?;
+        let name = match reference.shorthand() {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        // Tags of trees or blobs don't belong in a commit log.
+        let target = match reference.peel_to_commit() {
+            Ok(commit) => commit,
+            Err(_) => continue,
+        };
+        let tag = reference.peel_to_tag().ok();
+
+        tags.push(TagEntry {
+            name,
+            target: target.id(),
+            target_summary: target.summary().unwrap_or_default().to_owned(),
+            annotated: tag.is_some(),
+            tagger: tag.as_ref().and_then(|tag| {
+                tag.tagger()
+                    .map(|tagger| String::from_utf8_lossy(tagger.name_bytes()).into_owned())
+            }),
+            date: tag.as_ref().and_then(|tag| {
+                tag.tagger()
+                    .map(|tagger| commit::format_time(tagger.when()))
+            }),
+            message: tag
+                .as_ref()
+                .and_then(|tag| tag.message().map(|message| message.trim_end().to_owned())),
+        });
+    }
+
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tags)
+}
+
+/// Names of the tags on every tagged commit, for decorating the log.
+pub fn tag_names_by_commit(repo: &Repository) -> Result<HashMap<Oid, Vec<String>>, git2::Error> {
+    let mut names: HashMap<Oid, Vec<String>> = HashMap::new();
+    for tag in load_tags(repo)? {
+        names.entry(tag.target).or_default().push(tag.name);
+    }
+    Ok(names)
+}
+
+/// Tags `target` as `name`, annotated by the configured user when there is a message.
+pub fn create_tag(
+    repo: &Repository,
+    name: &str,
+    target: Oid,
+    message: Option<&str>,
+) -> Result<String, git2::Error> {
+    let object = repo.find_object(target, None)?;
+    let short = &target.to_string()[..7];
+
+    match message {
+        Some(message) => {
+            let tagger = repo.signature()?;
+            repo.tag(name, &object, &tagger, message, false)?;
+            Ok(format!("Created the annotated tag '{name}' on {short}"))
+        }
+        None => {
+            repo.tag_lightweight(name, &object, false)?;
+            Ok(format!("Created the tag '{name}' on {short}"))
+        }
+    }
+}
+
+pub fn delete_tag(repo: &Repository, name: &str) -> Result<String, git2::Error> {
+    repo.tag_delete(name)?;
+    Ok(format!("Deleted the tag '{name}'"))
+}
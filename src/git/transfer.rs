@@ -0,0 +1,492 @@
+use std::cell::RefCell;
+
+use git2::{
+    AutotagOption, FetchOptions, Oid, Progress as IndexerProgress, PushOptions, RemoteCallbacks,
+    Repository,
+};
+
+use super::{
+    credentials::Authenticator,
+    merge::{self, MergeMode},
+    remote,
+};
+use crate::traits::transfer_ui::TransferUi;
+
+/// How far a transfer got, `done` out of `total` objects or deltas in the current stage.
+pub struct Progress {
+    /// What is being transferred, like "Receiving objects".
+    pub stage: &'static str,
+    pub done: usize,
+    pub total: usize,
+    pub bytes: usize,
+}
+
+impl Progress {
+    fn fetched(stats: &IndexerProgress) -> Progress {
+        match stats.received_objects() < stats.total_objects() || stats.total_deltas() == 0 {
+            true => Progress {
+                stage: "Receiving objects",
+                done: stats.received_objects(),
+                total: stats.total_objects(),
+                bytes: stats.received_bytes(),
+            },
+            false => Progress {
+                stage: "Resolving deltas",
+                done: stats.indexed_deltas(),
+                total: stats.total_deltas(),
+                bytes: stats.received_bytes(),
+            },
+        }
+    }
+}
+
+/// The remote named by the user, or the default one.
+fn remote_name(repo: &Repository, name: Option<&str>) -> Result<String, git2::Error> {
+    name.map(str::to_owned)
+        .or_else(|| remote::default_remote(repo))
+        .ok_or_else(|| git2::Error::from_str("There is no remote, add one with `remote add`"))
+}
+
+/// `refs/remotes/origin/main` as `origin/main`, and the same for branches and tags.
+fn short_ref(refname: &str) -> &str {
+    ["refs/remotes/", "refs/heads/", "refs/tags/"]
+        .iter()
+        .find_map(|prefix| refname.strip_prefix(prefix))
+        .unwrap_or(refname)
+}
+
+fn short_id(id: Oid) -> String {
+    id.to_string()[..7].to_owned()
+}
+
+/// One line of the updated refs summary, like `origin/main abc1234..def5678`.
+fn ref_update(refname: &str, old: Oid, new: Oid) -> String {
+    let name = short_ref(refname);
+    match (old.is_zero(), new.is_zero()) {
+        (true, _) => format!("{name} (new)"),
+        (_, true) => format!("{name} (deleted)"),
+        _ => format!("{name} {}..{}", short_id(old), short_id(new)),
+    }
+}
+
+/// Fetches the branches and tags of a remote, the default one when `name` is `None`.
+pub fn fetch(
+    repo: &Repository,
+    name: Option<&str>,
+    ui: &mut dyn TransferUi,
+) -> Result<String, git2::Error> {
+    let name = remote_name(repo, name)?;
+    let mut remote = repo.find_remote(&name)?;
+    ui.start(&format!("Fetching from '{name}'"));
+
+    let ui = RefCell::new(ui);
+    let authenticator = RefCell::new(Authenticator::new(repo));
+    let updated = RefCell::new(Vec::new());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        authenticator
+            .borrow_mut()
+            .credentials(url, username, allowed, *ui.borrow_mut())
+    });
+    callbacks.transfer_progress(|stats| {
+        ui.borrow_mut().progress(&Progress::fetched(&stats));
+        true
+    });
+    callbacks.update_tips(|refname, old, new| {
+        updated.borrow_mut().push(ref_update(refname, old, new));
+        true
+    });
+
+    let mut options = FetchOptions::new();
+    options
+        .remote_callbacks(callbacks)
+        .download_tags(AutotagOption::Auto);
+    // No refspecs fetches the ones configured for the remote.
+    let result = remote.fetch::<&str>(&[], Some(&mut options), None);
+    authenticator.borrow_mut().finish(&result);
+    result?;
+
+    let updated = updated.take();
+    match updated.is_empty() {
+        true => Ok(format!("Fetched from '{name}', everything was up to date")),
+        false => Ok(format!("Fetched from '{name}': {}", updated.join(", "))),
+    }
+}
+
+/// Fetches and merges the upstream of the current branch into it, or the branch of the same
+/// name on the remote when it has none or another remote is given, like `git pull`. The merge
+/// fast-forwards when it can, follows `merge.ff` when no mode is given and leaves conflicts
+/// in progress as [`merge::merge`] does.
+pub fn pull(
+    repo: &Repository,
+    name: Option<&str>,
+    mode: Option<MergeMode>,
+    ui: &mut dyn TransferUi,
+) -> Result<String, git2::Error> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(git2::Error::from_str(
+            "HEAD is detached, check out the branch to pull into",
+        ));
+    }
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+    let head_name = head.name().unwrap_or_default().to_owned();
+
+    let configured = repo
+        .branch_upstream_name(&head_name)
+        .ok()
+        .and_then(|upstream| upstream.as_str().map(str::to_owned));
+    // The upstream's own remote is fetched, which isn't always the default one.
+    let (remote, upstream) = match (name, configured) {
+        (None, Some(upstream)) => {
+            let remote = repo.branch_upstream_remote(&head_name)?;
+            (remote.as_str().unwrap_or_default().to_owned(), upstream)
+        }
+        (name, _) => {
+            let remote = remote_name(repo, name)?;
+            let upstream = format!("refs/remotes/{remote}/{branch}");
+            (remote, upstream)
+        }
+    };
+
+    let fetched = fetch(repo, Some(&remote), ui)?;
+
+    if repo.find_reference(&upstream).is_err() {
+        return Err(git2::Error::from_str(&format!(
+            "{fetched}, but there is no {}",
+            short_ref(&upstream)
+        )));
+    }
+    // The fetch is done either way, so its summary is kept in front of the merge's.
+    match merge::merge(repo, &upstream, mode) {
+        Ok(merged) => Ok(format!("{fetched}. {merged}")),
+        Err(err) => Err(git2::Error::from_str(&format!(
+            "{fetched}. {}",
+            err.message()
+        ))),
+    }
+}
+
+/// Pushes `branches` to the branches of the same name on a remote, the current branch when
+/// none are given. Without `force` the push is refused before anything is sent when the remote
+/// has commits the branch doesn't.
+pub fn push(
+    repo: &Repository,
+    name: Option<&str>,
+    branches: &[String],
+    force: bool,
+    ui: &mut dyn TransferUi,
+) -> Result<String, git2::Error> {
+    let name = remote_name(repo, name)?;
+    let mut remote = repo.find_remote(&name)?;
+
+    let branches = match branches.is_empty() {
+        true => {
+            let head = repo.head()?;
+            if !head.is_branch() {
+                return Err(git2::Error::from_str(
+                    "HEAD is detached, name the branch to push",
+                ));
+            }
+            vec![head.shorthand().unwrap_or_default().to_owned()]
+        }
+        false => branches.to_vec(),
+    };
+    let refspecs: Vec<String> = branches
+        .iter()
+        .map(|branch| {
+            repo.find_branch(branch, git2::BranchType::Local)?;
+            let force = if force { "+" } else { "" };
+            Ok(format!("{force}refs/heads/{branch}:refs/heads/{branch}"))
+        })
+        .collect::<Result<_, git2::Error>>()?;
+
+    ui.start(&format!("Pushing to '{name}'"));
+
+    let ui = RefCell::new(ui);
+    let authenticator = RefCell::new(Authenticator::new(repo));
+    let updated = RefCell::new(Vec::new());
+    let rejected = RefCell::new(Vec::new());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        authenticator
+            .borrow_mut()
+            .credentials(url, username, allowed, *ui.borrow_mut())
+    });
+    callbacks.push_transfer_progress(|done, total, bytes| {
+        ui.borrow_mut().progress(&Progress {
+            stage: "Writing objects",
+            done,
+            total,
+            bytes,
+        });
+    });
+    callbacks.push_negotiation(|updates| {
+        for update in updates {
+            let refname = update.dst_refname().unwrap_or_default();
+            let (old, new) = (update.src(), update.dst());
+            // A remote commit missing here can't be an ancestor of what is pushed either.
+            let fast_forward =
+                old.is_zero() || old == new || repo.graph_descendant_of(new, old).unwrap_or(false);
+
+            if force || fast_forward {
+                updated.borrow_mut().push(ref_update(refname, old, new));
+            } else {
+                rejected.borrow_mut().push(format!(
+                    "{} has commits which aren't on the branch, pull first or push with --force",
+                    short_ref(refname)
+                ));
+            }
+        }
+
+        match rejected.borrow().is_empty() {
+            true => Ok(()),
+            false => Err(git2::Error::from_str("non-fast-forward")),
+        }
+    });
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            rejected
+                .borrow_mut()
+                .push(format!("{} was rejected: {status}", short_ref(refname)));
+        }
+        Ok(())
+    });
+
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    let result = remote.push(&refspecs, Some(&mut options));
+    authenticator.borrow_mut().finish(&result);
+
+    let rejected = rejected.take();
+    if !rejected.is_empty() {
+        return Err(git2::Error::from_str(&format!(
+            "Push to '{name}' rejected: {}",
+            rejected.join("; ")
+        )));
+    }
+    result?;
+
+    let updated = updated.take();
+    match updated.is_empty() {
+        true => Ok(format!("Pushed to '{name}', everything was up to date")),
+        false => Ok(format!("Pushed to '{name}': {}", updated.join(", "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use git2::{Commit, RepositoryInitOptions, Signature};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::git::credentials::Credentials;
+
+    struct NoUi;
+
+    impl TransferUi for NoUi {
+        fn start(&mut self, _title: &str) {}
+
+        fn progress(&mut self, _progress: &Progress) {}
+
+        fn credentials(&mut self, _url: &str, _username: Option<&str>) -> Option<Credentials> {
+            None
+        }
+    }
+
+    /// A bare remote with one commit on `main`, the repository which pushed it and a clone of
+    /// it, all in a temporary directory.
+    struct Remotes {
+        remote: Repository,
+        one: Repository,
+        two: Repository,
+        dir: TempDir,
+    }
+
+    fn init(path: &Path, bare: bool) -> Repository {
+        let mut options = RepositoryInitOptions::new();
+        options.bare(bare).initial_head("main");
+        Repository::init_opts(path, &options).unwrap()
+    }
+
+    fn commit(repo: &Repository, content: &str) -> Oid {
+        commit_file(repo, "file.txt", content)
+    }
+
+    fn commit_file(repo: &Repository, file: &str, content: &str) -> Oid {
+        fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let signature = Signature::now("Volnita", "volnita@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            content,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    fn remotes() -> Remotes {
+        let dir = tempfile::tempdir().unwrap();
+        let url = dir.path().join("remote.git");
+        let remote = init(&url, true);
+
+        let one = init(&dir.path().join("one"), false);
+        commit(&one, "first");
+        one.remote("origin", url.to_str().unwrap()).unwrap();
+        push(&one, Some("origin"), &[], false, &mut NoUi).unwrap();
+
+        let two = Repository::clone(url.to_str().unwrap(), dir.path().join("two")).unwrap();
+        Remotes {
+            remote,
+            one,
+            two,
+            dir,
+        }
+    }
+
+    fn head(repo: &Repository) -> Oid {
+        repo.head().unwrap().target().unwrap()
+    }
+
+    #[test]
+    fn fetch_lists_the_updated_refs() {
+        let remotes = remotes();
+        let old = head(&remotes.one);
+        let new = commit(&remotes.one, "second");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+
+        let fetched = fetch(&remotes.two, Some("origin"), &mut NoUi).unwrap();
+        assert_eq!(
+            fetched,
+            format!(
+                "Fetched from 'origin': origin/main {}..{}",
+                short_id(old),
+                short_id(new)
+            )
+        );
+
+        let fetched = fetch(&remotes.two, Some("origin"), &mut NoUi).unwrap();
+        assert_eq!(fetched, "Fetched from 'origin', everything was up to date");
+    }
+
+    #[test]
+    fn pull_fast_forwards_the_branch() {
+        let remotes = remotes();
+        let new = commit(&remotes.one, "second");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+
+        let pulled = pull(&remotes.two, Some("origin"), None, &mut NoUi).unwrap();
+        assert!(pulled.ends_with(&format!("Fast-forwarded 'main' to {}", short_id(new))));
+        assert_eq!(head(&remotes.two), new);
+        assert_eq!(
+            fs::read_to_string(remotes.dir.path().join("two/file.txt")).unwrap(),
+            "second"
+        );
+    }
+
+    #[test]
+    fn pull_fetches_the_remote_of_the_upstream() {
+        let remotes = remotes();
+        let url = remotes.dir.path().join("remote.git");
+        remotes.two.remote("other", url.to_str().unwrap()).unwrap();
+        let mut config = remotes.two.config().unwrap();
+        config.set_str("branch.main.remote", "other").unwrap();
+        let new = commit(&remotes.one, "second");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+
+        let pulled = pull(&remotes.two, None, None, &mut NoUi).unwrap();
+        assert!(pulled.starts_with("Fetched from 'other'"));
+        assert_eq!(head(&remotes.two), new);
+    }
+
+    #[test]
+    fn pull_merges_diverged_branches() {
+        let remotes = remotes();
+        let theirs = commit(&remotes.one, "theirs");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+        let ours = commit_file(&remotes.two, "other.txt", "ours");
+        let mut config = remotes.two.config().unwrap();
+        config.set_str("user.name", "Volnita").unwrap();
+        config.set_str("user.email", "volnita@example.com").unwrap();
+
+        let pulled = pull(&remotes.two, Some("origin"), None, &mut NoUi).unwrap();
+        assert!(pulled.ends_with("Merged 'origin/main' into 'main'"));
+        let merge = remotes.two.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(merge.parent_ids().collect::<Vec<_>>(), [ours, theirs]);
+        assert_eq!(
+            fs::read_to_string(remotes.dir.path().join("two/file.txt")).unwrap(),
+            "theirs"
+        );
+    }
+
+    #[test]
+    fn pull_stops_on_conflicts() {
+        let remotes = remotes();
+        commit(&remotes.one, "theirs");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+        let ours = commit(&remotes.two, "ours");
+
+        let pulled = pull(&remotes.two, Some("origin"), None, &mut NoUi).unwrap();
+        assert!(pulled.ends_with("Merging 'origin/main' stopped with conflicts in 1 file(s)"));
+        assert!(merge::in_progress(&remotes.two).is_some());
+        assert_eq!(head(&remotes.two), ours);
+    }
+
+    #[test]
+    fn pull_with_ff_only_refuses_diverged_branches() {
+        let remotes = remotes();
+        commit(&remotes.one, "theirs");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+        let ours = commit_file(&remotes.two, "other.txt", "ours");
+
+        let mode = Some(MergeMode::FastForwardOnly);
+        let err = pull(&remotes.two, Some("origin"), mode, &mut NoUi).unwrap_err();
+        assert!(err.message().ends_with(
+            "'main' and 'origin/main' have diverged, they can't be merged with --ff-only"
+        ));
+        assert_eq!(head(&remotes.two), ours);
+    }
+
+    #[test]
+    fn push_refuses_to_drop_remote_commits() {
+        let remotes = remotes();
+        let theirs = commit(&remotes.one, "theirs");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+        commit(&remotes.two, "ours");
+
+        let err = push(&remotes.two, Some("origin"), &[], false, &mut NoUi).unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("Push to 'origin' rejected: main has commits"));
+        assert_eq!(
+            remotes.remote.refname_to_id("refs/heads/main").unwrap(),
+            theirs
+        );
+    }
+
+    #[test]
+    fn force_push_replaces_remote_commits() {
+        let remotes = remotes();
+        commit(&remotes.one, "theirs");
+        push(&remotes.one, Some("origin"), &[], false, &mut NoUi).unwrap();
+        let ours = commit(&remotes.two, "ours");
+
+        let pushed = push(&remotes.two, Some("origin"), &[], true, &mut NoUi).unwrap();
+        assert!(pushed.starts_with("Pushed to 'origin': main "));
+        assert_eq!(
+            remotes.remote.refname_to_id("refs/heads/main").unwrap(),
+            ours
+        );
+    }
+}
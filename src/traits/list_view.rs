@@ -0,0 +1,51 @@
+use crossterm::event::{KeyEvent, KeyEventKind};
+
+use crate::{app_flags::AppLoopFlag, view_components::input_field};
+
+/// A view listing entries of the repository, like stashes, tags or remotes, which acts on the
+/// selected entry through popups and reports the outcome in its help bar.
+pub trait ListView {
+    type Popup;
+
+    /// Reloads the entries from the repository.
+    fn refresh(&mut self);
+
+    fn set_message(&mut self, message: String);
+
+    fn take_popup(&mut self) -> Option<Self::Popup>;
+
+    fn handle_popup_key(&mut self, popup: Self::Popup, key_event: &KeyEvent);
+
+    /// Handles a key while no popup is open, returns the flag to leave the view with.
+    fn handle_key(&mut self, key_event: &KeyEvent) -> Option<AppLoopFlag>;
+
+    /// Shows the summary of an action, or why it failed, and reloads the entries it changed.
+    fn run(&mut self, result: Result<String, git2::Error>) {
+        self.set_message(match result {
+            Ok(summary) => summary,
+            Err(err) => err.message().to_owned(),
+        });
+        self.refresh();
+    }
+
+    /// Waits for a key and hands it to the open popup or the view. Returns the flag to leave
+    /// the view with.
+    fn handle_input(&mut self) -> Option<AppLoopFlag> {
+        let key_event = input_field::key_wait()?;
+        if input_field::is_quit_event(&key_event) {
+            return Some(AppLoopFlag::terminate());
+        }
+
+        // See OpenedRepoView for why only presses are handled.
+        if key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+        match self.take_popup() {
+            Some(popup) => {
+                self.handle_popup_key(popup, &key_event);
+                None
+            }
+            None => self.handle_key(&key_event),
+        }
+    }
+}
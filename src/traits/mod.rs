@@ -1 +1,3 @@
 pub mod display_view;
+pub mod list_view;
+pub mod transfer_ui;
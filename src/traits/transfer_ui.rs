@@ -0,0 +1,13 @@
+use crate::git::{credentials::Credentials, transfer::Progress};
+
+/// What fetching from and pushing to remotes shows the user while it runs.
+pub trait TransferUi {
+    /// Starts showing a transfer described by `title`, like "Fetching from 'origin'".
+    fn start(&mut self, title: &str);
+
+    fn progress(&mut self, progress: &Progress);
+
+    /// Asks for the username, unless `username` is known already, and the password to access
+    /// `url`. `None` when the user gave up.
+    fn credentials(&mut self, url: &str, username: Option<&str>) -> Option<Credentials>;
+}
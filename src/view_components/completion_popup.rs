@@ -0,0 +1,95 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::command::completion::Completion;
+
+/// Most candidates shown at once, the list scrolls past this.
+const MAX_VISIBLE: usize = 10;
+
+pub enum CompletionResult {
+    Accepted(String),
+    Cancelled,
+    Pending,
+    /// The key is not used by the popup, which should close and pass it on.
+    Ignored,
+}
+
+/// List of completion candidates drawn above a command input.
+pub struct CompletionPopup {
+    pub completion: Completion,
+    state: ListState,
+}
+
+impl CompletionPopup {
+    pub fn new(completion: Completion) -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { completion, state }
+    }
+
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> CompletionResult {
+        let count = self.completion.candidates.len();
+        let selected = self.state.selected().unwrap_or_default();
+
+        match key_event.code {
+            KeyCode::Tab | KeyCode::Down => {
+                self.state.select(Some((selected + 1) % count));
+                CompletionResult::Pending
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.state.select(Some((selected + count - 1) % count));
+                CompletionResult::Pending
+            }
+            KeyCode::Enter => {
+                CompletionResult::Accepted(self.completion.candidates[selected].to_owned())
+            }
+            KeyCode::Esc => CompletionResult::Cancelled,
+            _ => CompletionResult::Ignored,
+        }
+    }
+
+    /// Draws the list right above `input_area`, lined up with the word being completed.
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, input_area: Rect) {
+        let candidates = &self.completion.candidates;
+        let width = candidates
+            .iter()
+            .map(|candidate| candidate.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(16) as u16
+            + 4;
+        let height = candidates.len().min(MAX_VISIBLE) as u16 + 2;
+
+        let frame = f.size();
+        let x = (input_area.x + 1 + self.completion.start as u16)
+            .min(frame.width.saturating_sub(width));
+        let area = Rect {
+            x,
+            y: input_area.y.saturating_sub(height),
+            width: width.min(frame.width),
+            height: height.min(input_area.y),
+        };
+
+        let items: Vec<ListItem> = candidates
+            .iter()
+            .map(|candidate| ListItem::new(candidate.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
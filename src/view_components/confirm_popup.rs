@@ -0,0 +1,93 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+pub enum ConfirmResult {
+    Accepted,
+    Declined,
+    Pending,
+}
+
+/// Yes/no dialog drawn over the current view before running a destructive action.
+pub struct ConfirmPopup {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+impl ConfirmPopup {
+    pub fn new(title: &str, lines: Vec<String>) -> Self {
+        Self {
+            title: title.to_owned(),
+            lines,
+        }
+    }
+
+    pub fn handle_key(&self, key_event: &KeyEvent) -> ConfirmResult {
+        match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => ConfirmResult::Accepted,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => ConfirmResult::Declined,
+            _ => ConfirmResult::Pending,
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>) {
+        let area = centered_rect(60, 60, f.size());
+
+        let mut text: Vec<Line> = self
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "y/Enter: confirm | n/Esc: cancel",
+            Style::default().fg(Color::Yellow),
+        )));
+
+        let paragraph = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.title.as_str())
+                    .style(Style::default().fg(Color::LightRed)),
+            )
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+}
+
+/// Area of `percent_x` by `percent_y` of `r` placed in its center.
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::git::diff::{DiffLineKind, FileDiff};
+
+/// One line of the pane, pointing into the header or the diff.
+#[derive(Clone, Copy)]
+enum DiffRow {
+    Header(usize),
+    File(usize),
+    Hunk(usize, usize),
+    Line(usize, usize, usize),
+}
+
+impl DiffRow {
+    fn file(&self) -> Option<usize> {
+        match self {
+            DiffRow::Header(_) => None,
+            DiffRow::File(file) | DiffRow::Hunk(file, _) | DiffRow::Line(file, _, _) => Some(*file),
+        }
+    }
+}
+
+/// Scrollable pane showing some header lines followed by a diff, where each file can be
+/// collapsed to its name.
+#[derive(Default)]
+pub struct DiffView {
+    header: Vec<Line<'static>>,
+    files: Vec<FileDiff>,
+    collapsed: Vec<bool>,
+    cursor: usize,
+    scroll: usize,
+    /// Height of the pane when it was last drawn, used to page.
+    page: usize,
+    /// Changed lines picked for staging, as file, hunk and line indices.
+    marked: BTreeSet<(usize, usize, usize)>,
+}
+
+impl DiffView {
+    pub fn set_content(&mut self, header: Vec<Line<'static>>, files: Vec<FileDiff>) {
+        self.collapsed = vec![false; files.len()];
+        self.header = header;
+        self.files = files;
+        self.cursor = 0;
+        self.scroll = 0;
+        self.marked.clear();
+    }
+
+    /// Replaces the diff after it changed, keeping the cursor and folds where they were.
+    pub fn update_files(&mut self, files: Vec<FileDiff>) {
+        if files.len() != self.files.len() {
+            self.collapsed = vec![false; files.len()];
+        }
+        self.files = files;
+        self.marked.clear();
+        self.cursor = self.cursor.min(self.rows().len().saturating_sub(1));
+    }
+
+    pub fn files(&self) -> &[FileDiff] {
+        &self.files
+    }
+
+    /// File and hunk the cursor is in, `None` on a file name or the header.
+    pub fn cursor_hunk(&self) -> Option<(usize, usize)> {
+        match self.rows().get(self.cursor)? {
+            DiffRow::Hunk(file, hunk) | DiffRow::Line(file, hunk, _) => Some((*file, *hunk)),
+            _ => None,
+        }
+    }
+
+    pub fn marked(&self) -> &BTreeSet<(usize, usize, usize)> {
+        &self.marked
+    }
+
+    /// Marks or unmarks the added or removed line under the cursor and moves to the next line.
+    /// Returns `false` if the cursor isn't on such a line.
+    pub fn toggle_mark(&mut self) -> bool {
+        let rows = self.rows();
+        let (file, hunk, line) = match rows.get(self.cursor) {
+            Some(DiffRow::Line(file, hunk, line)) => (*file, *hunk, *line),
+            _ => return false,
+        };
+
+        let kind = self.files[file].hunks[hunk].lines[line].kind;
+        if kind != DiffLineKind::Added && kind != DiffLineKind::Removed {
+            return false;
+        }
+
+        if !self.marked.remove(&(file, hunk, line)) {
+            self.marked.insert((file, hunk, line));
+        }
+        self.cursor = (self.cursor + 1).min(rows.len() - 1);
+        true
+    }
+
+    /// Handles a key while the pane has focus, returns `true` if it was used.
+    ///
+    /// Up/Down and j/k move the cursor, PageUp/PageDown page, Enter or Space collapses or
+    /// expands the file under the cursor, n/p jump between files and c/e collapse or expand all.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        let rows = self.rows();
+        let last = rows.len().saturating_sub(1);
+
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::PageDown => self.page_by(true),
+            KeyCode::PageUp => self.page_by(false),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = last,
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(file) = rows.get(self.cursor).and_then(DiffRow::file) {
+                    self.collapsed[file] = !self.collapsed[file];
+                    self.cursor_to_file(file);
+                }
+            }
+            KeyCode::Char('n') => self.jump_file(&rows, true),
+            KeyCode::Char('p') => self.jump_file(&rows, false),
+            KeyCode::Char('c') | KeyCode::Char('e') => {
+                let collapse = key_event.code == KeyCode::Char('c');
+                let file = rows.get(self.cursor).and_then(DiffRow::file);
+                self.collapsed.iter_mut().for_each(|c| *c = collapse);
+                match file {
+                    Some(file) => self.cursor_to_file(file),
+                    None => self.cursor = self.cursor.min(self.rows().len().saturating_sub(1)),
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Scrolls a page up or down, usable without giving the pane focus.
+    pub fn page_by(&mut self, forward: bool) {
+        let page = self.page.max(1);
+        let last = self.rows().len().saturating_sub(1);
+
+        if forward {
+            self.cursor = (self.cursor.max(self.scroll + page - 1) + page).min(last);
+            self.scroll += page;
+        } else {
+            self.cursor = self.cursor.min(self.scroll).saturating_sub(page);
+            self.scroll = self.scroll.saturating_sub(page);
+        }
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        block: Block,
+        focused: bool,
+    ) {
+        let inner = block.inner(area);
+        let height = inner.height as usize;
+        self.page = height;
+
+        let rows = self.rows();
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .map(|(i, row)| {
+                let mut line = self.row_line(*row);
+                if let DiffRow::Line(file, hunk, index) = row {
+                    if self.marked.contains(&(*file, *hunk, *index)) {
+                        line.patch_style(Style::default().bg(Color::DarkGray));
+                    }
+                }
+                if focused && i == self.cursor {
+                    line.patch_style(Style::default().add_modifier(Modifier::REVERSED));
+                }
+                line
+            })
+            .collect();
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn rows(&self) -> Vec<DiffRow> {
+        let mut rows: Vec<DiffRow> = (0..self.header.len()).map(DiffRow::Header).collect();
+
+        for (file_index, file) in self.files.iter().enumerate() {
+            rows.push(DiffRow::File(file_index));
+            if self.collapsed[file_index] {
+                continue;
+            }
+
+            for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+                rows.push(DiffRow::Hunk(file_index, hunk_index));
+                rows.extend(
+                    (0..hunk.lines.len()).map(|line| DiffRow::Line(file_index, hunk_index, line)),
+                );
+            }
+        }
+
+        rows
+    }
+
+    fn row_line(&self, row: DiffRow) -> Line<'static> {
+        match row {
+            DiffRow::Header(i) => self.header[i].clone(),
+            DiffRow::File(i) => {
+                let file = &self.files[i];
+                let (added, removed) = file.line_stats();
+                let marker = if self.collapsed[i] { '▸' } else { '▾' };
+                let path = match &file.old_path {
+                    Some(old_path) => format!("{old_path} → {}", file.path),
+                    None => file.path.to_owned(),
+                };
+                let stats = match file.binary {
+                    true => " (binary)".to_owned(),
+                    false => format!(" (+{added} -{removed})"),
+                };
+
+                Line::from(vec![
+                    Span::styled(
+                        format!("{marker} {} {path}", file.status_char()),
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(stats, Style::default().fg(Color::DarkGray)),
+                ])
+            }
+            DiffRow::Hunk(file, hunk) => Line::from(Span::styled(
+                self.files[file].hunks[hunk].header.to_owned(),
+                Style::default().fg(Color::Magenta),
+            )),
+            DiffRow::Line(file, hunk, line) => {
+                let line = &self.files[file].hunks[hunk].lines[line];
+                let (prefix, color) = match line.kind {
+                    DiffLineKind::Added => ("+", Color::Green),
+                    DiffLineKind::Removed => ("-", Color::Red),
+                    DiffLineKind::Context => (" ", Color::White),
+                    DiffLineKind::Info => ("\\ ", Color::DarkGray),
+                };
+
+                Line::from(Span::styled(
+                    format!("{prefix}{}", line.content.replace('\t', "    ")),
+                    Style::default().fg(color),
+                ))
+            }
+        }
+    }
+
+    fn jump_file(&mut self, rows: &[DiffRow], forward: bool) {
+        let is_file = |row: &DiffRow| matches!(row, DiffRow::File(_));
+        let target = if forward {
+            rows.iter()
+                .enumerate()
+                .skip(self.cursor + 1)
+                .find(|(_, row)| is_file(row))
+        } else {
+            rows.iter()
+                .enumerate()
+                .take(self.cursor)
+                .rev()
+                .find(|(_, row)| is_file(row))
+        };
+
+        if let Some((i, _)) = target {
+            self.cursor = i;
+        }
+    }
+
+    fn cursor_to_file(&mut self, file: usize) {
+        if let Some(i) = self
+            .rows()
+            .iter()
+            .position(|row| matches!(row, DiffRow::File(f) if *f == file))
+        {
+            self.cursor = i;
+        }
+    }
+}
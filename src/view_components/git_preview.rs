@@ -0,0 +1,105 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::git::action::{self, GitAction};
+
+/// Shows the git command line equivalent to the action a view is about to perform, and lets
+/// the user edit it and run the edited command instead.
+#[derive(Default)]
+pub struct GitPreview {
+    actions: Vec<GitAction>,
+    editing: Option<Input>,
+    error: Option<String>,
+}
+
+impl GitPreview {
+    /// Updates the previewed actions, ignored while the command line is being edited.
+    pub fn set_actions(&mut self, actions: Vec<GitAction>) {
+        if self.editing.is_none() {
+            self.actions = actions;
+        }
+    }
+
+    pub fn actions(&self) -> &[GitAction] {
+        &self.actions
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    pub fn start_editing(&mut self) {
+        self.error = None;
+        self.editing = Some(Input::new(action::command_line(&self.actions)));
+    }
+
+    /// Handles a key while editing. Returns the parsed actions once the edited command line
+    /// is submitted, parse errors keep the editor open.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> Option<Vec<GitAction>> {
+        let input = self.editing.as_mut()?;
+
+        match key_event.code {
+            KeyCode::Enter => match action::parse_command_line(input.value()) {
+                Ok(actions) => {
+                    self.editing = None;
+                    self.error = None;
+                    Some(actions)
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    None
+                }
+            },
+            KeyCode::Esc => {
+                self.editing = None;
+                self.error = None;
+                None
+            }
+            _ => {
+                input.handle_event(&Event::Key(*key_event));
+                None
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, edit_key: &str) {
+        let (title, text, color) = match &self.editing {
+            Some(input) => {
+                let title = match &self.error {
+                    Some(err) => format!("Edit Git Command - {err}"),
+                    None => "Edit Git Command (Enter: run | Esc: cancel)".to_owned(),
+                };
+                (title, input.value().to_owned(), Color::Yellow)
+            }
+            None => (
+                format!("Git Command ({edit_key}: edit)"),
+                action::command_line(&self.actions),
+                Color::Gray,
+            ),
+        };
+
+        let paragraph = Paragraph::new(format!("$ {text}"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .style(Style::default().fg(color)),
+            )
+            .style(Style::default().fg(Color::White));
+        f.render_widget(paragraph, area);
+
+        if let Some(input) = &self.editing {
+            let cursor_x = (area.x as usize + 3 + input.visual_cursor())
+                .try_into()
+                .unwrap_or(u16::MAX);
+            f.set_cursor(cursor_x, area.y + 1);
+        }
+    }
+}
@@ -11,43 +11,210 @@ use crate::input_mode::InputMode;
 pub struct InputField {
     pub input: Input,
     pub input_mode: InputMode,
+    /// Entered messages, oldest first, which are also the history recalled with the arrow keys.
     pub messages: Vec<String>,
+    /// Index of the recalled message and the text which was being typed before recalling it.
+    browsing: Option<(usize, String)>,
+    search: Option<HistorySearch>,
+}
+
+/// State of a reverse incremental search through the history.
+struct HistorySearch {
+    query: String,
+    /// Index of the message matching `query`.
+    found: Option<usize>,
+    /// Input from before the search, restored when it is cancelled.
+    draft: String,
 }
 
 impl InputField {
+    /// Waits for a key and applies it to the input. Keys used to move through the history are
+    /// not returned.
     pub fn input_wait(&mut self) -> Option<KeyEvent> {
-        if let Ok(Event::Key(key)) = event::read() {
-            self.input.handle_event(&Event::Key(key));
-            return Some(key);
+        let key = key_wait()?;
+        match self.handle_key(&key) {
+            true => None,
+            false => Some(key),
+        }
+    }
+
+    /// Applies `key_event` to the input. Returns `true` if the key moved through the history,
+    /// so the view should not use it.
+    ///
+    /// Ctrl-P and Ctrl-N recall older and newer messages, as do Up and Down once something is
+    /// typed or recalled. Ctrl-R searches backwards through the history for what is typed next.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        if is_quit_event(key_event) {
+            return false;
+        }
+
+        if self.search.is_some() {
+            if self.search_key(key_event) {
+                return true;
+            }
+        } else {
+            let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+            let recalling = self.browsing.is_some() || !self.input.value().is_empty();
+
+            match key_event.code {
+                KeyCode::Char('r') if ctrl => {
+                    self.browsing = None;
+                    self.search = Some(HistorySearch {
+                        query: String::new(),
+                        found: None,
+                        draft: self.input.value().to_owned(),
+                    });
+                    return true;
+                }
+                KeyCode::Char('p') if ctrl => return self.recall_older(),
+                KeyCode::Char('n') if ctrl => return self.recall_newer(),
+                KeyCode::Up if recalling => return self.recall_older(),
+                KeyCode::Down if self.browsing.is_some() => return self.recall_newer(),
+                _ => {}
+            }
+        }
+
+        // Editing a recalled message makes it the text being typed.
+        self.browsing = None;
+        self.input.handle_event(&Event::Key(*key_event));
+        false
+    }
+
+    /// Title describing the running history search, if any.
+    pub fn search_prompt(&self) -> Option<String> {
+        let search = self.search.as_ref()?;
+        Some(match search.found {
+            Some(_) => format!("reverse-i-search: {}", search.query),
+            None if search.query.is_empty() => "reverse-i-search".to_owned(),
+            None => format!("failing reverse-i-search: {}", search.query),
+        })
+    }
+
+    fn recall_older(&mut self) -> bool {
+        let end = match &self.browsing {
+            Some((index, _)) => *index,
+            None => self.messages.len(),
+        };
+
+        if let Some(index) = (0..end).rev().find(|i| !self.messages[*i].is_empty()) {
+            let draft = match self.browsing.take() {
+                Some((_, draft)) => draft,
+                None => self.input.value().to_owned(),
+            };
+            self.input = Input::new(self.messages[index].to_owned());
+            self.browsing = Some((index, draft));
+        }
+        true
+    }
+
+    fn recall_newer(&mut self) -> bool {
+        let (current, draft) = match self.browsing.take() {
+            Some(browsing) => browsing,
+            None => return true,
+        };
+
+        match (current + 1..self.messages.len()).find(|i| !self.messages[*i].is_empty()) {
+            Some(index) => {
+                self.input = Input::new(self.messages[index].to_owned());
+                self.browsing = Some((index, draft));
+            }
+            None => self.input = Input::new(draft),
+        }
+        true
+    }
+
+    /// Handles a key during a history search. Keys which are not part of the search end it,
+    /// keeping the match, and are passed on.
+    fn search_key(&mut self, key_event: &KeyEvent) -> bool {
+        let mut search = match self.search.take() {
+            Some(search) => search,
+            None => return false,
+        };
+        let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+
+        match key_event.code {
+            KeyCode::Char('r') if ctrl => {
+                let end = search.found.unwrap_or(self.messages.len());
+                search.found = self.find_older(&search.query, end).or(search.found);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                search.query.push(c);
+                let end = search.found.map(|i| i + 1).unwrap_or(self.messages.len());
+                search.found = self.find_older(&search.query, end);
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.found = self.find_older(&search.query, self.messages.len());
+            }
+            KeyCode::Esc => {
+                self.input = Input::new(search.draft);
+                return true;
+            }
+            KeyCode::Enter => return true,
+            _ => return false,
+        }
+
+        if let Some(index) = search.found {
+            self.input = Input::new(self.messages[index].to_owned());
+        }
+        self.search = Some(search);
+        true
+    }
+
+    /// Newest message before `end` containing `query`.
+    fn find_older(&self, query: &str, end: usize) -> Option<usize> {
+        if query.is_empty() {
+            return None;
         }
 
-        None
+        (0..end.min(self.messages.len()))
+            .rev()
+            .find(|i| self.messages[*i].contains(query))
     }
 
     pub fn input_prompt<B: Backend>(
         &mut self,
         terminal: &mut Terminal<B>,
         msg: &str,
+    ) -> std::io::Result<&str> {
+        self.prompt(terminal, msg, false)
+    }
+
+    /// [`InputField::input_prompt`] which shows what is typed as `*`, for passwords.
+    pub fn password_prompt<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        msg: &str,
+    ) -> std::io::Result<&str> {
+        self.prompt(terminal, msg, true)
+    }
+
+    fn prompt<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        msg: &str,
+        hidden: bool,
     ) -> std::io::Result<&str> {
         self.input_mode = InputMode::Editing;
 
         loop {
             terminal.draw(|f| {
                 let size = f.size();
+                let value = match hidden {
+                    true => "*".repeat(self.input.value().chars().count()),
+                    false => self.input.value().to_owned(),
+                };
                 let block = Block::default()
-                    .title(msg.to_owned() + "\n" + self.input.value())
+                    .title(msg.to_owned() + "\n" + &value)
                     .borders(Borders::NONE);
                 let cursor_x = msg.len() + self.input.cursor();
-                f.set_cursor(cursor_x.try_into().unwrap_or(u16::max_value()), 0);
+                f.set_cursor(cursor_x.try_into().unwrap_or(u16::MAX), 0);
                 f.render_widget(block, size);
             })?;
 
             if let Some(key_event) = self.input_wait() {
                 if is_quit_event(&key_event) {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Quitting application",
-                    ));
+                    return Err(std::io::Error::other("Quitting application"));
                 } else if key_event.code == KeyCode::Enter {
                     self.enter_message();
                     return Ok(self
@@ -59,9 +226,26 @@ impl InputField {
         }
     }
 
+    /// Moves the input to the messages, dropping an earlier copy of the same message.
     pub fn enter_message(&mut self) {
-        self.messages.push(self.input.value().to_owned());
+        let message = self.input.value().to_owned();
+        if !message.is_empty() {
+            self.messages.retain(|entry| *entry != message);
+        }
+
+        self.messages.push(message);
         self.input.reset();
+        self.browsing = None;
+        self.search = None;
+    }
+
+    /// Text of the input up to the cursor.
+    pub fn before_cursor(&self) -> String {
+        self.input
+            .value()
+            .chars()
+            .take(self.input.cursor())
+            .collect()
     }
 
     pub fn last_message(&self) -> Option<String> {
@@ -75,10 +259,22 @@ impl Default for InputField {
             input: Input::default(),
             input_mode: InputMode::Editing,
             messages: Vec::new(),
+            browsing: None,
+            search: None,
         }
     }
 }
 
+/// Blocks until the next key event without feeding it to any input, for views which only
+/// react to single key presses.
+pub fn key_wait() -> Option<KeyEvent> {
+    if let Ok(Event::Key(key)) = event::read() {
+        return Some(key);
+    }
+
+    None
+}
+
 pub fn is_quit_event(key_event: &KeyEvent) -> bool {
     key_event.modifiers == KeyModifiers::CONTROL && key_event.code == KeyCode::Char('c')
 }
@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use tui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+/// Splits the screen of a list view into the list taking `list_percentage` of it, the detail
+/// of the selected entry and the help bar.
+pub fn areas(area: Rect, list_percentage: u16) -> Rc<[Rect]> {
+    Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage(list_percentage),
+                Constraint::Min(5),
+                Constraint::Length(3),
+            ]
+            .as_ref(),
+        )
+        .margin(1)
+        .split(area)
+}
+
+/// Splits the detail area of a list view into the detail and the git command preview under
+/// it.
+pub fn with_preview(area: Rect) -> Rc<[Rect]> {
+    Layout::default()
+        .constraints([Constraint::Min(2), Constraint::Length(3)].as_ref())
+        .split(area)
+}
+
+/// The table of entries with its column `headers`, highlighting the selected row.
+pub fn table<'a>(headers: &[&'a str], rows: Vec<Row<'a>>, title: String) -> Table<'a> {
+    let header_cells = headers
+        .iter()
+        .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
+    let header = Row::new(header_cells)
+        .style(Style::default().bg(Color::Blue))
+        .height(1)
+        .bottom_margin(1);
+
+    Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ")
+}
+
+/// The last message under the list, with the keys of the view as the title.
+pub fn help_bar<'a>(message: &'a str, keys: &'a str) -> Paragraph<'a> {
+    Paragraph::new(message)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(keys)
+                .style(Style::default().fg(Color::LightBlue)),
+        )
+        .style(Style::default().fg(Color::White))
+}
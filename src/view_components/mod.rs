@@ -1 +1,11 @@
+pub mod completion_popup;
+pub mod confirm_popup;
+pub mod diff_view;
+pub mod git_preview;
 pub mod input_field;
+pub mod list_frame;
+pub mod prompt_popup;
+pub mod table_filter;
+pub mod text_editor;
+pub mod transfer_popup;
+pub mod ui_services;
@@ -0,0 +1,69 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use super::confirm_popup::centered_rect;
+
+pub enum PromptResult {
+    Submitted(String),
+    Cancelled,
+    Pending,
+}
+
+/// Single line input drawn over the current view, the in-view version of
+/// [`InputField::input_prompt`](super::input_field::InputField::input_prompt).
+pub struct PromptPopup {
+    pub title: String,
+    pub input: Input,
+}
+
+impl PromptPopup {
+    pub fn new(title: &str, initial: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            input: Input::new(initial.to_owned()),
+        }
+    }
+
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> PromptResult {
+        match key_event.code {
+            KeyCode::Enter => PromptResult::Submitted(self.input.value().to_owned()),
+            KeyCode::Esc => PromptResult::Cancelled,
+            _ => {
+                self.input.handle_event(&Event::Key(*key_event));
+                PromptResult::Pending
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>) {
+        let popup = centered_rect(60, 20, f.size());
+        let area = Rect {
+            height: popup.height.min(3),
+            ..popup
+        };
+
+        let paragraph = Paragraph::new(self.input.value())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.title.as_str())
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+
+        let cursor_x = (area.x as usize + 1 + self.input.visual_cursor())
+            .try_into()
+            .unwrap_or(u16::MAX);
+        f.set_cursor(cursor_x, area.y + 1);
+    }
+}
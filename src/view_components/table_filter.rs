@@ -0,0 +1,163 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::TableState,
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{data_table::DataTable, fuzzy};
+
+/// A row of the table matching the filter.
+pub struct RowMatch {
+    /// Index of the row in [`DataTable::table_items`].
+    pub row: usize,
+    /// Matched char indices of every column of the row.
+    pub positions: Vec<Vec<usize>>,
+}
+
+pub enum FilterResult {
+    /// The filter took the key and the table should be redrawn.
+    Handled,
+    /// The filter was cleared, the full table is shown again.
+    Cleared,
+    /// The selection should move to the next or previous matching row.
+    Move { forward: bool },
+}
+
+/// Fuzzy filter narrowing a [`DataTable`] to the rows matching the typed text. The table keeps
+/// its own selection as an index of all rows, so clearing the filter leaves it on the same row.
+#[derive(Default)]
+pub struct TableFilter {
+    pub input: Input,
+    /// Whether typed keys go to the filter, rather than just showing the filtered rows.
+    pub editing: bool,
+    matches: Vec<RowMatch>,
+    /// Selection and scroll position within the matching rows.
+    state: TableState,
+}
+
+impl TableFilter {
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self) -> &[RowMatch] {
+        &self.matches
+    }
+
+    /// Matches the filter against every row. A row matches when any of its columns does.
+    pub fn update(&mut self, table: &DataTable) {
+        let pattern = self.input.value();
+
+        self.matches = table
+            .table_items
+            .iter()
+            .enumerate()
+            .filter_map(|(row, columns)| {
+                let positions: Vec<Vec<usize>> = columns
+                    .iter()
+                    .map(|column| fuzzy::fuzzy_match(pattern, column).unwrap_or_default())
+                    .collect();
+
+                let matched = pattern.is_empty() || positions.iter().any(|p| !p.is_empty());
+                matched.then_some(RowMatch { row, positions })
+            })
+            .collect();
+    }
+
+    /// Handles a key while the filter is being edited.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> FilterResult {
+        match key_event.code {
+            KeyCode::Esc => FilterResult::Cleared,
+            KeyCode::Enter => {
+                self.editing = false;
+                FilterResult::Handled
+            }
+            KeyCode::Down => FilterResult::Move { forward: true },
+            KeyCode::Up => FilterResult::Move { forward: false },
+            _ => {
+                self.input.handle_event(&Event::Key(*key_event));
+                FilterResult::Handled
+            }
+        }
+    }
+
+    /// Keeps `selected` if it still matches, otherwise picks the first matching row. Nothing is
+    /// selected while no row matches, so actions can't pick a hidden row.
+    pub fn stable_selection(&self, selected: Option<usize>) -> Option<usize> {
+        match selected {
+            Some(row) if self.matches.iter().any(|m| m.row == row) => Some(row),
+            _ => self.matches.first().map(|m| m.row),
+        }
+    }
+
+    /// Matching row after or before `selected`, wrapping around.
+    pub fn step(&self, selected: Option<usize>, forward: bool) -> Option<usize> {
+        let count = self.matches.len();
+        if count == 0 {
+            return selected;
+        }
+
+        let position = selected.and_then(|row| self.matches.iter().position(|m| m.row == row));
+        let next = match (position, forward) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        Some(self.matches[next].row)
+    }
+
+    /// Table state selecting `selected` within the matching rows, for drawing them.
+    pub fn table_state(&mut self, selected: Option<usize>) -> &mut TableState {
+        let position = selected.and_then(|row| self.matches.iter().position(|m| m.row == row));
+        self.state.select(position);
+        &mut self.state
+    }
+}
+
+/// `text` with the chars at `positions` highlighted.
+pub fn highlight(text: &str, positions: &[usize]) -> Text<'static> {
+    let matched_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    let mut positions = positions.iter().peekable();
+
+    let flush = |run: &mut String, matched: bool, spans: &mut Vec<Span<'static>>| {
+        if !run.is_empty() {
+            let style = if matched {
+                matched_style
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(std::mem::take(run), style));
+        }
+    };
+
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.next_if_eq(&&i).is_some();
+
+        if c == '\n' {
+            flush(&mut run, run_matched, &mut spans);
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            continue;
+        }
+
+        if matched != run_matched {
+            flush(&mut run, run_matched, &mut spans);
+            run_matched = matched;
+        }
+        run.push(c);
+    }
+    flush(&mut run, run_matched, &mut spans);
+    lines.push(Line::from(spans));
+
+    Text::from(lines)
+}
@@ -0,0 +1,151 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// Multi-line counterpart of `tui_input::Input`, used for commit and tag messages.
+pub struct TextEditor {
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor position in characters, not bytes.
+    col: usize,
+}
+
+impl Default for TextEditor {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+        }
+    }
+}
+
+impl TextEditor {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.lines().map(str::to_owned).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.row = 0;
+        self.col = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.trim().is_empty())
+    }
+
+    /// Applies an editing key, returning false when the key is not used by the editor.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        match key_event.code {
+            KeyCode::Char(c) => {
+                let byte = self.byte_index();
+                self.lines[self.row].insert(byte, c);
+                self.col += 1;
+            }
+            KeyCode::Enter => {
+                let byte = self.byte_index();
+                let rest = self.lines[self.row].split_off(byte);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.col = 0;
+            }
+            KeyCode::Backspace => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    let byte = self.byte_index();
+                    self.lines[self.row].remove(byte);
+                } else if self.row > 0 {
+                    let line = self.lines.remove(self.row);
+                    self.row -= 1;
+                    self.col = self.line_len();
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Delete => {
+                if self.col < self.line_len() {
+                    let byte = self.byte_index();
+                    self.lines[self.row].remove(byte);
+                } else if self.row + 1 < self.lines.len() {
+                    let line = self.lines.remove(self.row + 1);
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Left => {
+                if self.col > 0 {
+                    self.col -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.col = self.line_len();
+                }
+            }
+            KeyCode::Right => {
+                if self.col < self.line_len() {
+                    self.col += 1;
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.col = 0;
+                }
+            }
+            KeyCode::Up => {
+                if self.row > 0 {
+                    self.row -= 1;
+                    self.col = self.col.min(self.line_len());
+                }
+            }
+            KeyCode::Down => {
+                if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.col = self.col.min(self.line_len());
+                }
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Draws the text inside `block`, scrolled so the cursor stays visible.
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner_height = area.height.saturating_sub(2) as usize;
+        let scroll = (self.row + 1).saturating_sub(inner_height);
+
+        let paragraph = Paragraph::new(self.text())
+            .block(block)
+            .scroll((scroll.try_into().unwrap_or(u16::MAX), 0));
+        f.render_widget(paragraph, area);
+
+        let cursor_x = (area.x as usize + 1 + self.col)
+            .try_into()
+            .unwrap_or(u16::MAX);
+        let cursor_y = (area.y as usize + 1 + self.row - scroll)
+            .try_into()
+            .unwrap_or(u16::MAX);
+        f.set_cursor(cursor_x, cursor_y);
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map(|(i, _)| i)
+            .unwrap_or(self.lines[self.row].len())
+    }
+}
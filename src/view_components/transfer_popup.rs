@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Gauge},
+    Terminal,
+};
+
+use super::{confirm_popup::centered_rect, input_field::InputField};
+use crate::{
+    git::{credentials::Credentials, transfer::Progress},
+    traits::transfer_ui::TransferUi,
+};
+
+/// Progress callbacks come for every few objects, drawing that often would slow the transfer.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Progress bar of a fetch or push, drawn straight to the terminal while the transfer blocks
+/// the application loop.
+pub struct TransferPopup<'t, B: Backend> {
+    terminal: &'t mut Terminal<B>,
+    title: String,
+    last_draw: Option<Instant>,
+}
+
+impl<'t, B: Backend> TransferPopup<'t, B> {
+    pub fn new(terminal: &'t mut Terminal<B>) -> Self {
+        Self {
+            terminal,
+            title: String::new(),
+            last_draw: None,
+        }
+    }
+
+    fn draw(&mut self, progress: Option<&Progress>) {
+        let (ratio, label) = match progress {
+            Some(progress) if progress.total > 0 => (
+                (progress.done as f64 / progress.total as f64).min(1.0),
+                format!(
+                    "{} {}/{} ({})",
+                    progress.stage,
+                    progress.done,
+                    progress.total,
+                    format_bytes(progress.bytes)
+                ),
+            ),
+            _ => (0.0, "Connecting".to_owned()),
+        };
+        let title = self.title.as_str();
+
+        // Nothing can be done about a failed draw in the middle of a transfer, the next
+        // progress report tries again.
+        let _ = self.terminal.draw(|f| {
+            let popup = centered_rect(60, 20, f.size());
+            let area = Rect {
+                height: popup.height.min(3),
+                ..popup
+            };
+
+            let gauge = Gauge::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(title)
+                        .style(Style::default().fg(Color::LightBlue)),
+                )
+                .gauge_style(Style::default().fg(Color::Green).bg(Color::Black))
+                .ratio(ratio)
+                .label(label);
+
+            f.render_widget(Clear, area);
+            f.render_widget(gauge, area);
+        });
+        self.last_draw = Some(Instant::now());
+    }
+}
+
+impl<B: Backend> TransferUi for TransferPopup<'_, B> {
+    fn start(&mut self, title: &str) {
+        self.title = title.to_owned();
+        self.draw(None);
+    }
+
+    fn progress(&mut self, progress: &Progress) {
+        let due = self
+            .last_draw
+            .is_none_or(|last| last.elapsed() >= REDRAW_INTERVAL);
+        if due || progress.done == progress.total {
+            self.draw(Some(progress));
+        }
+    }
+
+    fn credentials(&mut self, url: &str, username: Option<&str>) -> Option<Credentials> {
+        // A fresh input each time, so nothing typed here ends up in the command history.
+        let username = match username {
+            Some(username) => username.to_owned(),
+            None => InputField::default()
+                .input_prompt(self.terminal, &format!("Username for {url}:"))
+                .ok()?
+                .to_owned(),
+        };
+        let password = InputField::default()
+            .password_prompt(self.terminal, &format!("Password for {username} at {url}:"))
+            .ok()?
+            .to_owned();
+
+        self.draw(None);
+        Some(Credentials { username, password })
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
@@ -0,0 +1,100 @@
+use crossterm::event::KeyEvent;
+use tui::{
+    backend::Backend,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::confirm_popup::{centered_rect, ConfirmPopup, ConfirmResult};
+
+struct MessagePopup {
+    title: String,
+    lines: Vec<String>,
+}
+
+/// UI a command can drive in the view it was entered in.
+#[derive(Default)]
+pub struct UiServices {
+    popup: Option<MessagePopup>,
+    /// Confirmation asked for by a command, with the command to run once it is accepted.
+    confirm: Option<(ConfirmPopup, String)>,
+    confirmed_command: Option<String>,
+}
+
+impl UiServices {
+    /// Shows `lines` in a popup over the view until the next key press.
+    pub fn show_popup(&mut self, title: &str, lines: Vec<String>) {
+        self.popup = Some(MessagePopup {
+            title: title.to_owned(),
+            lines,
+        });
+    }
+
+    /// Asks to confirm `lines` before `command` runs again with the confirmation given.
+    pub fn confirm(&mut self, title: &str, lines: Vec<String>, command: String) {
+        self.confirm = Some((ConfirmPopup::new(title, lines), command));
+    }
+
+    pub fn has_popup(&self) -> bool {
+        self.popup.is_some() || self.confirm.is_some()
+    }
+
+    /// Closes the popup or answers the confirmation. Returns `true` when the key was used to do
+    /// so and should not reach the view.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        if let Some((popup, command)) = self.confirm.take() {
+            match popup.handle_key(key_event) {
+                ConfirmResult::Accepted => self.confirmed_command = Some(command),
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.confirm = Some((popup, command)),
+            }
+            return true;
+        }
+
+        self.popup.take().is_some()
+    }
+
+    /// The command whose confirmation was just accepted, for the view to run.
+    pub fn take_confirmed_command(&mut self) -> Option<String> {
+        self.confirmed_command.take()
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>) {
+        if let Some((popup, _)) = &self.confirm {
+            popup.render(f);
+            return;
+        }
+
+        let popup = match &self.popup {
+            Some(popup) => popup,
+            None => return,
+        };
+        let area = centered_rect(60, 60, f.size());
+
+        let mut text: Vec<Line> = popup
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "Press any key to close",
+            Style::default().fg(Color::Yellow),
+        )));
+
+        let paragraph = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(popup.title.as_str())
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+}
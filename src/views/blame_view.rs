@@ -0,0 +1,273 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::event::{KeyCode, KeyEventKind};
+use git2::{Oid, Repository};
+use tui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::blame::{self, BlameLine},
+    traits::display_view::DisplayView,
+    view_components::input_field,
+};
+
+/// Lines moved by PageUp and PageDown.
+const PAGE: isize = 20;
+
+/// Shows every line of a file with the commit which last changed it, and follows lines back
+/// through older commits.
+pub struct BlameView<'a> {
+    path: String,
+    /// Commit the file is blamed at.
+    at: Oid,
+    lines: Vec<BlameLine>,
+    table: DataTable,
+    /// Blames shown before this one with their selected line, Backspace goes back to them.
+    history: Vec<(String, Oid, Option<usize>)>,
+    /// Seconds since the epoch the ages are counted from.
+    now: i64,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+}
+
+impl<'a> BlameView<'a> {
+    pub fn new(repo: &'a Repository, path: &str, at: Oid) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() as i64)
+            .unwrap_or_default();
+
+        let mut view = Self {
+            path: path.to_owned(),
+            at,
+            lines: Vec::new(),
+            table: DataTable::default(),
+            history: Vec::new(),
+            now,
+            repo,
+            force_draw: true,
+            message: String::new(),
+        };
+
+        if let Err(err) = view.load(path, at) {
+            view.message = err.message().to_owned();
+        }
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    /// Blames `path` at `at`, keeping the current blame when it fails.
+    fn load(&mut self, path: &str, at: Oid) -> Result<(), git2::Error> {
+        self.lines = blame::load_blame(self.repo, path, at)?;
+        self.path = path.to_owned();
+        self.at = at;
+
+        // The commit is only shown on the first of the lines it changed in a row.
+        let mut previous = None;
+        self.table.table_items = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let first = previous != Some(line.commit);
+                previous = Some(line.commit);
+
+                let mut row = match first {
+                    true => vec![
+                        line.commit.to_string()[..7].to_owned(),
+                        line.author.to_owned(),
+                        blame::format_age(self.now, line.time),
+                    ],
+                    false => vec![String::new(); 3],
+                };
+                row.push((i + 1).to_string());
+                row.push(line.content.replace('\t', "    "));
+                row
+            })
+            .collect();
+        self.table.clamp_selection();
+        Ok(())
+    }
+
+    fn selected_line(&self) -> Option<&BlameLine> {
+        self.lines.get(self.table.table_state.selected()?)
+    }
+
+    /// Blames the file as it was before the selected line last changed, on the same line
+    /// number as far as the older file has it.
+    fn blame_parent(&mut self) {
+        let (path, parent) = match self.selected_line() {
+            Some(line) => match blame::parent_of(self.repo, line) {
+                Ok(parent) => (line.path.to_owned(), parent),
+                Err(err) => {
+                    self.message = err.message().to_owned();
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        let current = (
+            self.path.to_owned(),
+            self.at,
+            self.table.table_state.selected(),
+        );
+        match self.load(&path, parent) {
+            Ok(()) => {
+                self.history.push(current);
+                self.message = format!("Blaming {path} at {}", &parent.to_string()[..7]);
+            }
+            Err(err) => self.message = err.message().to_owned(),
+        }
+    }
+
+    /// Goes back to the blame shown before the last `blame_parent`.
+    fn back(&mut self) {
+        let (path, at, selected) = match self.history.pop() {
+            Some(previous) => previous,
+            None => {
+                self.message = "This is the first blame".to_owned();
+                return;
+            }
+        };
+
+        match self.load(&path, at) {
+            Ok(()) => {
+                self.table.table_state.select(selected);
+                self.table.clamp_selection();
+                self.message.clear();
+            }
+            Err(err) => self.message = err.message().to_owned(),
+        }
+    }
+
+    fn move_by(&mut self, lines: isize) {
+        let count = self.table.table_items.len();
+        if count == 0 {
+            return;
+        }
+
+        let selected = self.table.table_state.selected().unwrap_or_default() as isize;
+        let selected = (selected + lines).clamp(0, count as isize - 1);
+        self.table.table_state.select(Some(selected as usize));
+    }
+}
+
+/// Newer lines are brighter, so recent changes stand out.
+fn age_color(now: i64, time: i64) -> Color {
+    const DAY: i64 = 86_400;
+
+    match now - time {
+        age if age < 7 * DAY => Color::LightGreen,
+        age if age < 30 * DAY => Color::Green,
+        age if age < 180 * DAY => Color::Yellow,
+        age if age < 365 * DAY => Color::Blue,
+        _ => Color::DarkGray,
+    }
+}
+
+impl DisplayView for BlameView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    match key_event.code {
+                        KeyCode::Down => self.arrow_down(),
+                        KeyCode::Up => self.arrow_up(),
+                        KeyCode::PageDown => self.move_by(PAGE),
+                        KeyCode::PageUp => self.move_by(-PAGE),
+                        KeyCode::Enter => {
+                            if let Some(line) = self.selected_line() {
+                                return AppLoopFlag::switch_view(SwitchView::Log(line.commit));
+                            }
+                        }
+                        KeyCode::Char('p') => self.blame_parent(),
+                        KeyCode::Backspace => self.back(),
+                        KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                        _ => {}
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        let rects = Layout::default()
+            .constraints([Constraint::Min(5), Constraint::Length(3)].as_ref())
+            .margin(1)
+            .split(f.size());
+
+        let rows = self
+            .table
+            .table_items
+            .iter()
+            .zip(&self.lines)
+            .map(|(item, line)| {
+                let blamed = Style::default().fg(age_color(self.now, line.time));
+                let cells = item.iter().enumerate().map(|(i, c)| match i {
+                    0..=2 => Cell::from(c.to_owned()).style(blamed),
+                    3 => Cell::from(c.to_owned()).style(Style::default().fg(Color::DarkGray)),
+                    _ => Cell::from(c.to_owned()),
+                });
+                Row::new(cells)
+            });
+
+        let summary = self
+            .repo
+            .find_commit(self.at)
+            .ok()
+            .and_then(|commit| commit.summary().map(str::to_owned))
+            .unwrap_or_default();
+        let title = format!(
+            "Blame - {} at {} {summary}",
+            self.path,
+            &self.at.to_string()[..7]
+        );
+        let table = Table::new(rows)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .widths(&[
+                Constraint::Length(7),
+                Constraint::Length(16),
+                Constraint::Length(10),
+                Constraint::Length(5),
+                Constraint::Percentage(100),
+            ]);
+        f.render_stateful_widget(table, rects[0], &mut self.table.table_state);
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(
+                        "Enter: show the commit in the log | p: blame before this line changed | \
+                         Backspace: previous blame | PgUp/PgDn: scroll | Esc: back",
+                    )
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        f.render_widget(help, rects[1]);
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.move_by(1);
+    }
+
+    fn arrow_up(&mut self) {
+        self.move_by(-1);
+    }
+}
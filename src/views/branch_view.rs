@@ -0,0 +1,336 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use git2::Repository;
+use tui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        branch::{self, BranchEntry, BranchKind},
+    },
+    traits::display_view::DisplayView,
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        git_preview::GitPreview,
+        input_field,
+        prompt_popup::{PromptPopup, PromptResult},
+    },
+};
+
+enum BranchPopup {
+    Create(PromptPopup),
+    Rename(String, PromptPopup),
+    Delete(ConfirmPopup),
+}
+
+pub struct BranchView<'a> {
+    pub branches: DataTable,
+    entries: Vec<BranchEntry>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    popup: Option<BranchPopup>,
+    preview: GitPreview,
+}
+
+impl<'a> BranchView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            branches: DataTable::default(),
+            entries: Vec::new(),
+            repo,
+            force_draw: true,
+            message: String::new(),
+            popup: None,
+            preview: GitPreview::default(),
+        };
+
+        view.refresh();
+        view
+    }
+
+    pub fn refresh(&mut self) {
+        match branch::load_branches(self.repo) {
+            Ok(entries) => self.entries = entries,
+            Err(err) => {
+                self.entries.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+
+        self.branches.table_items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let head = if entry.is_head { "* " } else { "  " };
+                let tracking = match entry.ahead_behind {
+                    Some((0, 0)) => "up to date".to_owned(),
+                    Some((ahead, behind)) => format!("↑{ahead} ↓{behind}"),
+                    None => String::new(),
+                };
+
+                vec![
+                    format!("{head}{}", entry.name),
+                    entry.upstream.to_owned().unwrap_or_default(),
+                    tracking,
+                    format!("{} {}", entry.short_id, entry.summary),
+                ]
+            })
+            .collect();
+        self.branches.clamp_selection();
+    }
+
+    fn selected_entry(&self) -> Option<&BranchEntry> {
+        self.entries.get(self.branches.table_state.selected()?)
+    }
+
+    fn run(&mut self, actions: &[GitAction]) {
+        self.message = match action::execute_all(self.repo, actions) {
+            Ok(summary) => summary,
+            Err(err) => err.message().to_owned(),
+        };
+        self.refresh();
+    }
+
+    fn checkout_action(&self) -> Option<GitAction> {
+        let entry = self.selected_entry()?;
+        Some(GitAction::Checkout {
+            branch: entry.name.to_owned(),
+            remote: entry.kind == BranchKind::Remote,
+        })
+    }
+
+    fn delete_action(&self) -> Option<GitAction> {
+        let entry = self.selected_entry()?;
+        Some(GitAction::DeleteBranch {
+            name: entry.name.to_owned(),
+            remote: entry.kind == BranchKind::Remote,
+        })
+    }
+
+    /// Action of the open popup, or the checkout of the selected branch without one.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        let action = match &self.popup {
+            Some(BranchPopup::Create(prompt)) => Some(GitAction::CreateBranch {
+                name: prompt.input.value().trim().to_owned(),
+            }),
+            Some(BranchPopup::Rename(old_name, prompt)) => Some(GitAction::RenameBranch {
+                old_name: old_name.to_owned(),
+                new_name: prompt.input.value().trim().to_owned(),
+            }),
+            Some(BranchPopup::Delete(_)) => self.delete_action(),
+            None => self.checkout_action(),
+        };
+
+        action.into_iter().collect()
+    }
+
+    fn checkout_selected(&mut self) {
+        if let Some(action) = self.checkout_action() {
+            self.run(&[action]);
+        }
+    }
+
+    fn handle_popup_key(&mut self, popup: BranchPopup, key_event: &KeyEvent) {
+        match popup {
+            BranchPopup::Create(mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(name) => {
+                    let name = name.trim().to_owned();
+                    self.run(&[GitAction::CreateBranch { name }]);
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(BranchPopup::Create(prompt)),
+            },
+            BranchPopup::Rename(old_name, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(name) => {
+                    let new_name = name.trim().to_owned();
+                    self.run(&[GitAction::RenameBranch { old_name, new_name }]);
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => {
+                    self.popup = Some(BranchPopup::Rename(old_name, prompt));
+                }
+            },
+            BranchPopup::Delete(confirm) => match confirm.handle_key(key_event) {
+                ConfirmResult::Accepted => {
+                    if let Some(action) = self.delete_action() {
+                        self.run(&[action]);
+                    }
+                }
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.popup = Some(BranchPopup::Delete(confirm)),
+            },
+        }
+    }
+
+    fn open_rename(&mut self) {
+        match self.selected_entry() {
+            Some(entry) if entry.kind == BranchKind::Local => {
+                let name = entry.name.to_owned();
+                let prompt = PromptPopup::new(&format!("Rename '{name}' to"), &name);
+                self.popup = Some(BranchPopup::Rename(name, prompt));
+            }
+            Some(_) => self.message = "Only local branches can be renamed".to_owned(),
+            None => {}
+        }
+    }
+
+    fn open_delete(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let mut lines = vec![format!("Delete branch '{}'?", entry.name)];
+            if entry.kind == BranchKind::Remote {
+                lines.push(
+                    "Only the remote-tracking ref is removed, the remote is left as is.".to_owned(),
+                );
+            } else if let Some((ahead, _)) = entry.ahead_behind.filter(|(ahead, _)| *ahead > 0) {
+                lines.push(format!(
+                    "{ahead} commit(s) have not been pushed to the upstream."
+                ));
+            }
+
+            self.popup = Some(BranchPopup::Delete(ConfirmPopup::new(
+                "Delete branch",
+                lines,
+            )));
+        }
+    }
+}
+
+fn branch_style(entry: &BranchEntry) -> Style {
+    if entry.is_head {
+        Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        match entry.kind {
+            BranchKind::Local => Style::default().fg(Color::Yellow),
+            BranchKind::Remote => Style::default().fg(Color::Red),
+        }
+    }
+}
+
+impl DisplayView for BranchView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    if self.preview.is_editing() {
+                        if let Some(actions) = self.preview.handle_key(&key_event) {
+                            self.run(&actions);
+                        }
+                    } else if let Some(popup) = self.popup.take() {
+                        self.handle_popup_key(popup, &key_event);
+                    } else {
+                        match key_event.code {
+                            KeyCode::Down => self.arrow_down(),
+                            KeyCode::Up => self.arrow_up(),
+                            KeyCode::Enter | KeyCode::Char('c') => self.checkout_selected(),
+                            KeyCode::Char('n') => {
+                                self.popup =
+                                    Some(BranchPopup::Create(PromptPopup::new("New branch", "")));
+                            }
+                            KeyCode::Char('r') => self.open_rename(),
+                            KeyCode::Char('d') => self.open_delete(),
+                            KeyCode::Char('e') => self.preview.start_editing(),
+                            KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = Layout::default()
+            .constraints(
+                [
+                    Constraint::Min(5),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .margin(1)
+            .split(f.size());
+
+        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+        let normal_style = Style::default().bg(Color::Blue);
+
+        let header_cells = ["Branch", "Upstream", "Ahead/Behind", "Commit"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
+        let header = Row::new(header_cells)
+            .style(normal_style)
+            .height(1)
+            .bottom_margin(1);
+
+        let rows = self
+            .branches
+            .table_items
+            .iter()
+            .zip(self.entries.iter())
+            .map(|(item, entry)| {
+                let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+                Row::new(cells).style(branch_style(entry))
+            });
+
+        let table = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("Branches"))
+            .highlight_style(selected_style)
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Percentage(25),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(40),
+            ]);
+
+        f.render_stateful_widget(table, rects[0], &mut self.branches.table_state);
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(
+                        "Enter: checkout | n: new | r: rename | d: delete | e: edit command | \
+                         Esc: back",
+                    )
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        self.preview.render(f, rects[1], "e");
+        f.render_widget(help, rects[2]);
+
+        match &self.popup {
+            Some(BranchPopup::Create(prompt)) | Some(BranchPopup::Rename(_, prompt)) => {
+                prompt.render(f)
+            }
+            Some(BranchPopup::Delete(confirm)) => confirm.render(f),
+            None => {}
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.branches.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.branches.select_previous();
+    }
+}
@@ -0,0 +1,163 @@
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use git2::Repository;
+use tui::{
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    git::{
+        action::{self, GitAction},
+        status::{self, FileState},
+    },
+    traits::display_view::DisplayView,
+    view_components::{git_preview::GitPreview, input_field, text_editor::TextEditor},
+};
+
+pub struct CommitView<'a> {
+    pub editor: TextEditor,
+    staged_files: Vec<String>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    preview: GitPreview,
+}
+
+impl<'a> CommitView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            editor: TextEditor::default(),
+            staged_files: Vec::new(),
+            repo,
+            force_draw: true,
+            message: String::new(),
+            preview: GitPreview::default(),
+        };
+
+        match status::load_status(repo) {
+            Ok(entries) => {
+                view.staged_files = entries
+                    .iter()
+                    .filter(|entry| entry.state == FileState::Staged)
+                    .map(|entry| format!("{:<10} {}", entry.change, entry.path))
+                    .collect();
+            }
+            Err(err) => view.message = err.message().to_owned(),
+        }
+
+        if view.staged_files.is_empty() && view.message.is_empty() {
+            view.message = "Nothing staged, use the status view to stage files".to_owned();
+        }
+
+        view
+    }
+
+    fn commit_action(&self) -> GitAction {
+        GitAction::Commit {
+            message: self.editor.text(),
+        }
+    }
+
+    /// Returns whether the actions, normally the commit, ran successfully.
+    fn run(&mut self, actions: &[GitAction]) -> bool {
+        match action::execute_all(self.repo, actions) {
+            Ok(_) => true,
+            Err(err) => {
+                self.message = err.message().to_owned();
+                false
+            }
+        }
+    }
+}
+
+impl DisplayView for CommitView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    let control = key_event.modifiers == KeyModifiers::CONTROL;
+
+                    if self.preview.is_editing() {
+                        if let Some(actions) = self.preview.handle_key(&key_event) {
+                            if self.run(&actions) {
+                                return AppLoopFlag::switch_view(SwitchView::Repo);
+                            }
+                        }
+                    } else if control && key_event.code == KeyCode::Char('s') {
+                        if self.run(&[self.commit_action()]) {
+                            return AppLoopFlag::switch_view(SwitchView::Repo);
+                        }
+                    } else if control && key_event.code == KeyCode::Char('e') {
+                        self.preview.start_editing();
+                    } else if key_event.code == KeyCode::Esc {
+                        return AppLoopFlag::switch_view(SwitchView::Repo);
+                    } else {
+                        self.editor.handle_key(&key_event);
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(vec![self.commit_action()]);
+
+        let rects = Layout::default()
+            .constraints(
+                [
+                    Constraint::Percentage(30),
+                    Constraint::Min(5),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .margin(1)
+            .split(f.size());
+
+        let staged: Vec<ListItem> = self
+            .staged_files
+            .iter()
+            .map(|file| ListItem::new(file.as_str()).style(Style::default().fg(Color::Green)))
+            .collect();
+        let staged_list = List::new(staged).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Staged Files ({})", self.staged_files.len())),
+        );
+        f.render_widget(staged_list, rects[0]);
+
+        self.editor.render(
+            f,
+            rects[1],
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Commit Message")
+                .style(Style::default().fg(Color::White)),
+        );
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Ctrl-S: commit | Ctrl-E: edit command | Esc: cancel")
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        self.preview.render(f, rects[2], "Ctrl-E");
+        f.render_widget(help, rects[3]);
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {}
+
+    fn arrow_up(&mut self) {}
+}
@@ -0,0 +1,497 @@
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyEventKind};
+use git2::Repository;
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction, SequenceCommand},
+        conflict::{self, ConflictEntry, ConflictHunk, ConflictSide},
+        merge,
+        rebase::{self, RebaseState},
+    },
+    traits::display_view::DisplayView,
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        git_preview::GitPreview,
+        input_field,
+    },
+};
+
+/// Lists the conflicted files of a stopped rebase, cherry-pick, revert or merge, resolves them
+/// and goes on with the operation.
+pub struct ConflictView<'a> {
+    conflicts: Vec<ConflictEntry>,
+    table: DataTable,
+    /// The stopped operation the conflicts come from, `None` when it wasn't started here.
+    state: Option<RebaseState>,
+    /// Title of the merge in progress, see [`merge::in_progress`].
+    merge: Option<String>,
+    repo: &'a Repository,
+    /// Conflict of the selected file which `O`, `T` and `B` resolve.
+    hunk: usize,
+    /// Lines the content of the selected file is scrolled by.
+    scroll: u16,
+    force_draw: bool,
+    message: String,
+    confirm_abort: Option<ConfirmPopup>,
+    preview: GitPreview,
+}
+
+impl<'a> ConflictView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            conflicts: Vec::new(),
+            table: DataTable::default(),
+            state: None,
+            merge: None,
+            repo,
+            hunk: 0,
+            scroll: 0,
+            force_draw: true,
+            message: String::new(),
+            confirm_abort: None,
+            preview: GitPreview::default(),
+        };
+
+        view.refresh();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    pub fn refresh(&mut self) {
+        match conflict::load_conflicts(self.repo) {
+            Ok(conflicts) => self.conflicts = conflicts,
+            Err(err) => {
+                self.conflicts.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+        match RebaseState::load(self.repo) {
+            Ok(state) => self.state = state,
+            Err(err) => self.message = err.message().to_owned(),
+        }
+        self.merge = merge::in_progress(self.repo);
+
+        self.table.table_items = self
+            .conflicts
+            .iter()
+            .map(|entry| vec![entry.describe().to_owned(), entry.path.to_owned()])
+            .collect();
+        self.table.clamp_selection();
+
+        if self.conflicts.is_empty() && self.message.is_empty() {
+            self.message = match (&self.state, &self.merge) {
+                (Some(state), _) => format!(
+                    "All conflicts are resolved, press c to continue the {}",
+                    state.kind.name()
+                ),
+                (None, Some(_)) => {
+                    "All conflicts are resolved, press c to commit the merge".to_owned()
+                }
+                (None, None) => "No conflicts".to_owned(),
+            };
+        }
+        self.hunk = self.hunk.min(self.hunks().len().saturating_sub(1));
+    }
+
+    fn selected_entry(&self) -> Option<&ConflictEntry> {
+        self.conflicts.get(self.table.table_state.selected()?)
+    }
+
+    /// Name of the operation the conflicts stop, which is also the command going on with it.
+    fn operation(&self) -> Option<&'static str> {
+        match (&self.state, &self.merge) {
+            (Some(state), _) => Some(state.kind.name()),
+            (None, Some(_)) => Some("merge"),
+            (None, None) => None,
+        }
+    }
+
+    /// The working tree version of the selected file, `None` when it was deleted.
+    fn content(&self) -> Option<String> {
+        let entry = self.selected_entry()?;
+        let content = fs::read(self.repo.workdir()?.join(&entry.path)).ok()?;
+        Some(String::from_utf8_lossy(&content).into_owned())
+    }
+
+    fn hunks(&self) -> Vec<ConflictHunk> {
+        self.content()
+            .map(|content| conflict::conflict_hunks(&content))
+            .unwrap_or_default()
+    }
+
+    /// Selects the next or previous conflict of the file and scrolls to it.
+    fn select_hunk(&mut self, forward: bool) {
+        let hunks = self.hunks();
+        if hunks.is_empty() {
+            self.message = "No conflict markers left in this file".to_owned();
+            return;
+        }
+
+        self.hunk = match forward {
+            true => (self.hunk + 1).min(hunks.len() - 1),
+            false => self.hunk.saturating_sub(1),
+        };
+        self.scroll_to_hunk(&hunks);
+    }
+
+    fn scroll_to_hunk(&mut self, hunks: &[ConflictHunk]) {
+        if let Some(hunk) = hunks.get(self.hunk) {
+            // A couple of lines before the conflict show where it is.
+            self.scroll = hunk.start.saturating_sub(2) as u16;
+        }
+    }
+
+    fn resolve_hunk(&mut self, side: ConflictSide) {
+        let path = match self.selected_entry() {
+            Some(entry) => entry.path.to_owned(),
+            None => return,
+        };
+
+        self.message = match conflict::take_hunk_side(self.repo, &path, self.hunk, side) {
+            Ok(0) => format!(
+                "Took {} in the last conflict of {path}, press Enter to mark it resolved",
+                side.name()
+            ),
+            Ok(left) => format!("Took {}, {left} conflict(s) left in {path}", side.name()),
+            Err(err) => err.message().to_owned(),
+        };
+        let hunks = self.hunks();
+        self.hunk = self.hunk.min(hunks.len().saturating_sub(1));
+        self.scroll_to_hunk(&hunks);
+    }
+
+    /// Takes `side` for the whole selected file and marks it resolved, or only marks it
+    /// resolved without a side.
+    fn resolve_actions(&self, side: Option<ConflictSide>) -> Vec<GitAction> {
+        let paths = match self.selected_entry() {
+            Some(entry) => vec![entry.path.to_owned()],
+            None => return Vec::new(),
+        };
+
+        let mut actions = Vec::new();
+        if let Some(side) = side {
+            actions.push(GitAction::CheckoutSide {
+                side,
+                paths: paths.clone(),
+            });
+        }
+        actions.push(GitAction::Add { paths });
+        actions
+    }
+
+    /// `command` for the stopped rebase, cherry-pick, revert or merge, `None` when nothing is
+    /// stopped or for skipping in a merge.
+    fn sequence_action(&self, command: SequenceCommand) -> Option<GitAction> {
+        match (&self.state, &self.merge, command) {
+            (Some(state), _, command) => Some(GitAction::Sequence {
+                kind: state.kind,
+                command,
+            }),
+            (None, Some(_), SequenceCommand::Continue) => Some(GitAction::MergeContinue),
+            (None, Some(_), SequenceCommand::Abort) => Some(GitAction::MergeAbort),
+            (None, _, _) => None,
+        }
+    }
+
+    /// Marking the selected file resolved, or continuing once no conflicts are left.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        match self.conflicts.is_empty() {
+            true => self
+                .sequence_action(SequenceCommand::Continue)
+                .into_iter()
+                .collect(),
+            false => self.resolve_actions(None),
+        }
+    }
+
+    fn resolve(&mut self, side: Option<ConflictSide>) {
+        let actions = self.resolve_actions(side);
+        if !actions.is_empty() {
+            self.run(&actions);
+        }
+    }
+
+    /// Runs `actions`, returning whether they went past the conflicts of the stopped
+    /// operation, which leaves this view.
+    fn run(&mut self, actions: &[GitAction]) -> bool {
+        let stopped = self.operation().is_some();
+        let passed = match action::execute_all(self.repo, actions) {
+            Ok(summary) => {
+                self.message = summary;
+                stopped
+                    && !rebase::stopped_on_conflict(self.repo)
+                    && merge::in_progress(self.repo).is_none()
+            }
+            Err(err) => {
+                self.message = err.message().to_owned();
+                false
+            }
+        };
+        self.refresh();
+        passed
+    }
+
+    /// Continues or skips in the stopped operation, continuing a merge commits it. Returns whether it
+    /// went past its conflicts, which leaves this view.
+    fn proceed(&mut self, skip: bool) -> bool {
+        let command = match skip {
+            true => SequenceCommand::Skip,
+            false => SequenceCommand::Continue,
+        };
+        if let Some(action) = self.sequence_action(command) {
+            return self.run(&[action]);
+        }
+
+        self.message = match &self.merge {
+            Some(_) => "A merge can't be skipped, abort it instead".to_owned(),
+            None => "No rebase, cherry-pick, revert or merge is in progress, commit the \
+                     resolution instead"
+                .to_owned(),
+        };
+        false
+    }
+
+    fn request_abort(&mut self) {
+        match self.operation() {
+            Some(name) => {
+                self.confirm_abort = Some(ConfirmPopup::new(
+                    &format!("Abort {name}"),
+                    vec![format!(
+                        "Abort the {name}? The branch goes back to where it was and the \
+                         resolved conflicts are thrown away."
+                    )],
+                ));
+            }
+            None => {
+                self.message = "No rebase, cherry-pick, revert or merge is in progress".to_owned()
+            }
+        }
+    }
+
+    /// Returns whether the operation was aborted.
+    fn abort(&mut self) -> bool {
+        match self.sequence_action(SequenceCommand::Abort) {
+            Some(action) => self.run(&[action]),
+            None => false,
+        }
+    }
+
+    /// The working tree version of the selected file with its conflict markers highlighted, the
+    /// markers of the selected conflict reversed.
+    fn content_lines(&self) -> Vec<Line<'static>> {
+        if self.selected_entry().is_none() {
+            return Vec::new();
+        }
+        let content = match self.content() {
+            Some(content) => content,
+            None => {
+                return vec![Line::from(Span::styled(
+                    "(not in the working tree)",
+                    Style::default().fg(Color::DarkGray),
+                ))]
+            }
+        };
+        let selected = conflict::conflict_hunks(&content)
+            .into_iter()
+            .nth(self.hunk)
+            .map(|hunk| hunk.start..=hunk.end);
+
+        let marker_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let mut section_style = Style::default();
+
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let marker = match line.get(..7) {
+                    Some("<<<<<<<") => Some(Style::default().fg(Color::Green)),
+                    Some("|||||||") => Some(Style::default().fg(Color::DarkGray)),
+                    Some("=======") => Some(Style::default().fg(Color::Cyan)),
+                    Some(">>>>>>>") => Some(Style::default()),
+                    _ => None,
+                };
+
+                let line = line.replace('\t', "    ");
+                match marker {
+                    Some(next_style) => {
+                        section_style = next_style;
+                        let style = match selected.as_ref().is_some_and(|hunk| hunk.contains(&i)) {
+                            true => marker_style.add_modifier(Modifier::REVERSED),
+                            false => marker_style,
+                        };
+                        Line::from(Span::styled(line, style))
+                    }
+                    None => Line::from(Span::styled(line, section_style)),
+                }
+            })
+            .collect()
+    }
+}
+
+impl DisplayView for ConflictView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    if self.preview.is_editing() {
+                        if let Some(actions) = self.preview.handle_key(&key_event) {
+                            if self.run(&actions) {
+                                return AppLoopFlag::switch_view(SwitchView::Repo)
+                                    .with_message(std::mem::take(&mut self.message));
+                            }
+                        }
+                    } else if let Some(popup) = self.confirm_abort.take() {
+                        match popup.handle_key(&key_event) {
+                            ConfirmResult::Accepted => {
+                                if self.abort() {
+                                    return AppLoopFlag::switch_view(SwitchView::Repo)
+                                        .with_message(std::mem::take(&mut self.message));
+                                }
+                            }
+                            ConfirmResult::Declined => {}
+                            ConfirmResult::Pending => self.confirm_abort = Some(popup),
+                        }
+                    } else {
+                        match key_event.code {
+                            KeyCode::Down => self.arrow_down(),
+                            KeyCode::Up => self.arrow_up(),
+                            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                            KeyCode::Char('o') => self.resolve(Some(ConflictSide::Ours)),
+                            KeyCode::Char('t') => self.resolve(Some(ConflictSide::Theirs)),
+                            KeyCode::Char('b') => self.resolve(Some(ConflictSide::Base)),
+                            KeyCode::Char('O') => self.resolve_hunk(ConflictSide::Ours),
+                            KeyCode::Char('T') => self.resolve_hunk(ConflictSide::Theirs),
+                            KeyCode::Char('B') => self.resolve_hunk(ConflictSide::Base),
+                            KeyCode::Char(']') => self.select_hunk(true),
+                            KeyCode::Char('[') => self.select_hunk(false),
+                            KeyCode::Enter => self.resolve(None),
+                            KeyCode::Char('c') | KeyCode::Char('s') => {
+                                let skip = key_event.code == KeyCode::Char('s');
+                                if self.proceed(skip) {
+                                    return AppLoopFlag::switch_view(SwitchView::Repo)
+                                        .with_message(std::mem::take(&mut self.message));
+                                }
+                            }
+                            KeyCode::Char('a') => self.request_abort(),
+                            KeyCode::Char('e') => self.preview.start_editing(),
+                            KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = Layout::default()
+            .constraints(
+                [
+                    Constraint::Min(5),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .margin(1)
+            .split(f.size());
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .split(rects[0]);
+
+        let title = match &self.state {
+            Some(state) => match state.todo.first() {
+                Some(step) => format!(
+                    "Conflicts - {} of {} {}",
+                    state.kind.name(),
+                    step.short_id(),
+                    step.summary()
+                ),
+                None => format!("Conflicts - {}", state.kind.name()),
+            },
+            None => match &self.merge {
+                Some(title) => format!("Conflicts - {title}"),
+                None => "Conflicts".to_owned(),
+            },
+        };
+
+        let rows = self.table.table_items.iter().map(|item| {
+            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+            Row::new(cells).style(Style::default().fg(Color::Red))
+        });
+        let table = Table::new(rows)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ")
+            .widths(&[Constraint::Length(16), Constraint::Percentage(100)]);
+        f.render_stateful_widget(table, panes[0], &mut self.table.table_state);
+
+        let content_title = match self.hunks().len() {
+            0 => "Working Tree (ours in green, base in gray, theirs in cyan)".to_owned(),
+            count => format!(
+                "Working Tree - conflict {} of {count} (ours in green, base in gray, theirs in \
+                 cyan)",
+                self.hunk + 1
+            ),
+        };
+        let content = Paragraph::new(self.content_lines())
+            .block(Block::default().borders(Borders::ALL).title(content_title))
+            .scroll((self.scroll, 0));
+        f.render_widget(content, panes[1]);
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(
+                        "o/t/b: take ours/theirs/base | O/T/B: same for the conflict | [/]: \
+                         conflict | Enter: mark resolved | c: continue | s: skip | a: abort | \
+                         e: edit command | PgUp/PgDn: scroll | Esc: back",
+                    )
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        self.preview.render(f, rects[1], "e");
+        f.render_widget(help, rects[2]);
+
+        if let Some(popup) = &self.confirm_abort {
+            popup.render(f);
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+        self.hunk = 0;
+        self.scroll = 0;
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+        self.hunk = 0;
+        self.scroll = 0;
+    }
+}
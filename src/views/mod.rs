@@ -1,2 +1,12 @@
+pub mod blame_view;
+pub mod branch_view;
+pub mod commit_view;
+pub mod conflict_view;
 pub mod opened_repo_view;
+pub mod rebase_view;
+pub mod remote_view;
+pub mod revert_view;
 pub mod start_view;
+pub mod stash_view;
+pub mod status_view;
+pub mod tag_view;
@@ -1,16 +1,36 @@
+use std::collections::BTreeSet;
+
 use crate::{
     app_flags::AppLoopFlag,
-    command::command_handler::CommandHandler,
+    command::{completion::Completion, context::ViewState},
     data_table::DataTable,
-    view_components::input_field::{self, InputField},
+    git::{
+        action::{self, GitAction},
+        commit::CommitDetail,
+        graph::{GraphCell, GraphRow},
+        log::{self, LogEntry},
+        merge,
+        rebase::RebaseState,
+    },
+    view_components::{
+        completion_popup::{CompletionPopup, CompletionResult},
+        diff_view::DiffView,
+        git_preview::GitPreview,
+        input_field::{self, InputField},
+        table_filter::{self, FilterResult, TableFilter},
+        ui_services::UiServices,
+    },
 };
 
-use crossterm::event::{KeyCode, KeyEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use git2::{Oid, Repository};
 use tui::{
     layout::{Constraint, Layout},
     style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
 };
+use tui_input::Input;
 
 use crate::traits::display_view::DisplayView;
 
@@ -18,8 +38,400 @@ pub struct OpenedRepoView {
     pub repo_commits: DataTable,
     pub input_field: InputField,
     pub force_draw: bool,
-    pub handler: CommandHandler,
     pub repo_name: String,
+    /// Graph column for each row of `repo_commits`.
+    pub commit_graph: Vec<GraphRow>,
+    /// Tags of each row of `repo_commits`, shown before the message.
+    pub commit_tags: Vec<Vec<String>>,
+    pub head_id: Option<Oid>,
+    /// Result of the last command, shown next to the command input.
+    pub message: String,
+    pub message_is_error: bool,
+    /// Name of the macro being recorded, if any.
+    pub recording: Option<String>,
+    pub ui: UiServices,
+    completion: Option<CompletionPopup>,
+    /// Fuzzy filter over the commits, opened with `/`.
+    filter: Option<TableFilter>,
+    /// Metadata and diff of the selected commit.
+    detail: DiffView,
+    /// Commit shown in `detail`, kept when loading it failed so it isn't retried every frame.
+    detail_id: Option<Oid>,
+    /// Whether keys go to the detail pane, toggled with Ctrl-D.
+    detail_focused: bool,
+    /// What a stopped rebase or merge is waiting for, shown above the log until it is done.
+    rebase_status: Option<String>,
+    /// Git command line the typed command translates to, shown above the command input.
+    preview: GitPreview,
+    /// Command and selection `preview` was made for.
+    preview_for: Option<(String, ViewState)>,
+}
+
+/// Lane colors of the commit graph, cycled through as new lanes open.
+const GRAPH_COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+];
+
+impl OpenedRepoView {
+    pub fn set_log(&mut self, entries: Vec<LogEntry>) {
+        self.repo_commits.table_items.clear();
+        self.repo_commits.clear_marked();
+        self.commit_graph.clear();
+        self.commit_tags.clear();
+
+        for entry in entries {
+            self.repo_commits.table_items.push(vec![
+                entry.message,
+                entry.author,
+                entry.id.to_string(),
+            ]);
+            self.commit_graph.push(entry.graph);
+            self.commit_tags.push(entry.tags);
+        }
+    }
+
+    /// Reloads the commit table, moving the selection to HEAD when it has moved so a newly
+    /// created commit is highlighted.
+    pub fn reload_log(&mut self, repo: &Repository) {
+        let head_id = repo.head().ok().and_then(|head| head.target());
+        self.set_log(log::load_log(repo).unwrap_or_default());
+
+        if head_id != self.head_id {
+            let head_row = head_id.and_then(|id| {
+                let id = id.to_string();
+                self.repo_commits
+                    .table_items
+                    .iter()
+                    .position(|row| row.get(2) == Some(&id))
+            });
+            self.repo_commits.table_state.select(head_row);
+            self.head_id = head_id;
+        }
+        self.repo_commits.clamp_selection();
+
+        self.rebase_status = match RebaseState::load(repo) {
+            Ok(state) => state
+                .map(|state| state.describe())
+                .or_else(|| merge::describe(repo)),
+            Err(err) => Some(err.message().to_owned()),
+        };
+
+        if let Some(filter) = self.filter.as_mut() {
+            filter.update(&self.repo_commits);
+            let selected = filter.stable_selection(self.repo_commits.table_state.selected());
+            self.repo_commits.table_state.select(selected);
+        }
+    }
+
+    pub fn set_message(&mut self, message: String, is_error: bool) {
+        self.message = message;
+        self.message_is_error = is_error;
+    }
+
+    /// Completes the word in front of the cursor, a single candidate is filled in and several
+    /// are listed in a popup after filling in what they share.
+    pub fn show_completion(&mut self, completion: Option<Completion>) {
+        let completion = match completion {
+            Some(completion) => completion,
+            None => return,
+        };
+
+        let input = &self.input_field.input;
+        if let [candidate] = completion.candidates.as_slice() {
+            self.input_field.input = completion.apply(input, candidate, true);
+            return;
+        }
+
+        let common = completion.common_prefix();
+        if common.len() > completion.typed.len() {
+            self.input_field.input = completion.apply(input, common, false);
+        }
+        self.completion = Some(CompletionPopup::new(completion));
+    }
+
+    /// Moves the selection to the row of `id`, returns `false` if the commit is not listed.
+    pub fn select_commit(&mut self, id: Oid) -> bool {
+        let id = id.to_string();
+        match self
+            .repo_commits
+            .table_items
+            .iter()
+            .position(|row| row.get(2) == Some(&id))
+        {
+            Some(row) => {
+                self.repo_commits.table_state.select(Some(row));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Selected commit when the detail pane shows a different one and has to be reloaded.
+    pub fn detail_request(&self) -> Option<Oid> {
+        self.view_state()
+            .selected_commit
+            .filter(|id| Some(*id) != self.detail_id)
+    }
+
+    /// Shows `detail` of the commit `id` in the detail pane, or why it couldn't be loaded.
+    pub fn set_detail(&mut self, id: Oid, detail: Result<CommitDetail, git2::Error>) {
+        self.detail_id = Some(id);
+        match detail {
+            Ok(detail) => {
+                let header = detail_header(&detail);
+                self.detail.set_content(header, detail.files);
+            }
+            Err(err) => self.detail.set_content(
+                vec![Line::from(Span::styled(
+                    format!("Failed to load commit {id}: {}", err.message()),
+                    Style::default().fg(Color::LightRed),
+                ))],
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// Command typed and the selection it applies to, when the preview pane was made for others
+    /// and has to be remade.
+    pub fn preview_request(&self) -> Option<(String, ViewState)> {
+        let request = (self.input_field.input.value().to_owned(), self.view_state());
+        (self.preview_for.as_ref() != Some(&request)).then_some(request)
+    }
+
+    /// Shows the actions the command of `request` translates to in the preview pane, which is
+    /// hidden when there are none.
+    pub fn set_preview(&mut self, request: (String, ViewState), actions: Vec<GitAction>) {
+        self.preview.set_actions(actions);
+        self.preview_for = Some(request);
+    }
+
+    /// Ctrl-E replaces the typed command with the git command line in the preview pane, to
+    /// edit it before running it. Returns `true` if the key was used.
+    fn preview_key(&mut self, key_event: &KeyEvent) -> bool {
+        let ctrl_e =
+            key_event.code == KeyCode::Char('e') && key_event.modifiers == KeyModifiers::CONTROL;
+        if !ctrl_e || self.preview.actions().is_empty() {
+            return false;
+        }
+
+        self.input_field.input = Input::new(action::command_line(self.preview.actions()));
+        true
+    }
+
+    /// Ctrl-D moves focus between the log and the detail pane, which gets every key while it
+    /// has focus. PageUp/PageDown scroll the pane from the log too. Returns `true` if the key
+    /// was used.
+    fn detail_key(&mut self, key_event: &KeyEvent) -> bool {
+        let ctrl_d = key_event.code == KeyCode::Char('d')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        if self.detail_focused {
+            if ctrl_d || key_event.code == KeyCode::Esc {
+                self.detail_focused = false;
+            } else {
+                self.detail.handle_key(key_event);
+            }
+            return true;
+        }
+
+        match key_event.code {
+            _ if ctrl_d => self.detail_focused = true,
+            KeyCode::PageDown => self.detail.page_by(true),
+            KeyCode::PageUp => self.detail.page_by(false),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Opens the filter on `/` with an empty command input, and handles keys while it is being
+    /// edited. Returns `true` if the filter used the key.
+    fn filter_key(&mut self, key_event: &KeyEvent) -> bool {
+        let input_empty = self.input_field.input.value().is_empty();
+        let slash =
+            key_event.code == KeyCode::Char('/') && key_event.modifiers == KeyModifiers::NONE;
+
+        let filter = match self.filter.as_mut() {
+            Some(filter) if filter.editing => filter,
+            Some(filter) if slash && input_empty => {
+                filter.editing = true;
+                return true;
+            }
+            Some(_) if key_event.code == KeyCode::Esc && input_empty => {
+                self.filter = None;
+                return true;
+            }
+            None if slash && input_empty => {
+                let mut filter = TableFilter::new();
+                filter.update(&self.repo_commits);
+                self.filter = Some(filter);
+                return true;
+            }
+            _ => return false,
+        };
+
+        let selected = self.repo_commits.table_state.selected();
+        match filter.handle_key(key_event) {
+            FilterResult::Handled => {
+                filter.update(&self.repo_commits);
+                let selected = filter.stable_selection(selected);
+                self.repo_commits.table_state.select(selected);
+            }
+            FilterResult::Move { forward } => {
+                let selected = filter.step(selected, forward);
+                self.repo_commits.table_state.select(selected);
+            }
+            FilterResult::Cleared => {
+                self.filter = None;
+                // A filter which matched nothing left no row selected.
+                self.repo_commits.clamp_selection();
+            }
+        }
+        true
+    }
+
+    /// Marks commits for commands like `cherry-pick` with Space while the command input is
+    /// empty, Esc clears the marks. Returns `true` if the key was used.
+    fn mark_key(&mut self, key_event: &KeyEvent) -> bool {
+        if !self.input_field.input.value().is_empty() || key_event.modifiers != KeyModifiers::NONE {
+            return false;
+        }
+
+        match key_event.code {
+            KeyCode::Char(' ') => {
+                self.repo_commits.toggle_marked();
+                self.arrow_down();
+                true
+            }
+            KeyCode::Esc if !self.repo_commits.marked_rows.is_empty() => {
+                self.repo_commits.clear_marked();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles a key while the completion popup is open, returns `true` if the popup used it.
+    fn completion_key(&mut self, key_event: &KeyEvent) -> bool {
+        let mut popup = match self.completion.take() {
+            Some(popup) => popup,
+            None => return false,
+        };
+
+        match popup.handle_key(key_event) {
+            CompletionResult::Accepted(candidate) => {
+                self.input_field.input =
+                    popup
+                        .completion
+                        .apply(&self.input_field.input, &candidate, true);
+                true
+            }
+            CompletionResult::Cancelled => true,
+            CompletionResult::Pending => {
+                self.completion = Some(popup);
+                true
+            }
+            CompletionResult::Ignored => false,
+        }
+    }
+
+    /// Selection handed to commands entered in this view.
+    pub fn view_state(&self) -> ViewState {
+        let row_id = |row: usize| {
+            self.repo_commits
+                .table_items
+                .get(row)
+                .and_then(|item| item.get(2))
+                .and_then(|id| Oid::from_str(id).ok())
+        };
+
+        ViewState {
+            selected_commit: self.repo_commits.table_state.selected().and_then(row_id),
+            marked_commits: self
+                .repo_commits
+                .marked_rows
+                .iter()
+                .filter_map(|row| row_id(*row))
+                .collect(),
+        }
+    }
+}
+
+/// Metadata lines above the diff, like `git show` prints them.
+fn detail_header(detail: &CommitDetail) -> Vec<Line<'static>> {
+    let label = |name: &str| Span::styled(format!("{name:<11}"), Style::default().fg(Color::Cyan));
+    let parents = detail
+        .parents
+        .iter()
+        .map(|id| id.to_string()[..7].to_owned())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("commit {}", detail.id),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![
+            label("Author:"),
+            Span::raw(format!("{}  {}", detail.author, detail.author_date)),
+        ]),
+        Line::from(vec![
+            label("Committer:"),
+            Span::raw(format!("{}  {}", detail.committer, detail.commit_date)),
+        ]),
+        Line::from(vec![label("Parents:"), Span::raw(parents)]),
+    ];
+    lines.extend(detail.trailers.iter().map(|(key, value)| {
+        Line::from(vec![label(&format!("{key}:")), Span::raw(value.to_owned())])
+    }));
+
+    lines.push(Line::default());
+    lines.extend(
+        detail
+            .message
+            .trim_end()
+            .lines()
+            .map(|line| Line::from(format!("    {line}"))),
+    );
+    lines.push(Line::default());
+
+    lines
+}
+
+/// Puts the tags of a commit in front of the first line of its message.
+fn decorate(mut text: Text<'static>, tags: &[String]) -> Text<'static> {
+    let decoration = Span::styled(
+        format!("(tag: {}) ", tags.join(", tag: ")),
+        Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD),
+    );
+    match text.lines.first_mut() {
+        Some(line) => line.spans.insert(0, decoration),
+        None => text.lines.push(Line::from(decoration)),
+    }
+    text
+}
+
+fn graph_line(cells: &[GraphCell]) -> Line<'static> {
+    Line::from(
+        cells
+            .iter()
+            .map(|cell| {
+                Span::styled(
+                    cell.symbol.to_string(),
+                    Style::default().fg(GRAPH_COLORS[cell.color % GRAPH_COLORS.len()]),
+                )
+            })
+            .collect::<Vec<Span>>(),
+    )
 }
 
 impl Default for OpenedRepoView {
@@ -27,6 +439,7 @@ impl Default for OpenedRepoView {
         let mut table = DataTable {
             table_state: TableState::default(),
             table_items: vec![],
+            marked_rows: BTreeSet::new(),
         };
 
         table.table_state.select(Some(0));
@@ -34,8 +447,22 @@ impl Default for OpenedRepoView {
             repo_commits: table,
             input_field: InputField::default(),
             force_draw: true,
-            handler: CommandHandler::default(),
             repo_name: String::default(),
+            commit_graph: Vec::new(),
+            commit_tags: Vec::new(),
+            head_id: None,
+            message: String::new(),
+            message_is_error: false,
+            recording: None,
+            ui: UiServices::default(),
+            completion: None,
+            filter: None,
+            detail: DiffView::default(),
+            detail_id: None,
+            detail_focused: false,
+            rebase_status: None,
+            preview: GitPreview::default(),
+            preview_for: None,
         }
     }
 }
@@ -43,7 +470,7 @@ impl Default for OpenedRepoView {
 impl DisplayView for OpenedRepoView {
     fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
         if !self.force_draw {
-            if let Some(key_event) = self.input_field.input_wait() {
+            if let Some(key_event) = input_field::key_wait() {
                 if input_field::is_quit_event(&key_event) {
                     return AppLoopFlag::terminate();
                 }
@@ -52,10 +479,20 @@ impl DisplayView for OpenedRepoView {
                 // this flag to be set on Windows. Without the flags for Unix OS's it will always
                 // default to 0, aka KeyEventKind::Press.
                 // See: https://docs.rs/crossterm/0.26.1/crossterm/event/struct.KeyEvent.html#structfield.kind
-                if key_event.kind == KeyEventKind::Press {
+                // Popups get the key before the command input does.
+                if key_event.kind == KeyEventKind::Press
+                    && !self.ui.handle_key(&key_event)
+                    && !self.completion_key(&key_event)
+                    && !self.detail_key(&key_event)
+                    && !self.filter_key(&key_event)
+                    && !self.mark_key(&key_event)
+                    && !self.preview_key(&key_event)
+                    && !self.input_field.handle_key(&key_event)
+                {
                     match key_event.code {
                         KeyCode::Down => self.arrow_down(),
                         KeyCode::Up => self.arrow_up(),
+                        KeyCode::Tab => return AppLoopFlag::complete_input(),
                         KeyCode::Enter => {
                             self.input_field.enter_message();
 
@@ -64,28 +501,41 @@ impl DisplayView for OpenedRepoView {
                                 .last_message()
                                 .expect("Expected input after pushing message to message buffer");
 
-                            if let Some(should_continue) = self.handler.call_handler(&input) {
-                                return AppLoopFlag::new(!should_continue);
+                            if !input.is_empty() {
+                                return AppLoopFlag::run_command(input);
                             }
                         }
 
                         _ => {}
                     }
                 }
+
+                if let Some(command) = self.ui.take_confirmed_command() {
+                    return AppLoopFlag::run_confirmed_command(command);
+                }
             }
         } else {
             self.force_draw = false;
         }
 
+        let filter_editing = self.filter.as_ref().is_some_and(|filter| filter.editing);
+        let show_preview = !filter_editing && !self.preview.actions().is_empty();
+        let mut constraints = vec![Constraint::Percentage(45), Constraint::Min(5)];
+        if show_preview {
+            constraints.push(Constraint::Length(3));
+        }
+        constraints.push(Constraint::Length(3));
+
         let rects = Layout::default()
-            .constraints([Constraint::Percentage(80), Constraint::Min(3)].as_ref())
+            .constraints(constraints)
             .margin(1)
             .split(f.size());
+        let input_area = rects[rects.len() - 1];
 
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
         let normal_style = Style::default().bg(Color::Blue);
 
-        let header_cells = ["Commit Message", "Author", "ID"]
+        let header_cells = ["Graph", "Commit Message", "Author", "ID"]
             .iter()
             .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
         let header = Row::new(header_cells)
@@ -93,102 +543,220 @@ impl DisplayView for OpenedRepoView {
             .height(1)
             .bottom_margin(1);
 
-        let rows = self.repo_commits.table_items.iter().map(|item| {
-            let height = item
+        let graph_width = self
+            .commit_graph
+            .iter()
+            .map(|row| row.node_line.len())
+            .max()
+            .unwrap_or(0)
+            .clamp(5, 40) as u16;
+
+        // Rows to show, with the characters matching the filter when there is one.
+        let visible: Vec<(usize, Option<&[Vec<usize>]>)> = match &self.filter {
+            Some(filter) => filter
+                .matches()
                 .iter()
-                .map(|content| {
-                    let mut skip = true;
-                    content
-                        .char_indices()
-                        .rev()
-                        .filter(|c| {
-                            // Skip all newlines at the end of the messages
-                            if skip && c.1 == '\n' {
-                                false
-                            } else {
-                                skip = false;
-                                c.1 == '\n'
-                            }
-                        })
-                        .count()
-                })
-                .max()
-                .unwrap_or(0)
-                + 1;
-            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
-            Row::new(cells).height(height as u16).bottom_margin(0)
-        });
+                .map(|m| (m.row, Some(m.positions.as_slice())))
+                .collect(),
+            None => (0..self.repo_commits.table_items.len())
+                .map(|i| (i, None))
+                .collect(),
+        };
+
+        let rows: Vec<Row> = visible
+            .into_iter()
+            .map(|(i, positions)| {
+                let item = &self.repo_commits.table_items[i];
+                let height = item
+                    .iter()
+                    .map(|content| {
+                        let mut skip = true;
+                        content
+                            .char_indices()
+                            .rev()
+                            .filter(|c| {
+                                // Skip all newlines at the end of the messages
+                                if skip && c.1 == '\n' {
+                                    false
+                                } else {
+                                    skip = false;
+                                    c.1 == '\n'
+                                }
+                            })
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+
+                let graph = match self.commit_graph.get(i) {
+                    Some(graph_row) => {
+                        let mut lines = vec![graph_line(&graph_row.node_line)];
+                        lines.extend((1..height).map(|_| graph_line(&graph_row.continuation_line)));
+                        Text::from(lines)
+                    }
+                    None => Text::default(),
+                };
+
+                let columns = item.iter().enumerate().map(|(column, c)| {
+                    let text = match positions.and_then(|positions| positions.get(column)) {
+                        Some(matched) if !matched.is_empty() => table_filter::highlight(c, matched),
+                        _ => Text::from(c.to_owned()),
+                    };
+                    match (column, self.commit_tags.get(i)) {
+                        (0, Some(tags)) if !tags.is_empty() => Cell::from(decorate(text, tags)),
+                        _ => Cell::from(text),
+                    }
+                });
+                let cells = std::iter::once(Cell::from(graph)).chain(columns);
+                let style = match self.repo_commits.is_marked(i) {
+                    true => Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                    false => Style::default(),
+                };
+                Row::new(cells)
+                    .height(height as u16)
+                    .bottom_margin(0)
+                    .style(style)
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(graph_width),
+            Constraint::Percentage(40),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+        ];
+        let mut table_title = self.repo_name.to_owned();
+        if let Some(filter) = &self.filter {
+            table_title.push_str(&format!(
+                " - filter \"{}\" ({} of {})",
+                filter.input.value(),
+                filter.matches().len(),
+                self.repo_commits.table_items.len()
+            ));
+        }
+        if !self.repo_commits.marked_rows.is_empty() {
+            table_title.push_str(&format!(
+                " ({} marked)",
+                self.repo_commits.marked_rows.len()
+            ));
+        }
+
+        let mut table_title = vec![Span::raw(table_title)];
+        if let Some(status) = &self.rebase_status {
+            table_title.push(Span::styled(
+                format!(" - {status}"),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
 
         let table = Table::new(rows)
             .header(header)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(self.repo_name.to_owned()),
+                    .title(Line::from(table_title)),
             )
             .highlight_style(selected_style)
             .highlight_symbol(">> ")
-            .widths(&[
-                Constraint::Percentage(40),
-                Constraint::Percentage(20),
-                Constraint::Percentage(40),
-            ]);
+            .widths(&widths);
+
+        let selected = self.repo_commits.table_state.selected();
+        let table_state = match self.filter.as_mut() {
+            Some(filter) => filter.table_state(selected),
+            None => &mut self.repo_commits.table_state,
+        };
+        f.render_stateful_widget(table, rects[0], table_state);
 
-        f.render_stateful_widget(table, rects[0], &mut self.repo_commits.table_state);
+        let detail_title = match self.detail_focused {
+            true => "Commit (Ctrl-D/Esc: back | Enter: fold file | n/p: next/prev file)",
+            false => "Commit (Ctrl-D: focus | PgUp/PgDn: scroll)",
+        };
+        let detail_color = match self.detail_focused {
+            true => Color::Yellow,
+            false => Color::White,
+        };
+        let detail_block = Block::default()
+            .borders(Borders::ALL)
+            .title(detail_title)
+            .border_style(Style::default().fg(detail_color));
+        self.detail
+            .render(f, rects[1], detail_block, self.detail_focused);
 
-        let input_field_text = Paragraph::new(self.input_field.input.value())
+        // The filter takes the place of the command input while it is being edited.
+        let (input, input_title, title_color) =
+            match self.filter.as_ref().filter(|filter| filter.editing) {
+                Some(filter) => (
+                    &filter.input,
+                    "Filter Commits (Enter: keep | Esc: clear | Up/Down: move)".to_owned(),
+                    Color::Yellow,
+                ),
+                None => {
+                    let mut input_title = self
+                        .input_field
+                        .search_prompt()
+                        .unwrap_or_else(|| "Run Command".to_owned());
+                    if let Some(name) = &self.recording {
+                        input_title.push_str(&format!(" [recording {name}]"));
+                    }
+                    if !self.message.is_empty() {
+                        input_title.push_str(&format!(" - {}", self.message));
+                    }
+
+                    let title_color = match self.message_is_error {
+                        true => Color::LightRed,
+                        false => Color::LightBlue,
+                    };
+                    (&self.input_field.input, input_title, title_color)
+                }
+            };
+
+        let input_field_text = Paragraph::new(input.value())
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Run Command")
-                    .style(Style::default().fg(Color::LightBlue)),
+                    .title(input_title)
+                    .style(Style::default().fg(title_color)),
             )
             .style(Style::default().fg(Color::White));
 
-        let input_y = rects[0].height + 2;
-        let input_x = (self.input_field.input.cursor() + 2)
-            .try_into()
-            .unwrap_or(u16::max_value());
+        let input_y = input_area.y + 1;
+        let input_x = (input.cursor() + 2).try_into().unwrap_or(u16::MAX);
         f.set_cursor(input_x, input_y);
 
-        f.render_widget(input_field_text, rects[1]);
+        f.render_widget(input_field_text, input_area);
+        if show_preview {
+            self.preview.render(f, rects[2], "Ctrl-E");
+        }
+        if let Some(popup) = self.completion.as_mut() {
+            popup.render(f, input_area);
+        }
+        self.ui.render(f);
 
         AppLoopFlag::continue_()
     }
 
     fn arrow_down(&mut self) {
-        let i = match self.repo_commits.table_state.selected() {
-            Some(i) => {
-                let count = self.repo_commits.table_items.len();
-
-                if count == 0 {
-                    return;
-                } else if i >= count - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.repo_commits.table_state.select(Some(i));
+        if let Some(filter) = &self.filter {
+            let selected = filter.step(self.repo_commits.table_state.selected(), true);
+            self.repo_commits.table_state.select(selected);
+            return;
+        }
+
+        self.repo_commits.select_next();
     }
 
     fn arrow_up(&mut self) {
-        let i = match self.repo_commits.table_state.selected() {
-            Some(i) => {
-                let count = self.repo_commits.table_items.len();
-
-                if count == 0 {
-                    return;
-                } else if i == 0 {
-                    count - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.repo_commits.table_state.select(Some(i));
+        if let Some(filter) = &self.filter {
+            let selected = filter.step(self.repo_commits.table_state.selected(), false);
+            self.repo_commits.table_state.select(selected);
+            return;
+        }
+
+        self.repo_commits.select_previous();
     }
 }
@@ -0,0 +1,354 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use git2::{Oid, Repository};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::rebase::{self, RebaseAction, RebaseStep},
+    traits::display_view::DisplayView,
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        input_field,
+        text_editor::TextEditor,
+    },
+};
+
+/// Plans an interactive rebase of the commits after `onto`, oldest first like git's todo
+/// list, and runs it.
+pub struct RebaseView<'a> {
+    steps: Vec<RebaseStep>,
+    /// Selection over `steps`.
+    table: DataTable,
+    onto: Oid,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    /// Index of the step being reworded and the editor for its message.
+    reword: Option<(usize, TextEditor)>,
+    confirm: Option<ConfirmPopup>,
+}
+
+impl<'a> RebaseView<'a> {
+    pub fn new(repo: &'a Repository, onto: Oid) -> Self {
+        let mut view = Self {
+            steps: Vec::new(),
+            table: DataTable::default(),
+            onto,
+            repo,
+            force_draw: true,
+            message: String::new(),
+            reword: None,
+            confirm: None,
+        };
+
+        match rebase::plan(repo, onto) {
+            Ok(steps) => view.steps = steps,
+            Err(err) => view.message = err.message().to_owned(),
+        }
+        view.update_table();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    fn update_table(&mut self) {
+        self.table.table_items = self
+            .steps
+            .iter()
+            .map(|step| {
+                vec![
+                    step.action.label().to_owned(),
+                    step.short_id().to_owned(),
+                    step.summary().to_owned(),
+                ]
+            })
+            .collect();
+    }
+
+    fn set_action(&mut self, action: RebaseAction) {
+        let i = match self.table.table_state.selected() {
+            Some(i) if i < self.steps.len() => i,
+            _ => return,
+        };
+
+        if action == RebaseAction::Reword {
+            let mut editor = TextEditor::default();
+            editor.set_text(&self.steps[i].message);
+            self.reword = Some((i, editor));
+            return;
+        }
+
+        self.steps[i].action = action;
+        self.update_table();
+        self.table.select_next();
+    }
+
+    /// Swaps the selected step with the one above or below it.
+    fn move_step(&mut self, down: bool) {
+        let i = match self.table.table_state.selected() {
+            Some(i) if i < self.steps.len() => i,
+            _ => return,
+        };
+        let j = match down {
+            true if i + 1 < self.steps.len() => i + 1,
+            false if i > 0 => i - 1,
+            _ => return,
+        };
+
+        self.steps.swap(i, j);
+        self.update_table();
+        self.table.table_state.select(Some(j));
+    }
+
+    fn handle_reword_key(&mut self, index: usize, mut editor: TextEditor, key_event: &KeyEvent) {
+        let control = key_event.modifiers == KeyModifiers::CONTROL;
+
+        if control && key_event.code == KeyCode::Char('s') {
+            match git2::message_prettify(editor.text(), Some(b'#')) {
+                Ok(message) if !message.trim().is_empty() => {
+                    let step = &mut self.steps[index];
+                    if message != step.message {
+                        step.message = message;
+                        step.action = RebaseAction::Reword;
+                    }
+                    self.update_table();
+                }
+                Ok(_) => {
+                    self.message = "The commit message can't be empty".to_owned();
+                    self.reword = Some((index, editor));
+                }
+                Err(err) => self.message = err.message().to_owned(),
+            }
+        } else if key_event.code != KeyCode::Esc {
+            editor.handle_key(key_event);
+            self.reword = Some((index, editor));
+        }
+    }
+
+    fn request_start(&mut self) {
+        if self.steps.is_empty() {
+            return;
+        }
+        if let Err(err) = rebase::validate(&self.steps) {
+            self.message = err.message().to_owned();
+            return;
+        }
+
+        let mut lines = vec![format!(
+            "Rebase {} commit(s) onto {}?",
+            self.steps.len(),
+            &self.onto.to_string()[..7]
+        )];
+        lines.push(String::new());
+        lines.extend(self.result_lines().iter().map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        }));
+
+        self.confirm = Some(ConfirmPopup::new("Rebase", lines));
+    }
+
+    /// Leaves the planner once the rebase ran, for the conflicts when it stopped on some and
+    /// for the log with the summary of the rebase otherwise.
+    fn start(&mut self) -> Option<AppLoopFlag> {
+        match rebase::start(self.repo, self.onto, self.steps.clone()) {
+            Ok(_) if rebase::stopped_on_conflict(self.repo) => {
+                Some(AppLoopFlag::switch_view(SwitchView::Conflicts))
+            }
+            Ok(summary) => Some(AppLoopFlag::switch_view(SwitchView::Repo).with_message(summary)),
+            Err(err) => {
+                self.message = err.message().to_owned();
+                None
+            }
+        }
+    }
+
+    /// The commits the plan results in, oldest first, with the commits melded into them.
+    fn result_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        for step in self.steps.iter() {
+            let text = format!("{} {}", step.short_id(), step.summary());
+            match step.action {
+                RebaseAction::Drop => {}
+                RebaseAction::Squash | RebaseAction::Fixup => lines.push(Line::from(Span::styled(
+                    format!("    + {} {text}", step.action.label()),
+                    Style::default().fg(Color::Magenta),
+                ))),
+                RebaseAction::Pick => lines.push(Line::from(text)),
+                RebaseAction::Reword => lines.push(Line::from(vec![
+                    Span::raw(text),
+                    Span::styled(" (reworded)", Style::default().fg(Color::Cyan)),
+                ])),
+                RebaseAction::Edit => lines.push(Line::from(vec![
+                    Span::raw(text),
+                    Span::styled(" (stop to edit)", Style::default().fg(Color::Yellow)),
+                ])),
+            }
+        }
+
+        lines
+    }
+}
+
+fn action_style(action: RebaseAction) -> Style {
+    match action {
+        RebaseAction::Pick => Style::default().fg(Color::White),
+        RebaseAction::Reword => Style::default().fg(Color::Cyan),
+        RebaseAction::Edit => Style::default().fg(Color::Yellow),
+        RebaseAction::Squash | RebaseAction::Fixup => Style::default().fg(Color::Magenta),
+        RebaseAction::Drop => Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::CROSSED_OUT),
+    }
+}
+
+impl DisplayView for RebaseView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+
+                    if let Some((index, editor)) = self.reword.take() {
+                        self.handle_reword_key(index, editor, &key_event);
+                    } else if let Some(popup) = self.confirm.take() {
+                        match popup.handle_key(&key_event) {
+                            ConfirmResult::Accepted => {
+                                if let Some(flag) = self.start() {
+                                    return flag;
+                                }
+                            }
+                            ConfirmResult::Declined => {}
+                            ConfirmResult::Pending => self.confirm = Some(popup),
+                        }
+                    } else {
+                        match key_event.code {
+                            KeyCode::Down if shift => self.move_step(true),
+                            KeyCode::Up if shift => self.move_step(false),
+                            KeyCode::Char('J') => self.move_step(true),
+                            KeyCode::Char('K') => self.move_step(false),
+                            KeyCode::Down | KeyCode::Char('j') => self.arrow_down(),
+                            KeyCode::Up | KeyCode::Char('k') => self.arrow_up(),
+                            KeyCode::Char('p') => self.set_action(RebaseAction::Pick),
+                            KeyCode::Char('r') => self.set_action(RebaseAction::Reword),
+                            KeyCode::Char('e') => self.set_action(RebaseAction::Edit),
+                            KeyCode::Char('s') => self.set_action(RebaseAction::Squash),
+                            KeyCode::Char('f') => self.set_action(RebaseAction::Fixup),
+                            KeyCode::Char('d') => self.set_action(RebaseAction::Drop),
+                            KeyCode::Enter => self.request_start(),
+                            KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        let rects = Layout::default()
+            .constraints([Constraint::Min(5), Constraint::Length(3)].as_ref())
+            .margin(1)
+            .split(f.size());
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+            .split(rects[0]);
+
+        let header_cells = ["Action", "Commit", "Message"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
+        let header = Row::new(header_cells)
+            .style(Style::default().bg(Color::Blue))
+            .height(1)
+            .bottom_margin(1);
+
+        let rows = self
+            .table
+            .table_items
+            .iter()
+            .zip(self.steps.iter())
+            .map(|(item, step)| {
+                let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+                Row::new(cells).style(action_style(step.action))
+            });
+
+        let table = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "Rebase onto {} (oldest first)",
+                &self.onto.to_string()[..7]
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Percentage(100),
+            ]);
+        f.render_stateful_widget(table, panes[0], &mut self.table.table_state);
+
+        match &self.reword {
+            Some((index, editor)) => editor.render(
+                f,
+                panes[1],
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Reword {} (Ctrl-S: save | Esc: cancel)",
+                        self.steps[*index].short_id()
+                    ))
+                    .style(Style::default().fg(Color::Yellow)),
+            ),
+            None => {
+                let result = Paragraph::new(self.result_lines()).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Resulting Commits"),
+                );
+                f.render_widget(result, panes[1]);
+            }
+        }
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(
+                        "p: pick | r: reword | e: edit | s: squash | f: fixup | d: drop | \
+                         Shift-Up/Down: move | Enter: start | Esc: back",
+                    )
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        f.render_widget(help, rects[1]);
+
+        if let Some(popup) = &self.confirm {
+            popup.render(f);
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+    }
+}
@@ -0,0 +1,361 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::Repository;
+use tui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Wrap},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        remote::{self, RemoteEntry},
+    },
+    traits::{display_view::DisplayView, list_view::ListView},
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        git_preview::GitPreview,
+        list_frame,
+        prompt_popup::{PromptPopup, PromptResult},
+    },
+};
+
+pub enum RemotePopup {
+    /// Name of a new remote, its URL is asked for next.
+    AddName(PromptPopup),
+    AddUrl(String, PromptPopup),
+    Rename(String, PromptPopup),
+    /// Remote whose fetch URL, or push URL when set, is edited.
+    SetUrl(String, bool, PromptPopup),
+    Remove(String, ConfirmPopup),
+    /// The git command of the preview is being edited.
+    Command,
+}
+
+/// Lists the remotes with their URLs and refspecs, and edits them.
+pub struct RemoteView<'a> {
+    remotes: Vec<RemoteEntry>,
+    table: DataTable,
+    /// Remote used when commands aren't given one.
+    default_remote: Option<String>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    popup: Option<RemotePopup>,
+    preview: GitPreview,
+}
+
+impl<'a> RemoteView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            remotes: Vec::new(),
+            table: DataTable::default(),
+            default_remote: None,
+            repo,
+            force_draw: true,
+            message: String::new(),
+            popup: None,
+            preview: GitPreview::default(),
+        };
+
+        view.refresh();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    fn selected_remote(&self) -> Option<&RemoteEntry> {
+        self.remotes.get(self.table.table_state.selected()?)
+    }
+
+    fn open_rename(&mut self) {
+        if let Some(remote) = self.selected_remote() {
+            let prompt = PromptPopup::new(&format!("Rename '{}' to", remote.name), &remote.name);
+            self.popup = Some(RemotePopup::Rename(remote.name.to_owned(), prompt));
+        }
+    }
+
+    fn open_set_url(&mut self, push: bool) {
+        if let Some(remote) = self.selected_remote() {
+            let (title, current) = match push {
+                true => (
+                    format!("Push URL of '{}', empty to use the fetch URL", remote.name),
+                    remote.push_url.to_owned().unwrap_or_default(),
+                ),
+                false => (
+                    format!("Fetch URL of '{}'", remote.name),
+                    remote.fetch_url.to_owned(),
+                ),
+            };
+            let prompt = PromptPopup::new(&title, &current);
+            self.popup = Some(RemotePopup::SetUrl(remote.name.to_owned(), push, prompt));
+        }
+    }
+
+    fn open_remove(&mut self) {
+        if let Some(remote) = self.selected_remote() {
+            let lines = vec![
+                format!("Remove the remote '{}'?", remote.name),
+                remote.fetch_url.to_owned(),
+                String::new(),
+                "Its remote-tracking branches are deleted with it.".to_owned(),
+            ];
+            self.popup = Some(RemotePopup::Remove(
+                remote.name.to_owned(),
+                ConfirmPopup::new("Remove remote", lines),
+            ));
+        }
+    }
+
+    /// Action of the open popup, the URL of a new remote is empty while its name is asked for.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        let action = match &self.popup {
+            Some(RemotePopup::AddName(prompt)) => Some(GitAction::AddRemote {
+                name: prompt.input.value().trim().to_owned(),
+                url: String::new(),
+            }),
+            Some(RemotePopup::AddUrl(name, prompt)) => Some(GitAction::AddRemote {
+                name: name.to_owned(),
+                url: prompt.input.value().trim().to_owned(),
+            }),
+            Some(RemotePopup::Rename(name, prompt)) => Some(GitAction::RenameRemote {
+                old_name: name.to_owned(),
+                new_name: prompt.input.value().trim().to_owned(),
+            }),
+            Some(RemotePopup::SetUrl(name, push, prompt)) => Some(GitAction::SetRemoteUrl {
+                name: name.to_owned(),
+                url: prompt.input.value().trim().to_owned(),
+                push: *push,
+            }),
+            Some(RemotePopup::Remove(name, _)) => Some(GitAction::RemoveRemote {
+                name: name.to_owned(),
+            }),
+            Some(RemotePopup::Command) | None => None,
+        };
+
+        action.into_iter().collect()
+    }
+
+    fn run_actions(&mut self, actions: &[GitAction]) {
+        self.run(action::execute_all(self.repo, actions));
+    }
+
+    /// The refspecs of the selected remote.
+    fn detail_lines(remote: &RemoteEntry) -> Vec<Line<'static>> {
+        let heading = |name: &str| {
+            Line::from(Span::styled(
+                name.to_owned(),
+                Style::default().fg(Color::Cyan),
+            ))
+        };
+        let refspecs = |refspecs: &[String]| match refspecs.is_empty() {
+            true => vec![Line::from("  (default)")],
+            false => refspecs
+                .iter()
+                .map(|refspec| Line::from(format!("  {refspec}")))
+                .collect(),
+        };
+
+        let mut lines = vec![heading("Fetch refspecs")];
+        lines.extend(refspecs(&remote.fetch_refspecs));
+        lines.push(heading("Push refspecs"));
+        lines.extend(refspecs(&remote.push_refspecs));
+        lines
+    }
+}
+
+impl ListView for RemoteView<'_> {
+    type Popup = RemotePopup;
+
+    fn refresh(&mut self) {
+        match remote::load_remotes(self.repo) {
+            Ok(remotes) => self.remotes = remotes,
+            Err(err) => {
+                self.remotes.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+        self.default_remote = remote::default_remote(self.repo);
+
+        self.table.table_items = self
+            .remotes
+            .iter()
+            .map(|remote| {
+                vec![
+                    remote.name.to_owned(),
+                    remote.fetch_url.to_owned(),
+                    remote
+                        .push_url
+                        .to_owned()
+                        .unwrap_or_else(|| "(fetch URL)".to_owned()),
+                ]
+            })
+            .collect();
+        self.table.clamp_selection();
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    fn take_popup(&mut self) -> Option<RemotePopup> {
+        self.popup.take()
+    }
+
+    fn handle_popup_key(&mut self, popup: RemotePopup, key_event: &KeyEvent) {
+        match popup {
+            RemotePopup::AddName(mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(name) => {
+                    let name = name.trim().to_owned();
+                    let prompt = PromptPopup::new(&format!("URL of '{name}'"), "");
+                    self.popup = Some(RemotePopup::AddUrl(name, prompt));
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(RemotePopup::AddName(prompt)),
+            },
+            RemotePopup::AddUrl(name, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(url) => self.run_actions(&[GitAction::AddRemote {
+                    name,
+                    url: url.trim().to_owned(),
+                }]),
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(RemotePopup::AddUrl(name, prompt)),
+            },
+            RemotePopup::Rename(name, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(new_name) => self.run_actions(&[GitAction::RenameRemote {
+                    old_name: name,
+                    new_name: new_name.trim().to_owned(),
+                }]),
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(RemotePopup::Rename(name, prompt)),
+            },
+            RemotePopup::SetUrl(name, push, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(url) => self.run_actions(&[GitAction::SetRemoteUrl {
+                    name,
+                    url: url.trim().to_owned(),
+                    push,
+                }]),
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(RemotePopup::SetUrl(name, push, prompt)),
+            },
+            RemotePopup::Remove(name, confirm) => match confirm.handle_key(key_event) {
+                ConfirmResult::Accepted => self.run_actions(&[GitAction::RemoveRemote { name }]),
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.popup = Some(RemotePopup::Remove(name, confirm)),
+            },
+            RemotePopup::Command => {
+                if let Some(actions) = self.preview.handle_key(key_event) {
+                    self.run_actions(&actions);
+                } else if self.preview.is_editing() {
+                    self.popup = Some(RemotePopup::Command);
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key_event: &KeyEvent) -> Option<AppLoopFlag> {
+        match key_event.code {
+            KeyCode::Down => self.arrow_down(),
+            KeyCode::Up => self.arrow_up(),
+            KeyCode::Char('n') => {
+                let prompt = PromptPopup::new("Name of the new remote", "");
+                self.popup = Some(RemotePopup::AddName(prompt));
+            }
+            KeyCode::Char('r') => self.open_rename(),
+            KeyCode::Char('u') => self.open_set_url(false),
+            KeyCode::Char('U') => self.open_set_url(true),
+            KeyCode::Char('d') => self.open_remove(),
+            KeyCode::Char('p') => {
+                if let Some(name) = self.selected_remote().map(|remote| remote.name.to_owned()) {
+                    self.run(remote::set_preferred(self.repo, Some(&name)));
+                }
+            }
+            KeyCode::Char('e') => {
+                self.preview.start_editing();
+                self.popup = Some(RemotePopup::Command);
+            }
+            KeyCode::Esc => return Some(AppLoopFlag::switch_view(SwitchView::Repo)),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl DisplayView for RemoteView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(flag) = self.handle_input() {
+                return flag;
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = list_frame::areas(f.size(), 60);
+        let detail = list_frame::with_preview(rects[1]);
+
+        let rows = self.table.table_items.iter().map(|item| {
+            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+            let style = match self.default_remote.as_ref() == item.first() {
+                true => Style::default().add_modifier(Modifier::BOLD),
+                false => Style::default(),
+            };
+            Row::new(cells).style(style)
+        });
+
+        let title = match &self.default_remote {
+            Some(name) => format!("Remotes - '{name}' is used by default"),
+            None => "Remotes".to_owned(),
+        };
+        let table = list_frame::table(&["Remote", "Fetch URL", "Push URL"], rows.collect(), title)
+            .widths(&[
+                Constraint::Percentage(20),
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+            ]);
+        f.render_stateful_widget(table, rects[0], &mut self.table.table_state);
+
+        let paragraph = match self.selected_remote() {
+            Some(remote) => Paragraph::new(Self::detail_lines(remote)),
+            None => Paragraph::new(""),
+        };
+        f.render_widget(
+            paragraph
+                .block(Block::default().borders(Borders::ALL).title("Refspecs"))
+                .wrap(Wrap { trim: false }),
+            detail[0],
+        );
+        self.preview.render(f, detail[1], "e");
+
+        let help = list_frame::help_bar(
+            &self.message,
+            "n: add | r: rename | u: fetch URL | U: push URL | d: remove | p: prefer | \
+             e: edit command | Esc: back",
+        );
+        f.render_widget(help, rects[2]);
+
+        match &self.popup {
+            Some(RemotePopup::AddName(prompt))
+            | Some(RemotePopup::AddUrl(_, prompt))
+            | Some(RemotePopup::Rename(_, prompt))
+            | Some(RemotePopup::SetUrl(_, _, prompt)) => prompt.render(f),
+            Some(RemotePopup::Remove(_, confirm)) => confirm.render(f),
+            Some(RemotePopup::Command) | None => {}
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+    }
+}
@@ -0,0 +1,473 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use git2::{Oid, Repository};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        rebase::{self, RebaseStep},
+        revert,
+    },
+    traits::display_view::DisplayView,
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        git_preview::GitPreview,
+        input_field,
+        prompt_popup::{PromptPopup, PromptResult},
+        text_editor::TextEditor,
+    },
+};
+
+enum RevertPopup {
+    /// Index of the step whose message is edited.
+    Message(usize, TextEditor),
+    /// Index of the merge whose mainline parent is asked for.
+    Mainline(usize, PromptPopup),
+    Confirm(ConfirmPopup),
+}
+
+/// Reverts commits of the log newest first, asking which side of merges to keep and letting
+/// the generated messages be edited first.
+pub struct RevertView<'a> {
+    steps: Vec<RebaseStep>,
+    /// Parents of every step's commit with their summaries, more than one for merges.
+    parents: Vec<Vec<(Oid, String)>>,
+    table: DataTable,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    popup: Option<RevertPopup>,
+    preview: GitPreview,
+}
+
+impl<'a> RevertView<'a> {
+    pub fn new(repo: &'a Repository, ids: &[Oid], mainline: Option<u32>) -> Self {
+        let mut view = Self {
+            steps: Vec::new(),
+            parents: Vec::new(),
+            table: DataTable::default(),
+            repo,
+            force_draw: true,
+            message: String::new(),
+            popup: None,
+            preview: GitPreview::default(),
+        };
+
+        for id in ids {
+            let loaded = revert::revert_step(repo, *id, mainline).and_then(|step| {
+                let commit = repo.find_commit(*id)?;
+                let parents = commit
+                    .parents()
+                    .map(|parent| (parent.id(), parent.summary().unwrap_or_default().to_owned()))
+                    .collect();
+                Ok((step, parents))
+            });
+
+            match loaded {
+                Ok((step, parents)) => {
+                    view.steps.push(step);
+                    view.parents.push(parents);
+                }
+                Err(err) => view.message = err.message().to_owned(),
+            }
+        }
+
+        view.update_table();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    fn update_table(&mut self) {
+        self.table.table_items = self
+            .steps
+            .iter()
+            .zip(self.parents.iter())
+            .map(|(step, parents)| {
+                let mainline = match (parents.len(), step.mainline) {
+                    (0 | 1, _) => "-".to_owned(),
+                    (_, Some(mainline)) => mainline.to_string(),
+                    (_, None) => "?".to_owned(),
+                };
+                vec![
+                    step.short_id().to_owned(),
+                    mainline,
+                    step.summary().to_owned(),
+                ]
+            })
+            .collect();
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.table
+            .table_state
+            .selected()
+            .filter(|i| *i < self.steps.len())
+    }
+
+    fn edit_message(&mut self) {
+        if let Some(i) = self.selected() {
+            let mut editor = TextEditor::default();
+            editor.set_text(&self.steps[i].message);
+            self.popup = Some(RevertPopup::Message(i, editor));
+        }
+    }
+
+    fn ask_mainline(&mut self, i: usize) {
+        let count = self.parents[i].len();
+        if count < 2 {
+            self.message = format!("{} is not a merge", self.steps[i].short_id());
+            return;
+        }
+
+        let current = self.steps[i].mainline.unwrap_or(1).to_string();
+        let prompt = PromptPopup::new(
+            &format!("Parent of {} to keep (1-{count})", self.steps[i].short_id()),
+            &current,
+        );
+        self.popup = Some(RevertPopup::Mainline(i, prompt));
+    }
+
+    fn set_mainline(&mut self, i: usize, input: &str) {
+        let count = self.parents[i].len();
+        let mainline = match input.trim().parse::<u32>() {
+            Ok(mainline) if mainline >= 1 && mainline as usize <= count => mainline,
+            _ => {
+                self.message = format!("Enter a parent number from 1 to {count}");
+                return;
+            }
+        };
+
+        let commit = match self.steps[i].oid().and_then(|id| self.repo.find_commit(id)) {
+            Ok(commit) => commit,
+            Err(err) => {
+                self.message = err.message().to_owned();
+                return;
+            }
+        };
+
+        // Keep a message edited by hand, only the generated one names the parent.
+        let step = &mut self.steps[i];
+        if step.message == revert::revert_message(&commit, step.mainline) {
+            step.message = revert::revert_message(&commit, Some(mainline));
+        }
+        step.mainline = Some(mainline);
+        self.update_table();
+    }
+
+    fn request_start(&mut self) {
+        if self.steps.is_empty() {
+            return;
+        }
+
+        // Merges are asked about first, the revert can't be planned without their mainline.
+        let unset = (0..self.steps.len())
+            .find(|i| self.parents[*i].len() > 1 && self.steps[*i].mainline.is_none());
+        if let Some(i) = unset {
+            self.table.table_state.select(Some(i));
+            self.ask_mainline(i);
+            return;
+        }
+
+        let mut lines = vec![format!("Revert {} commit(s)?", self.steps.len())];
+        lines.push(String::new());
+        lines.extend(self.steps.iter().map(|step| step.summary().to_owned()));
+        self.popup = Some(RevertPopup::Confirm(ConfirmPopup::new("Revert", lines)));
+    }
+
+    /// Whether the message of step `i` is no longer the one git starts with.
+    fn edited(&self, i: usize) -> bool {
+        let step = &self.steps[i];
+        step.oid()
+            .and_then(|id| self.repo.find_commit(id))
+            .map(|commit| step.message != revert::revert_message(&commit, step.mainline))
+            .unwrap_or(false)
+    }
+
+    /// The reverts of the steps, the ones in a row keeping the same parent and with messages
+    /// edited or not going in one command. Edited messages leave out `--no-edit`, as they are
+    /// what would be typed in git's editor.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        let mut actions: Vec<GitAction> = Vec::new();
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let edited = self.edited(i);
+            match actions.last_mut() {
+                Some(GitAction::Revert {
+                    revisions,
+                    mainline,
+                    edit,
+                }) if *mainline == step.mainline && *edit == edited => {
+                    revisions.push(step.id.to_owned())
+                }
+                _ => actions.push(GitAction::Revert {
+                    revisions: vec![step.id.to_owned()],
+                    mainline: step.mainline,
+                    edit: edited,
+                }),
+            }
+        }
+        actions
+    }
+
+    /// Runs `actions`, reverts without `--no-edit` taking the messages edited here. Leaves the
+    /// view once they ran, for the conflicts when a revert stopped on some and for the log
+    /// with the summary otherwise.
+    fn run(&mut self, actions: &[GitAction]) -> Option<AppLoopFlag> {
+        let repo = self.repo;
+        let result = action::execute_all_with(repo, actions, |action| match action {
+            GitAction::Revert {
+                revisions,
+                mainline,
+                edit: true,
+            } => {
+                let mut steps = Vec::new();
+                for revision in revisions {
+                    let id = repo.revparse_single(revision)?.peel_to_commit()?.id();
+                    let step = self
+                        .steps
+                        .iter()
+                        .find(|step| step.id == id.to_string() && step.mainline == *mainline);
+                    steps.push(match step {
+                        Some(step) => step.clone(),
+                        None => revert::revert_step(repo, id, *mainline)?,
+                    });
+                }
+                revert::revert(repo, steps)
+            }
+            action => action.execute(repo),
+        });
+
+        match result {
+            Ok(_) if rebase::stopped_on_conflict(repo) => {
+                Some(AppLoopFlag::switch_view(SwitchView::Conflicts))
+            }
+            Ok(summary) => Some(AppLoopFlag::switch_view(SwitchView::Repo).with_message(summary)),
+            Err(err) => {
+                self.message = err.message().to_owned();
+                None
+            }
+        }
+    }
+
+    fn handle_popup_key(
+        &mut self,
+        popup: RevertPopup,
+        key_event: &KeyEvent,
+    ) -> Option<AppLoopFlag> {
+        match popup {
+            RevertPopup::Message(i, mut editor) => {
+                let control = key_event.modifiers == KeyModifiers::CONTROL;
+
+                if control && key_event.code == KeyCode::Char('s') {
+                    match git2::message_prettify(editor.text(), Some(b'#')) {
+                        Ok(message) if !message.trim().is_empty() => {
+                            self.steps[i].message = message;
+                            self.update_table();
+                        }
+                        Ok(_) => {
+                            self.message = "The commit message can't be empty".to_owned();
+                            self.popup = Some(RevertPopup::Message(i, editor));
+                        }
+                        Err(err) => self.message = err.message().to_owned(),
+                    }
+                } else if key_event.code != KeyCode::Esc {
+                    editor.handle_key(key_event);
+                    self.popup = Some(RevertPopup::Message(i, editor));
+                }
+            }
+            RevertPopup::Mainline(i, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(input) => self.set_mainline(i, &input),
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(RevertPopup::Mainline(i, prompt)),
+            },
+            RevertPopup::Confirm(confirm) => match confirm.handle_key(key_event) {
+                ConfirmResult::Accepted => return self.run(&self.preview_actions()),
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.popup = Some(RevertPopup::Confirm(confirm)),
+            },
+        }
+        None
+    }
+
+    /// The parents of the selected merge, the one kept highlighted, followed by the message.
+    fn detail_lines(&self, i: usize) -> Vec<Line<'static>> {
+        let step = &self.steps[i];
+        let mut lines = Vec::new();
+
+        if self.parents[i].len() > 1 {
+            for (n, (id, summary)) in self.parents[i].iter().enumerate() {
+                let n = n as u32 + 1;
+                let style = match step.mainline == Some(n) {
+                    true => Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                    false => Style::default().fg(Color::DarkGray),
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("Parent {n}: {} {summary}", &id.to_string()[..7]),
+                    style,
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+
+        lines.extend(step.message.lines().map(|line| Line::from(line.to_owned())));
+        lines
+    }
+}
+
+impl DisplayView for RevertView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    let control = key_event.modifiers == KeyModifiers::CONTROL;
+
+                    if self.preview.is_editing() {
+                        if let Some(actions) = self.preview.handle_key(&key_event) {
+                            if let Some(flag) = self.run(&actions) {
+                                return flag;
+                            }
+                        }
+                    } else if let Some(popup) = self.popup.take() {
+                        if let Some(flag) = self.handle_popup_key(popup, &key_event) {
+                            return flag;
+                        }
+                    } else {
+                        match key_event.code {
+                            KeyCode::Char('e') if control => self.preview.start_editing(),
+                            KeyCode::Down | KeyCode::Char('j') => self.arrow_down(),
+                            KeyCode::Up | KeyCode::Char('k') => self.arrow_up(),
+                            KeyCode::Char('e') => self.edit_message(),
+                            KeyCode::Char('m') => {
+                                if let Some(i) = self.selected() {
+                                    self.ask_mainline(i);
+                                }
+                            }
+                            KeyCode::Enter => self.request_start(),
+                            KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = Layout::default()
+            .constraints(
+                [
+                    Constraint::Min(5),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .margin(1)
+            .split(f.size());
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(rects[0]);
+
+        let header_cells = ["Commit", "Parent", "Revert Message"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
+        let header = Row::new(header_cells)
+            .style(Style::default().bg(Color::Blue))
+            .height(1)
+            .bottom_margin(1);
+
+        let rows = self.table.table_items.iter().map(|item| {
+            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+            Row::new(cells)
+        });
+        let table = Table::new(rows)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Revert (newest first)"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Percentage(100),
+            ]);
+        f.render_stateful_widget(table, panes[0], &mut self.table.table_state);
+
+        match (&self.popup, self.selected()) {
+            (Some(RevertPopup::Message(i, editor)), _) => editor.render(
+                f,
+                panes[1],
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Message for {} (Ctrl-S: save | Esc: cancel)",
+                        self.steps[*i].short_id()
+                    ))
+                    .style(Style::default().fg(Color::Yellow)),
+            ),
+            (_, Some(i)) => {
+                let detail = Paragraph::new(self.detail_lines(i))
+                    .block(Block::default().borders(Borders::ALL).title("Message"))
+                    .wrap(Wrap { trim: false });
+                f.render_widget(detail, panes[1]);
+            }
+            (_, None) => {
+                f.render_widget(
+                    Block::default().borders(Borders::ALL).title("Message"),
+                    panes[1],
+                );
+            }
+        }
+
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(
+                        "e: edit message | m: merge parent to keep | Enter: revert | Ctrl-E: edit \
+                         command | Esc: back",
+                    )
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+        self.preview.render(f, rects[1], "Ctrl-E");
+        f.render_widget(help, rects[2]);
+
+        match &self.popup {
+            Some(RevertPopup::Mainline(_, prompt)) => prompt.render(f),
+            Some(RevertPopup::Confirm(confirm)) => confirm.render(f),
+            _ => {}
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+    }
+}
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::BTreeSet, path::PathBuf, str::FromStr};
 
 use crossterm::event::{KeyCode, KeyEventKind};
 
@@ -10,28 +10,33 @@ use tui::{
 
 use crate::{
     app_flags::AppLoopFlag,
-    command::command_handler::CommandHandler,
     config::{
         repo::{SavedRepositories, SerializedRepository},
         Config,
     },
     data_table::DataTable,
     traits::display_view::DisplayView,
-    view_components::input_field::{self, InputField},
+    view_components::{
+        input_field::{self, InputField},
+        ui_services::UiServices,
+    },
 };
 
 pub struct StartView {
     pub repositories: DataTable,
     pub input_field: InputField,
-    force_draw: bool,
-    pub handler: CommandHandler,
+    pub force_draw: bool,
     pub repo_selected: Option<SerializedRepository>,
     arrow_used: bool,
+    /// Result of the last command, shown next to the input.
+    pub message: String,
+    pub ui: UiServices,
 }
 
 impl StartView {
     pub fn load_table(&mut self) {
         self.repositories.table_items = Self::get_table_items();
+        self.repositories.clamp_selection();
     }
 
     fn get_table_items() -> Vec<Vec<String>> {
@@ -56,12 +61,14 @@ impl Default for StartView {
             repositories: DataTable {
                 table_state: TableState::default(),
                 table_items: StartView::get_table_items(),
+                marked_rows: BTreeSet::new(),
             },
             input_field: InputField::default(),
             force_draw: true,
-            handler: CommandHandler::default(),
             repo_selected: None,
             arrow_used: true,
+            message: String::new(),
+            ui: UiServices::default(),
         };
 
         view.repositories.table_state.select(Some(0));
@@ -81,7 +88,7 @@ impl DisplayView for StartView {
                 // this flag to be set on Windows. Without the flags for Unix OS's it will always
                 // default to 0, aka KeyEventKind::Press.
                 // See: https://docs.rs/crossterm/0.26.1/crossterm/event/struct.KeyEvent.html#structfield.kind
-                if key_event.kind == KeyEventKind::Press {
+                if key_event.kind == KeyEventKind::Press && !self.ui.handle_key(&key_event) {
                     match key_event.code {
                         KeyCode::Down => self.arrow_down(),
                         KeyCode::Up => self.arrow_up(),
@@ -98,13 +105,14 @@ impl DisplayView for StartView {
                                         )
                                         .unwrap_or_default(),
                                         name: selected_repo
-                                            .get(0)
+                                            .first()
                                             .unwrap_or(&String::new())
                                             .to_string(),
                                         repo_url: selected_repo
                                             .get(2)
                                             .unwrap_or(&String::new())
                                             .to_string(),
+                                        preferred_remote: None,
                                     };
 
                                     self.repo_selected = Some(repo);
@@ -117,11 +125,14 @@ impl DisplayView for StartView {
                                     "Expected input after pushing message to message buffer",
                                 );
 
-                                if let Ok(git_repo) = git2::Repository::open(input) {
+                                if let Ok(git_repo) = git2::Repository::open(&input) {
                                     let recent_repo = crate::git::GitRepo::from_git2_repo(git_repo);
                                     self.repo_selected = Some(recent_repo.seralized_data);
 
                                     return AppLoopFlag::terminate();
+                                } else if !input.is_empty() {
+                                    // Anything which is not a repository is run as a command.
+                                    return AppLoopFlag::run_command(input);
                                 }
                             }
                         }
@@ -179,11 +190,16 @@ impl DisplayView for StartView {
 
         f.render_stateful_widget(table, rects[0], &mut self.repositories.table_state);
 
+        let mut input_title = "Open folder or run command".to_owned();
+        if !self.message.is_empty() {
+            input_title.push_str(&format!(" - {}", self.message));
+        }
+
         let input_field_text = Paragraph::new(self.input_field.input.value())
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Open folder")
+                    .title(input_title)
                     .style(Style::default().fg(Color::LightBlue)),
             )
             .style(Style::default().fg(Color::White));
@@ -191,10 +207,11 @@ impl DisplayView for StartView {
         let input_y = rects[0].height + 2;
         let input_x = (self.input_field.input.cursor() + 2)
             .try_into()
-            .unwrap_or(u16::max_value());
+            .unwrap_or(u16::MAX);
         f.set_cursor(input_x, input_y);
 
         f.render_widget(input_field_text, rects[1]);
+        self.ui.render(f);
 
         AppLoopFlag::continue_()
     }
@@ -0,0 +1,365 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::{Oid, Repository};
+use tui::{
+    layout::Constraint,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Row},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        stash::{self, StashEntry},
+    },
+    traits::{display_view::DisplayView, list_view::ListView},
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        diff_view::DiffView,
+        git_preview::GitPreview,
+        list_frame,
+        prompt_popup::{PromptPopup, PromptResult},
+    },
+};
+
+pub enum StashPopup {
+    Save(PromptPopup),
+    /// Index of the stash to turn into a branch.
+    Branch(usize, PromptPopup),
+    Drop(usize, ConfirmPopup),
+    /// The git command of the preview is being edited.
+    Command,
+}
+
+/// Lists the stashes with the changes of the selected one, and creates, applies and drops them.
+pub struct StashView<'a> {
+    stashes: Vec<StashEntry>,
+    table: DataTable,
+    diff: DiffView,
+    /// Stash shown in `diff`.
+    diff_id: Option<Oid>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    popup: Option<StashPopup>,
+    /// Options of the next stash created with `n`.
+    include_untracked: bool,
+    keep_index: bool,
+    preview: GitPreview,
+}
+
+impl<'a> StashView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            stashes: Vec::new(),
+            table: DataTable::default(),
+            diff: DiffView::default(),
+            diff_id: None,
+            repo,
+            force_draw: true,
+            message: String::new(),
+            popup: None,
+            include_untracked: false,
+            keep_index: false,
+            preview: GitPreview::default(),
+        };
+
+        view.refresh();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    fn selected_stash(&self) -> Option<&StashEntry> {
+        self.stashes.get(self.table.table_state.selected()?)
+    }
+
+    /// Shows the diff of the selected stash when the selection moved.
+    fn load_diff(&mut self) {
+        let stash = match self.selected_stash() {
+            Some(stash) => stash,
+            None => {
+                if self.diff_id.take().is_some() {
+                    self.diff.set_content(Vec::new(), Vec::new());
+                }
+                return;
+            }
+        };
+        if self.diff_id == Some(stash.id) {
+            return;
+        }
+
+        let label =
+            |name: &str| Span::styled(format!("{name:<6}"), Style::default().fg(Color::Cyan));
+        let header = vec![
+            Line::from(vec![
+                label("Stash"),
+                Span::raw(format!("{}: {}", stash.name(), stash.message)),
+            ]),
+            Line::from(vec![
+                label("Base"),
+                Span::styled(
+                    stash.base.to_string()[..7].to_owned(),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!(" {}", stash.base_summary)),
+            ]),
+            Line::from(vec![label("Date"), Span::raw(stash.date.to_owned())]),
+            Line::from(""),
+        ];
+
+        let id = stash.id;
+        match stash::stash_diff(self.repo, id) {
+            Ok(files) => self.diff.set_content(header, files),
+            Err(err) => self.diff.set_content(
+                vec![Line::from(Span::styled(
+                    err.message().to_owned(),
+                    Style::default().fg(Color::LightRed),
+                ))],
+                Vec::new(),
+            ),
+        }
+        self.diff_id = Some(id);
+    }
+
+    fn open_save(&mut self) {
+        let mut title = "Stash message".to_owned();
+        if self.include_untracked {
+            title.push_str(" (with untracked files)");
+        }
+        if self.keep_index {
+            title.push_str(" (keeping the index)");
+        }
+        self.popup = Some(StashPopup::Save(PromptPopup::new(&title, "")));
+    }
+
+    fn push_action(&self, message: &str) -> GitAction {
+        let message = message.trim();
+        GitAction::StashPush {
+            message: (!message.is_empty()).then(|| message.to_owned()),
+            include_untracked: self.include_untracked,
+            keep_index: self.keep_index,
+        }
+    }
+
+    /// Action of the open popup, or applying the selected stash without one.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        let action = match &self.popup {
+            Some(StashPopup::Save(prompt)) => Some(self.push_action(prompt.input.value())),
+            Some(StashPopup::Branch(index, prompt)) => Some(GitAction::StashBranch {
+                name: prompt.input.value().trim().to_owned(),
+                index: *index,
+            }),
+            Some(StashPopup::Drop(index, _)) => Some(GitAction::StashDrop { index: *index }),
+            Some(StashPopup::Command) => None,
+            None => self.selected_stash().map(|stash| GitAction::StashApply {
+                index: stash.index,
+                pop: false,
+            }),
+        };
+
+        action.into_iter().collect()
+    }
+
+    fn run_actions(&mut self, actions: &[GitAction]) {
+        self.run(action::execute_all(self.repo, actions));
+    }
+
+    fn open_branch(&mut self) {
+        if let Some(stash) = self.selected_stash() {
+            let prompt = PromptPopup::new(&format!("New branch for {}", stash.name()), "");
+            self.popup = Some(StashPopup::Branch(stash.index, prompt));
+        }
+    }
+
+    fn open_drop(&mut self) {
+        if let Some(stash) = self.selected_stash() {
+            let lines = vec![
+                format!("Drop {}?", stash.name()),
+                stash.message.to_owned(),
+                String::new(),
+                "Its changes are lost unless it is applied somewhere.".to_owned(),
+            ];
+            self.popup = Some(StashPopup::Drop(
+                stash.index,
+                ConfirmPopup::new("Drop stash", lines),
+            ));
+        }
+    }
+}
+
+impl ListView for StashView<'_> {
+    type Popup = StashPopup;
+
+    fn refresh(&mut self) {
+        match stash::load_stashes(self.repo) {
+            Ok(stashes) => self.stashes = stashes,
+            Err(err) => {
+                self.stashes.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+
+        self.table.table_items = self
+            .stashes
+            .iter()
+            .map(|stash| {
+                vec![
+                    stash.name(),
+                    stash.message.to_owned(),
+                    format!("{} {}", &stash.base.to_string()[..7], stash.base_summary),
+                ]
+            })
+            .collect();
+        self.table.clamp_selection();
+        // The indexes moved, reload the diff even if the same stash is selected.
+        self.diff_id = None;
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    fn take_popup(&mut self) -> Option<StashPopup> {
+        self.popup.take()
+    }
+
+    fn handle_popup_key(&mut self, popup: StashPopup, key_event: &KeyEvent) {
+        match popup {
+            StashPopup::Save(mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(message) => {
+                    self.run_actions(&[self.push_action(&message)]);
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(StashPopup::Save(prompt)),
+            },
+            StashPopup::Branch(index, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(name) => {
+                    let name = name.trim().to_owned();
+                    self.run_actions(&[GitAction::StashBranch { name, index }]);
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(StashPopup::Branch(index, prompt)),
+            },
+            StashPopup::Drop(index, confirm) => match confirm.handle_key(key_event) {
+                ConfirmResult::Accepted => self.run_actions(&[GitAction::StashDrop { index }]),
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.popup = Some(StashPopup::Drop(index, confirm)),
+            },
+            StashPopup::Command => {
+                if let Some(actions) = self.preview.handle_key(key_event) {
+                    self.run_actions(&actions);
+                } else if self.preview.is_editing() {
+                    self.popup = Some(StashPopup::Command);
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key_event: &KeyEvent) -> Option<AppLoopFlag> {
+        let index = self.selected_stash().map(|stash| stash.index);
+
+        match key_event.code {
+            KeyCode::Down => self.arrow_down(),
+            KeyCode::Up => self.arrow_up(),
+            KeyCode::PageDown => self.diff.page_by(true),
+            KeyCode::PageUp => self.diff.page_by(false),
+            KeyCode::Char('n') => self.open_save(),
+            KeyCode::Char('u') => self.include_untracked = !self.include_untracked,
+            KeyCode::Char('k') => self.keep_index = !self.keep_index,
+            KeyCode::Char('a') | KeyCode::Char('p') => {
+                if let Some(index) = index {
+                    let pop = key_event.code == KeyCode::Char('p');
+                    self.run_actions(&[GitAction::StashApply { index, pop }]);
+                }
+            }
+            KeyCode::Char('d') => self.open_drop(),
+            KeyCode::Char('b') => self.open_branch(),
+            KeyCode::Char('e') => {
+                self.preview.start_editing();
+                self.popup = Some(StashPopup::Command);
+            }
+            KeyCode::Esc => return Some(AppLoopFlag::switch_view(SwitchView::Repo)),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl DisplayView for StashView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(flag) = self.handle_input() {
+                return flag;
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.load_diff();
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = list_frame::areas(f.size(), 35);
+        let detail = list_frame::with_preview(rects[1]);
+
+        let rows = self.table.table_items.iter().map(|item| {
+            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+            Row::new(cells)
+        });
+
+        let mut options = Vec::new();
+        if self.include_untracked {
+            options.push("include untracked");
+        }
+        if self.keep_index {
+            options.push("keep index");
+        }
+        let title = match options.is_empty() {
+            true => "Stashes".to_owned(),
+            false => format!("Stashes - new stashes {}", options.join(", ")),
+        };
+
+        let table =
+            list_frame::table(&["Stash", "Message", "Base"], rows.collect(), title).widths(&[
+                Constraint::Length(10),
+                Constraint::Percentage(55),
+                Constraint::Percentage(45),
+            ]);
+        f.render_stateful_widget(table, rects[0], &mut self.table.table_state);
+
+        self.diff.render(
+            f,
+            detail[0],
+            Block::default().borders(Borders::ALL).title("Changes"),
+            false,
+        );
+        self.preview.render(f, detail[1], "e");
+
+        let help = list_frame::help_bar(
+            &self.message,
+            "n: new | u: untracked | k: keep index | a: apply | p: pop | d: drop | b: branch | \
+             e: edit command | PgUp/PgDn: scroll | Esc: back",
+        );
+        f.render_widget(help, rects[2]);
+
+        match &self.popup {
+            Some(StashPopup::Save(prompt)) | Some(StashPopup::Branch(_, prompt)) => {
+                prompt.render(f)
+            }
+            Some(StashPopup::Drop(_, confirm)) => confirm.render(f),
+            Some(StashPopup::Command) | None => {}
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+    }
+}
@@ -0,0 +1,508 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use git2::{ApplyLocation, Repository};
+use tui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        diff,
+        status::{self, FileState, StatusEntry},
+    },
+    traits::display_view::DisplayView,
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        diff_view::DiffView,
+        git_preview::GitPreview,
+        input_field,
+    },
+};
+
+pub struct StatusView<'a> {
+    pub files: DataTable,
+    entries: Vec<StatusEntry>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    confirm_discard: Option<ConfirmPopup>,
+    preview: GitPreview,
+    /// Diff of the selected file, where hunks and lines are picked to stage them.
+    diff: DiffView,
+    /// Path and state of the entry `diff` shows.
+    diff_entry: Option<(String, FileState)>,
+    /// Set by [`StatusView::refresh`], the diff is reloaded before the next draw.
+    diff_stale: bool,
+    /// Whether keys go to the diff pane, toggled with Tab.
+    diff_focused: bool,
+    confirm_patch_discard: Option<ConfirmPopup>,
+}
+
+/// What is done with the hunk or lines picked in the diff pane.
+#[derive(Clone, Copy)]
+enum PatchAction {
+    Stage,
+    Unstage,
+    Discard,
+}
+
+impl<'a> StatusView<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        let mut view = Self {
+            files: DataTable::default(),
+            entries: Vec::new(),
+            repo,
+            force_draw: true,
+            message: String::new(),
+            confirm_discard: None,
+            preview: GitPreview::default(),
+            diff: DiffView::default(),
+            diff_entry: None,
+            diff_stale: true,
+            diff_focused: false,
+            confirm_patch_discard: None,
+        };
+
+        view.refresh();
+        view
+    }
+
+    pub fn refresh(&mut self) {
+        match status::load_status(self.repo) {
+            Ok(entries) => self.entries = entries,
+            Err(err) => {
+                self.entries.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+
+        self.files.table_items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.state.label().to_owned(),
+                    entry.change.to_owned(),
+                    entry.path.to_owned(),
+                ]
+            })
+            .collect();
+        self.files.clear_marked();
+        self.files.clamp_selection();
+        self.diff_stale = true;
+    }
+
+    /// Loads the diff of the selected file when the selection moved or the status was
+    /// refreshed. A refreshed diff of the same file keeps its scroll position.
+    fn load_diff(&mut self) {
+        let entry = self
+            .selected_entry()
+            .map(|entry| (entry.path.to_owned(), entry.state));
+        if entry == self.diff_entry && !self.diff_stale {
+            return;
+        }
+
+        let file = match &entry {
+            Some((path, FileState::Staged)) => diff::staged_diff(self.repo, path),
+            Some((path, _)) => diff::unstaged_diff(self.repo, path),
+            None => Ok(None),
+        };
+        let files = match file {
+            Ok(file) => file.into_iter().collect(),
+            Err(err) => {
+                self.message = err.message().to_owned();
+                Vec::new()
+            }
+        };
+
+        if entry == self.diff_entry {
+            self.diff.update_files(files);
+        } else {
+            self.diff.set_content(Vec::new(), files);
+        }
+        self.diff_entry = entry;
+        self.diff_stale = false;
+    }
+
+    /// Patch of the lines marked in the diff pane, or of the hunk under its cursor when no
+    /// line is marked, along with a description of what was picked.
+    fn picked_patch(&self, reverse: bool) -> Result<(Vec<u8>, String), git2::Error> {
+        let file = self
+            .diff
+            .files()
+            .first()
+            .ok_or_else(|| git2::Error::from_str("The selected file has no changes"))?;
+        let marked = self.diff.marked();
+
+        let (patch, picked) = if marked.is_empty() {
+            let (_, hunk) = self.diff.cursor_hunk().ok_or_else(|| {
+                git2::Error::from_str("Move to a hunk or mark lines with Space first")
+            })?;
+            let patch = diff::selection_patch(file, reverse, |h, _| h == hunk)?;
+            (patch, "1 hunk".to_owned())
+        } else {
+            let patch = diff::selection_patch(file, reverse, |h, l| marked.contains(&(0, h, l)))?;
+            (patch, format!("{} line(s)", marked.len()))
+        };
+
+        let patch = patch.ok_or_else(|| git2::Error::from_str("The hunk has no changes"))?;
+        Ok((patch, picked))
+    }
+
+    /// Applies the hunk or lines picked in the diff pane, staging them to the index,
+    /// unstaging them from it or discarding them from the working tree.
+    fn apply_picked(&mut self, action: PatchAction) {
+        let (state, reverse, location, verb) = match action {
+            PatchAction::Stage => (FileState::Unstaged, false, ApplyLocation::Index, "stage"),
+            PatchAction::Unstage => (FileState::Staged, true, ApplyLocation::Index, "unstage"),
+            PatchAction::Discard => (FileState::Unstaged, true, ApplyLocation::WorkDir, "discard"),
+        };
+
+        let path = match self.selected_entry() {
+            Some(entry) if entry.state == state => entry.path.to_owned(),
+            _ => {
+                self.message = format!(
+                    "Pick hunks from {} changes to {verb} them",
+                    state.label().to_lowercase()
+                );
+                return;
+            }
+        };
+
+        let result = self.picked_patch(reverse).and_then(|(patch, picked)| {
+            diff::apply_patch(self.repo, &patch, location)?;
+            Ok(picked)
+        });
+
+        self.message = match result {
+            Ok(picked) => format!("{}d {picked} of {path}", capitalize(verb)),
+            Err(err) => err.message().to_owned(),
+        };
+        self.refresh();
+        self.select_entry(&path, state);
+    }
+
+    fn request_patch_discard(&mut self) {
+        let path = match self.selected_entry() {
+            Some(entry) if entry.state == FileState::Unstaged => entry.path.to_owned(),
+            _ => {
+                self.message = "Pick hunks from unstaged changes to discard them".to_owned();
+                return;
+            }
+        };
+
+        match self.picked_patch(true) {
+            Ok((_, picked)) => {
+                let lines = vec![format!(
+                    "Discard {picked} of {path}? This cannot be undone."
+                )];
+                self.confirm_patch_discard = Some(ConfirmPopup::new("Discard changes", lines));
+            }
+            Err(err) => self.message = err.message().to_owned(),
+        }
+    }
+
+    /// Moves the selection to the entry of `path` in `state`, if there still is one.
+    fn select_entry(&mut self, path: &str, state: FileState) {
+        if let Some(i) = self
+            .entries
+            .iter()
+            .position(|entry| entry.path == path && entry.state == state)
+        {
+            self.files.table_state.select(Some(i));
+        }
+    }
+
+    /// Keys of the focused diff pane.
+    fn diff_key(&mut self, key_event: &KeyEvent) {
+        match key_event.code {
+            KeyCode::Tab | KeyCode::Esc => self.diff_focused = false,
+            KeyCode::Char('s') => self.apply_picked(PatchAction::Stage),
+            KeyCode::Char('u') => self.apply_picked(PatchAction::Unstage),
+            KeyCode::Char('d') => self.request_patch_discard(),
+            KeyCode::Char(' ') if self.diff.toggle_mark() => {}
+            _ => {
+                self.diff.handle_key(key_event);
+            }
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&StatusEntry> {
+        self.entries.get(self.files.table_state.selected()?)
+    }
+
+    fn run(&mut self, actions: &[GitAction]) {
+        self.message = match action::execute_all(self.repo, actions) {
+            Ok(summary) => summary,
+            Err(err) => err.message().to_owned(),
+        };
+        self.refresh();
+    }
+
+    fn stage_selected(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let paths = vec![entry.path.to_owned()];
+            self.run(&[GitAction::Add { paths }]);
+        }
+    }
+
+    fn unstage_selected(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let paths = vec![entry.path.to_owned()];
+            self.run(&[GitAction::Unstage { paths }]);
+        }
+    }
+
+    /// Stages the selected file, or unstages it when it is already staged.
+    fn toggle_action(&self) -> Option<GitAction> {
+        let entry = self.selected_entry()?;
+        let paths = vec![entry.path.to_owned()];
+
+        Some(match entry.state {
+            FileState::Staged => GitAction::Unstage { paths },
+            FileState::Unstaged | FileState::Untracked => GitAction::Add { paths },
+        })
+    }
+
+    fn discard_actions(&self) -> Vec<GitAction> {
+        let entries: Vec<&StatusEntry> = self
+            .files
+            .marked_or_selected()
+            .iter()
+            .filter_map(|i| self.entries.get(*i))
+            .collect();
+
+        GitAction::discard(&entries)
+    }
+
+    /// Actions the preview pane shows, the discard while files are marked and the stage
+    /// toggle of the selected file otherwise.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        if self.confirm_discard.is_some() || !self.files.marked_rows.is_empty() {
+            self.discard_actions()
+        } else {
+            self.toggle_action().into_iter().collect()
+        }
+    }
+
+    fn request_discard(&mut self) {
+        let rows = self.files.marked_or_selected();
+        if rows.is_empty() {
+            return;
+        }
+
+        let mut lines = vec![format!(
+            "Discard the changes to {} file(s)? This cannot be undone.",
+            rows.len()
+        )];
+        lines.push(String::new());
+        lines.extend(
+            rows.iter()
+                .filter_map(|i| self.entries.get(*i))
+                .map(|entry| format!("{:<10} {}", entry.state.label(), entry.path)),
+        );
+
+        self.confirm_discard = Some(ConfirmPopup::new("Discard changes", lines));
+    }
+
+    fn discard_marked(&mut self) {
+        let actions = self.discard_actions();
+        self.run(&actions);
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(action) = self.toggle_action() {
+            self.run(&[action]);
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn state_color(state: FileState) -> Color {
+    match state {
+        FileState::Staged => Color::Green,
+        FileState::Unstaged => Color::Red,
+        FileState::Untracked => Color::Gray,
+    }
+}
+
+impl DisplayView for StatusView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(key_event) = input_field::key_wait() {
+                if input_field::is_quit_event(&key_event) {
+                    return AppLoopFlag::terminate();
+                }
+
+                // See OpenedRepoView for why only presses are handled.
+                if key_event.kind == KeyEventKind::Press {
+                    if self.preview.is_editing() {
+                        if let Some(actions) = self.preview.handle_key(&key_event) {
+                            self.run(&actions);
+                        }
+                    } else if let Some(popup) = self.confirm_discard.take() {
+                        match popup.handle_key(&key_event) {
+                            ConfirmResult::Accepted => self.discard_marked(),
+                            ConfirmResult::Declined => {}
+                            ConfirmResult::Pending => self.confirm_discard = Some(popup),
+                        }
+                    } else if let Some(popup) = self.confirm_patch_discard.take() {
+                        match popup.handle_key(&key_event) {
+                            ConfirmResult::Accepted => self.apply_picked(PatchAction::Discard),
+                            ConfirmResult::Declined => {}
+                            ConfirmResult::Pending => self.confirm_patch_discard = Some(popup),
+                        }
+                    } else if self.diff_focused {
+                        self.diff_key(&key_event);
+                    } else {
+                        match key_event.code {
+                            KeyCode::Down => self.arrow_down(),
+                            KeyCode::Up => self.arrow_up(),
+                            KeyCode::Enter => self.toggle_selected(),
+                            KeyCode::Char('s') => self.stage_selected(),
+                            KeyCode::Char('u') => self.unstage_selected(),
+                            KeyCode::Char('r') => self.refresh(),
+                            KeyCode::Char(' ') => {
+                                self.files.toggle_marked();
+                                self.arrow_down();
+                            }
+                            KeyCode::Char('a') => self.files.mark_all(),
+                            KeyCode::Char('i') => self.files.invert_marked(),
+                            KeyCode::Char('d') => self.request_discard(),
+                            KeyCode::Char('e') => self.preview.start_editing(),
+                            KeyCode::Tab => self.diff_focused = true,
+                            KeyCode::PageDown => self.diff.page_by(true),
+                            KeyCode::PageUp => self.diff.page_by(false),
+                            KeyCode::Esc => return AppLoopFlag::switch_view(SwitchView::Repo),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.load_diff();
+        // Picking hunks has no equivalent command line to show.
+        match self.diff_focused {
+            true => self.preview.set_actions(Vec::new()),
+            false => self.preview.set_actions(self.preview_actions()),
+        }
+
+        let rects = Layout::default()
+            .constraints(
+                [
+                    Constraint::Percentage(35),
+                    Constraint::Min(5),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .margin(1)
+            .split(f.size());
+
+        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+        let normal_style = Style::default().bg(Color::Blue);
+
+        let header_cells = ["", "State", "Change", "Path"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().fg(Color::White)));
+        let header = Row::new(header_cells)
+            .style(normal_style)
+            .height(1)
+            .bottom_margin(1);
+
+        let rows = self
+            .files
+            .table_items
+            .iter()
+            .zip(self.entries.iter())
+            .enumerate()
+            .map(|(i, (item, entry))| {
+                let mark = if self.files.is_marked(i) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                let cells = std::iter::once(Cell::from(mark))
+                    .chain(item.iter().map(|c| Cell::from(c.to_owned())));
+                Row::new(cells).style(Style::default().fg(state_color(entry.state)))
+            });
+
+        let table = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .highlight_style(selected_style)
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Length(3),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(70),
+            ]);
+
+        f.render_stateful_widget(table, rects[0], &mut self.files.table_state);
+
+        let (diff_title, diff_color) = match self.diff_focused {
+            true => ("Diff", Color::Yellow),
+            false => ("Diff (Tab: pick hunks | PgUp/PgDn: scroll)", Color::White),
+        };
+        let diff_block = Block::default()
+            .borders(Borders::ALL)
+            .title(diff_title)
+            .border_style(Style::default().fg(diff_color));
+        self.diff.render(f, rects[1], diff_block, self.diff_focused);
+
+        let help_title = match self.diff_focused {
+            true => {
+                "Space: mark line | s: stage | u: unstage | d: discard | Enter: fold | \
+                 Tab/Esc: back to files"
+            }
+            false => {
+                "Enter: stage/unstage | Space: mark | a: mark all | i: invert | \
+                 d: discard | e: edit command | r: refresh | Esc: back"
+            }
+        };
+        let help = Paragraph::new(self.message.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(help_title)
+                    .style(Style::default().fg(Color::LightBlue)),
+            )
+            .style(Style::default().fg(Color::White));
+
+        self.preview.render(f, rects[2], "e");
+        f.render_widget(help, rects[3]);
+
+        if let Some(popup) = &self.confirm_discard {
+            popup.render(f);
+        }
+        if let Some(popup) = &self.confirm_patch_discard {
+            popup.render(f);
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.files.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.files.select_previous();
+    }
+}
@@ -0,0 +1,359 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git2::{Oid, Repository};
+use tui::{
+    layout::Constraint,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Wrap},
+};
+
+use crate::{
+    app_flags::{AppLoopFlag, SwitchView},
+    data_table::DataTable,
+    git::{
+        action::{self, GitAction},
+        tag::{self, TagEntry},
+    },
+    traits::{display_view::DisplayView, list_view::ListView},
+    view_components::{
+        confirm_popup::{ConfirmPopup, ConfirmResult},
+        git_preview::GitPreview,
+        list_frame,
+        prompt_popup::{PromptPopup, PromptResult},
+        text_editor::TextEditor,
+    },
+};
+
+pub enum TagPopup {
+    /// Name of a new tag, annotated ones go on to the message editor.
+    Name(bool, PromptPopup),
+    /// Message of the annotated tag with the given name.
+    Message(String, TextEditor),
+    Delete(String, ConfirmPopup),
+    /// The git command of the preview is being edited.
+    Command,
+}
+
+/// Lists the tags with their commit and tagger, and creates tags on the commit selected in the
+/// log.
+pub struct TagView<'a> {
+    tags: Vec<TagEntry>,
+    table: DataTable,
+    /// Commit selected in the log, which new tags point at.
+    target: Option<Oid>,
+    repo: &'a Repository,
+    force_draw: bool,
+    message: String,
+    popup: Option<TagPopup>,
+    preview: GitPreview,
+}
+
+impl<'a> TagView<'a> {
+    pub fn new(repo: &'a Repository, target: Option<Oid>) -> Self {
+        let mut view = Self {
+            tags: Vec::new(),
+            table: DataTable::default(),
+            target,
+            repo,
+            force_draw: true,
+            message: String::new(),
+            popup: None,
+            preview: GitPreview::default(),
+        };
+
+        view.refresh();
+        view.table.table_state.select(Some(0));
+        view.table.clamp_selection();
+        view
+    }
+
+    fn selected_tag(&self) -> Option<&TagEntry> {
+        self.tags.get(self.table.table_state.selected()?)
+    }
+
+    fn open_create(&mut self, annotated: bool) {
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                self.message = "Select a commit in the log to tag".to_owned();
+                return;
+            }
+        };
+
+        let kind = match annotated {
+            true => "annotated tag",
+            false => "tag",
+        };
+        let prompt = PromptPopup::new(&format!("New {kind} on {}", &target.to_string()[..7]), "");
+        self.popup = Some(TagPopup::Name(annotated, prompt));
+    }
+
+    fn open_delete(&mut self) {
+        if let Some(tag) = self.selected_tag() {
+            let lines = vec![
+                format!("Delete the tag '{}'?", tag.name),
+                format!("It points at {}", &tag.target.to_string()[..7]),
+            ];
+            self.popup = Some(TagPopup::Delete(
+                tag.name.to_owned(),
+                ConfirmPopup::new("Delete tag", lines),
+            ));
+        }
+    }
+
+    fn create_action(&self, name: &str, message: Option<&str>) -> Option<GitAction> {
+        Some(GitAction::CreateTag {
+            name: name.trim().to_owned(),
+            target: self.target?.to_string(),
+            message: message.map(str::to_owned),
+        })
+    }
+
+    /// Action of the open popup. An annotated tag shows the message as it is written.
+    fn preview_actions(&self) -> Vec<GitAction> {
+        let action = match &self.popup {
+            Some(TagPopup::Name(annotated, prompt)) => {
+                let message = annotated.then_some("");
+                self.create_action(prompt.input.value(), message)
+            }
+            Some(TagPopup::Message(name, editor)) => {
+                self.create_action(name, Some(editor.text().as_str()))
+            }
+            Some(TagPopup::Delete(name, _)) => Some(GitAction::DeleteTag {
+                name: name.to_owned(),
+            }),
+            Some(TagPopup::Command) | None => None,
+        };
+
+        action.into_iter().collect()
+    }
+
+    fn run_actions(&mut self, actions: &[GitAction]) {
+        self.run(action::execute_all(self.repo, actions));
+    }
+
+    fn create(&mut self, name: &str, message: Option<&str>) {
+        if let Some(action) = self.create_action(name, message) {
+            self.run_actions(&[action]);
+        }
+    }
+
+    /// Tagger, date and message of the selected annotated tag.
+    fn detail_lines(tag: &TagEntry) -> Vec<Line<'static>> {
+        let label =
+            |name: &str| Span::styled(format!("{name:<8}"), Style::default().fg(Color::Cyan));
+        let mut lines = vec![Line::from(vec![
+            label("Commit"),
+            Span::styled(
+                tag.target.to_string()[..7].to_owned(),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!(" {}", tag.target_summary)),
+        ])];
+
+        if let Some(tagger) = &tag.tagger {
+            lines.push(Line::from(vec![
+                label("Tagger"),
+                Span::raw(tagger.to_owned()),
+            ]));
+        }
+        if let Some(date) = &tag.date {
+            lines.push(Line::from(vec![label("Date"), Span::raw(date.to_owned())]));
+        }
+        if let Some(message) = &tag.message {
+            lines.push(Line::from(""));
+            lines.extend(message.lines().map(|line| Line::from(line.to_owned())));
+        }
+        lines
+    }
+}
+
+impl ListView for TagView<'_> {
+    type Popup = TagPopup;
+
+    fn refresh(&mut self) {
+        match tag::load_tags(self.repo) {
+            Ok(tags) => self.tags = tags,
+            Err(err) => {
+                self.tags.clear();
+                self.message = err.message().to_owned();
+            }
+        }
+
+        self.table.table_items = self
+            .tags
+            .iter()
+            .map(|tag| {
+                let kind = match tag.annotated {
+                    true => "annotated",
+                    false => "lightweight",
+                };
+                vec![
+                    tag.name.to_owned(),
+                    kind.to_owned(),
+                    format!("{} {}", &tag.target.to_string()[..7], tag.target_summary),
+                    tag.tagger.to_owned().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        self.table.clamp_selection();
+    }
+
+    fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    fn take_popup(&mut self) -> Option<TagPopup> {
+        self.popup.take()
+    }
+
+    fn handle_popup_key(&mut self, popup: TagPopup, key_event: &KeyEvent) {
+        match popup {
+            TagPopup::Name(annotated, mut prompt) => match prompt.handle_key(key_event) {
+                PromptResult::Submitted(name) => {
+                    let name = name.trim().to_owned();
+                    if name.is_empty() {
+                        self.message = "The tag name can't be empty".to_owned();
+                    } else if annotated {
+                        self.popup = Some(TagPopup::Message(name, TextEditor::default()));
+                    } else {
+                        self.create(&name, None);
+                    }
+                }
+                PromptResult::Cancelled => {}
+                PromptResult::Pending => self.popup = Some(TagPopup::Name(annotated, prompt)),
+            },
+            TagPopup::Message(name, mut editor) => {
+                let control = key_event.modifiers == KeyModifiers::CONTROL;
+
+                if control && key_event.code == KeyCode::Char('s') {
+                    match git2::message_prettify(editor.text(), Some(b'#')) {
+                        Ok(message) if !message.trim().is_empty() => {
+                            self.create(&name, Some(&message))
+                        }
+                        Ok(_) => {
+                            self.message = "The tag message can't be empty".to_owned();
+                            self.popup = Some(TagPopup::Message(name, editor));
+                        }
+                        Err(err) => self.message = err.message().to_owned(),
+                    }
+                } else if key_event.code != KeyCode::Esc {
+                    editor.handle_key(key_event);
+                    self.popup = Some(TagPopup::Message(name, editor));
+                }
+            }
+            TagPopup::Delete(name, confirm) => match confirm.handle_key(key_event) {
+                ConfirmResult::Accepted => self.run_actions(&[GitAction::DeleteTag { name }]),
+                ConfirmResult::Declined => {}
+                ConfirmResult::Pending => self.popup = Some(TagPopup::Delete(name, confirm)),
+            },
+            TagPopup::Command => {
+                if let Some(actions) = self.preview.handle_key(key_event) {
+                    self.run_actions(&actions);
+                } else if self.preview.is_editing() {
+                    self.popup = Some(TagPopup::Command);
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key_event: &KeyEvent) -> Option<AppLoopFlag> {
+        match key_event.code {
+            KeyCode::Down => self.arrow_down(),
+            KeyCode::Up => self.arrow_up(),
+            KeyCode::Char('n') => self.open_create(false),
+            KeyCode::Char('a') => self.open_create(true),
+            KeyCode::Char('d') => self.open_delete(),
+            KeyCode::Char('e') => {
+                self.preview.start_editing();
+                self.popup = Some(TagPopup::Command);
+            }
+            KeyCode::Esc => return Some(AppLoopFlag::switch_view(SwitchView::Repo)),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl DisplayView for TagView<'_> {
+    fn display_view<B: tui::backend::Backend>(&mut self, f: &mut tui::Frame<B>) -> AppLoopFlag {
+        if !self.force_draw {
+            if let Some(flag) = self.handle_input() {
+                return flag;
+            }
+        } else {
+            self.force_draw = false;
+        }
+
+        self.preview.set_actions(self.preview_actions());
+
+        let rects = list_frame::areas(f.size(), 60);
+        let detail = list_frame::with_preview(rects[1]);
+
+        let rows = self.table.table_items.iter().map(|item| {
+            let cells = item.iter().map(|c| Cell::from(c.to_owned()));
+            Row::new(cells)
+        });
+
+        let title = match self.target {
+            Some(target) => format!("Tags - new tags go on {}", &target.to_string()[..7]),
+            None => "Tags".to_owned(),
+        };
+        let table = list_frame::table(&["Tag", "Type", "Commit", "Tagger"], rows.collect(), title)
+            .widths(&[
+                Constraint::Percentage(25),
+                Constraint::Length(11),
+                Constraint::Percentage(50),
+                Constraint::Percentage(25),
+            ]);
+        f.render_stateful_widget(table, rects[0], &mut self.table.table_state);
+
+        match (&self.popup, self.selected_tag()) {
+            (Some(TagPopup::Message(name, editor)), _) => editor.render(
+                f,
+                detail[0],
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Message of '{name}' (Ctrl-S: create | Esc: cancel)"
+                    ))
+                    .style(Style::default().fg(Color::Yellow)),
+            ),
+            (_, Some(tag)) => {
+                let paragraph = Paragraph::new(Self::detail_lines(tag))
+                    .block(Block::default().borders(Borders::ALL).title("Tag"))
+                    .wrap(Wrap { trim: false });
+                f.render_widget(paragraph, detail[0]);
+            }
+            (_, None) => {
+                f.render_widget(
+                    Block::default().borders(Borders::ALL).title("Tag"),
+                    detail[0],
+                );
+            }
+        }
+        self.preview.render(f, detail[1], "e");
+
+        let help = list_frame::help_bar(
+            &self.message,
+            "n: new tag | a: new annotated tag | d: delete | e: edit command | Esc: back",
+        );
+        f.render_widget(help, rects[2]);
+
+        match &self.popup {
+            Some(TagPopup::Name(_, prompt)) => prompt.render(f),
+            Some(TagPopup::Delete(_, confirm)) => confirm.render(f),
+            _ => {}
+        }
+
+        AppLoopFlag::continue_()
+    }
+
+    fn arrow_down(&mut self) {
+        self.table.select_next();
+    }
+
+    fn arrow_up(&mut self) {
+        self.table.select_previous();
+    }
+}
@@ -1,11 +1,22 @@
-use git2::{Commit, ErrorClass, Repository};
+use git2::{ErrorClass, Repository};
 
 use crate::{
-    app_flags::AppLoopFlag,
-    config::Config,
-    git::GitRepo,
-    traits::display_view::DisplayView,
-    views::{opened_repo_view::OpenedRepoView, start_view::StartView},
+    app_flags::{AppLoopFlag, SwitchView},
+    command::{
+        command_handler::CommandHandler,
+        completion,
+        context::{CommandContext, CommandResult, ViewState},
+    },
+    config::{history::CommandHistory, Config},
+    git::{self, commit, GitRepo},
+    traits::{display_view::DisplayView, transfer_ui::TransferUi},
+    view_components::{transfer_popup::TransferPopup, ui_services::UiServices},
+    views::{
+        blame_view::BlameView, branch_view::BranchView, commit_view::CommitView,
+        conflict_view::ConflictView, opened_repo_view::OpenedRepoView, rebase_view::RebaseView,
+        remote_view::RemoteView, revert_view::RevertView, start_view::StartView,
+        stash_view::StashView, status_view::StatusView, tag_view::TagView,
+    },
 };
 
 #[cfg(windows)]
@@ -24,12 +35,7 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::{
-    collections::HashMap,
-    io::ErrorKind,
-    path::{Path, PathBuf},
-    str::FromStr,
-};
+use std::{collections::HashMap, io::ErrorKind, path::Path};
 use std::{env, error::Error, io};
 use tui::{
     backend::{Backend, CrosstermBackend},
@@ -89,28 +95,228 @@ fn configure_terminal(stdout: &mut io::Stdout) -> Result<(), Box<dyn Error>> {
 fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
     let mut view = OpenedRepoView::default();
     let args: Vec<String> = env::args().collect();
+    // Shared by every view which takes commands, so macros being recorded carry over.
+    let mut handler = CommandHandler::default();
 
-    if let Some(items) = lib_git_run(terminal, &args) {
-        view.repo_commits.table_items = items.0;
-        view.repo_name = items.1;
+    let repo = if let Some(repo) = lib_git_run(terminal, &args, &mut handler) {
+        view.reload_log(&repo.git2_repository);
+        view.repo_name = repo.seralized_data.name.to_owned();
+        repo
     } else {
         return Err(std::io::Error::new(
             ErrorKind::InvalidInput,
             "Error opening repo select menu",
         ));
-    }
+    };
+
+    let mut history = CommandHistory::load_or_create_config();
+    view.input_field.messages = history.entries(repo.git2_repository.path());
+
+    let mut active_view = ActiveView::Repo;
 
     loop {
         let mut run_flag = AppLoopFlag::default();
 
-        terminal.draw(|f| run_flag = view.display_view(f))?;
+        // Load the detail pane when the selection moved, and redraw right away to show it.
+        if let ActiveView::Repo = active_view {
+            if let Some(id) = view.detail_request() {
+                view.set_detail(id, commit::load_commit_detail(&repo.git2_repository, id));
+                view.force_draw = true;
+            }
+            // The preview pane follows what is typed and the selection it applies to.
+            if let Some((command, view_state)) = view.preview_request() {
+                let ctx = CommandContext {
+                    repo: Some(&repo),
+                    view: view_state.clone(),
+                    ui: &mut view.ui,
+                    confirmed: false,
+                    transfer_ui: None,
+                };
+                let actions = handler.translate(&ctx, &command).and_then(Result::ok);
+                view.set_preview((command, view_state), actions.unwrap_or_default());
+                view.force_draw = true;
+            }
+        }
+
+        match &mut active_view {
+            ActiveView::Repo => terminal.draw(|f| run_flag = view.display_view(f))?,
+            ActiveView::Status(status) => terminal.draw(|f| run_flag = status.display_view(f))?,
+            ActiveView::Commit(commit) => terminal.draw(|f| run_flag = commit.display_view(f))?,
+            ActiveView::Branches(branches) => {
+                terminal.draw(|f| run_flag = branches.display_view(f))?
+            }
+            ActiveView::Rebase(rebase) => terminal.draw(|f| run_flag = rebase.display_view(f))?,
+            ActiveView::Conflicts(conflicts) => {
+                terminal.draw(|f| run_flag = conflicts.display_view(f))?
+            }
+            ActiveView::Revert(revert) => terminal.draw(|f| run_flag = revert.display_view(f))?,
+            ActiveView::Stashes(stashes) => {
+                terminal.draw(|f| run_flag = stashes.display_view(f))?
+            }
+            ActiveView::Tags(tags) => terminal.draw(|f| run_flag = tags.display_view(f))?,
+            ActiveView::Remotes(remotes) => {
+                terminal.draw(|f| run_flag = remotes.display_view(f))?
+            }
+            ActiveView::Blame(blame) => terminal.draw(|f| run_flag = blame.display_view(f))?,
+        };
 
         if run_flag.should_terminate() {
             return Ok(());
         }
+
+        if run_flag.should_complete_input() {
+            let line = view.input_field.before_cursor();
+            view.show_completion(completion::complete(
+                &handler,
+                Some(&repo.git2_repository),
+                &line,
+            ));
+            view.force_draw = true;
+        }
+
+        if let Some(command) = run_flag.take_command() {
+            // History is only kept for convenience, failing to save it is not worth reporting.
+            // Confirmed commands repeat the command which asked, which is in the history.
+            let confirmed = run_flag.is_confirmed();
+            if !confirmed {
+                history.record(repo.git2_repository.path(), &command);
+                let _ = history.save_config();
+            }
+
+            let view_state = view.view_state();
+            let selected = view_state.selected_commit;
+            let (result, view_state) = dispatch_command(
+                &mut handler,
+                Some(&repo),
+                view_state,
+                &mut view.ui,
+                Some(&mut TransferPopup::new(terminal)),
+                &command,
+                confirmed,
+            );
+            view.recording = handler.recording().map(str::to_owned);
+            view.set_message(String::new(), false);
+            view.force_draw = true;
+
+            match result {
+                CommandResult::Continue => {}
+                CommandResult::Quit => return Ok(()),
+                CommandResult::SwitchView(requested) => {
+                    run_flag = AppLoopFlag::switch_view(requested)
+                }
+                CommandResult::Refresh => view.reload_log(&repo.git2_repository),
+                CommandResult::RefreshWithMessage(message) => {
+                    view.reload_log(&repo.git2_repository);
+                    view.set_message(message, false);
+                }
+                CommandResult::Message(message) => view.set_message(message, false),
+                CommandResult::Error(err) => view.set_message(err, true),
+            }
+
+            if let Some(id) = view_state
+                .selected_commit
+                .filter(|id| Some(*id) != selected)
+            {
+                if !view.select_commit(id) {
+                    view.set_message(format!("Commit {id} is not in the log"), true);
+                }
+            }
+        }
+
+        match run_flag.requested_view() {
+            Some(SwitchView::Repo) => {
+                view.reload_log(&repo.git2_repository);
+                if let Some(message) = run_flag.take_message() {
+                    view.set_message(message, false);
+                }
+                view.force_draw = true;
+                active_view = ActiveView::Repo;
+            }
+            Some(SwitchView::Log(id)) => {
+                view.reload_log(&repo.git2_repository);
+                if !view.select_commit(id) {
+                    view.set_message(format!("Commit {id} is not in the log"), true);
+                }
+                view.force_draw = true;
+                active_view = ActiveView::Repo;
+            }
+            Some(SwitchView::Status) => {
+                active_view = ActiveView::Status(StatusView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Commit) => {
+                active_view = ActiveView::Commit(CommitView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Branches) => {
+                active_view = ActiveView::Branches(BranchView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Rebase(onto)) => {
+                active_view = ActiveView::Rebase(RebaseView::new(&repo.git2_repository, onto));
+            }
+            Some(SwitchView::Conflicts) => {
+                active_view = ActiveView::Conflicts(ConflictView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Revert(ids, mainline)) => {
+                active_view =
+                    ActiveView::Revert(RevertView::new(&repo.git2_repository, &ids, mainline));
+            }
+            Some(SwitchView::Stashes) => {
+                active_view = ActiveView::Stashes(StashView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Tags) => {
+                let target = view.view_state().selected_commit;
+                active_view = ActiveView::Tags(TagView::new(&repo.git2_repository, target));
+            }
+            Some(SwitchView::Remotes) => {
+                active_view = ActiveView::Remotes(RemoteView::new(&repo.git2_repository));
+            }
+            Some(SwitchView::Blame(path, at)) => {
+                active_view = ActiveView::Blame(BlameView::new(&repo.git2_repository, &path, at));
+            }
+            None => {}
+        }
     }
 }
 
+/// Runs a command entered in a view, reporting unknown commands as errors. Returns the view
+/// state as the command left it.
+fn dispatch_command<'a>(
+    handler: &mut CommandHandler,
+    repo: Option<&'a GitRepo>,
+    view: ViewState,
+    ui: &'a mut UiServices,
+    transfer_ui: Option<&'a mut dyn TransferUi>,
+    command: &str,
+    confirmed: bool,
+) -> (CommandResult, ViewState) {
+    let mut ctx = CommandContext {
+        repo,
+        view,
+        ui,
+        confirmed,
+        transfer_ui,
+    };
+
+    let result = handler
+        .call_handler(&mut ctx, command)
+        .unwrap_or_else(|| CommandResult::Error(format!("Unknown command: {command}")));
+    (result, ctx.view)
+}
+
+/// Views drawn by [`run_app`] in place of the commit table.
+enum ActiveView<'a> {
+    Repo,
+    Status(StatusView<'a>),
+    Commit(CommitView<'a>),
+    Branches(BranchView<'a>),
+    Rebase(RebaseView<'a>),
+    Conflicts(ConflictView<'a>),
+    Revert(RevertView<'a>),
+    Stashes(StashView<'a>),
+    Tags(TagView<'a>),
+    Remotes(RemoteView<'a>),
+    Blame(BlameView<'a>),
+}
+
 fn open_arg_repo(args: &[String]) -> Result<GitRepo, git2::Error> {
     if let Some(path_str) = args.get(1) {
         let path = Path::new(path_str);
@@ -119,7 +325,7 @@ fn open_arg_repo(args: &[String]) -> Result<GitRepo, git2::Error> {
         let git_repo = GitRepo::from_git2_repo(repo);
 
         return Ok(git_repo);
-    } else if let Some(path_str) = args.get(0) {
+    } else if let Some(path_str) = args.first() {
         let path = Path::new(path_str);
         let repo = Repository::open(path)?;
 
@@ -140,7 +346,8 @@ fn open_arg_repo(args: &[String]) -> Result<GitRepo, git2::Error> {
 fn lib_git_run<B: Backend>(
     terminal: &mut Terminal<B>,
     args: &[String],
-) -> Option<(Vec<Vec<String>>, String)> {
+    handler: &mut CommandHandler,
+) -> Option<GitRepo> {
     let repo: GitRepo;
 
     if let Ok(arg_repo) = open_arg_repo(args) {
@@ -160,58 +367,52 @@ fn lib_git_run<B: Backend>(
                     selected_repo = start_view.repo_selected;
                     break;
                 }
+
+                if let Some(command) = run_flag.take_command() {
+                    let (result, _) = dispatch_command(
+                        handler,
+                        None,
+                        ViewState::default(),
+                        &mut start_view.ui,
+                        None,
+                        &command,
+                        false,
+                    );
+                    start_view.message.clear();
+                    start_view.force_draw = true;
+
+                    match result {
+                        CommandResult::Quit => return None,
+                        CommandResult::Refresh => start_view.load_table(),
+                        CommandResult::RefreshWithMessage(message) => {
+                            start_view.load_table();
+                            start_view.message = message;
+                        }
+                        CommandResult::Message(message) | CommandResult::Error(message) => {
+                            start_view.message = message
+                        }
+                        // There is no repository to show other views for yet.
+                        CommandResult::SwitchView(_) | CommandResult::Continue => {}
+                    }
+                }
             }
         }
 
         repo = GitRepo::from_serialized_repo(selected_repo?).ok()?;
     }
 
-    let head = repo.git2_repository.head().ok()?;
-    //let name = head.name().unwrap();
-
-    let commit = head.peel_to_commit().ok()?;
-    let mut commit_history = Vec::new();
-    let mut parent = commit.parents().next();
-
-    let commit_item = extract_commit_data(&commit)?;
-
-    commit_history.push(commit_item);
-
-    while let Some(p_commit) = parent {
-        let commit_item = extract_commit_data(&p_commit)?;
-
-        commit_history.push(commit_item);
-
-        parent = p_commit.parents().next();
-    }
-
-    let mut url = String::new();
-
-    if let Ok(remote) = repo.git2_repository.find_remote("origin") {
-        url = remote.url().unwrap_or_default().to_owned();
-    }
-
-    let repo_path = repo
-        .git2_repository
-        .path()
-        .as_os_str()
-        .to_str()
-        .unwrap_or("")
-        .to_owned()
-        .split(".git/")
-        .next()
-        .unwrap_or_default()
-        .to_owned();
-
-    let folders: Vec<&str> = repo_path.split('/').collect();
+    let repo_path = git::saved_path(&repo.git2_repository);
+    let path_str = repo_path.to_string_lossy().into_owned();
+    let folders: Vec<&str> = path_str.split('/').collect();
 
     let recent_repo = crate::config::repo::SerializedRepository {
-        path: PathBuf::from_str(&repo_path).unwrap_or_default(),
         name: folders
             .get(folders.len() - 2)
             .unwrap_or(&"UNNAMED")
             .to_string(),
-        repo_url: url,
+        repo_url: git::get_repo_url(&repo.git2_repository).unwrap_or_default(),
+        preferred_remote: None,
+        path: repo_path,
     };
 
     save_recent_repo(recent_repo);
@@ -224,30 +425,10 @@ fn lib_git_run<B: Backend>(
        }
     */
 
-    /*let mut branch_name = input_field.input_prompt(terminal, "Input name of branch").unwrap().to_owned();
-    let len = branch_name.trim_end_matches(&['\r', '\n'][..]).len();
-    branch_name.truncate(len);
-    drop(len);
-
-    let mut remote = repo.find_remote("origin").unwrap();
-    remote.connect(git2::Direction::Fetch).unwrap();
-
-    println!("{}", remote.default_branch().unwrap().as_str().unwrap());
-    println!("{}", remote.name().unwrap());*/
-
-    Some((commit_history, repo.seralized_data.name))
-}
-
-fn extract_commit_data(commit: &Commit) -> Option<Vec<String>> {
-    let commit_item = vec![
-        commit.message()?.to_owned(),
-        commit.author().name()?.to_owned(),
-        commit.id().to_string(),
-    ];
-    Some(commit_item)
+    Some(repo)
 }
 
-fn save_recent_repo(repo: crate::config::repo::SerializedRepository) -> Option<()> {
+fn save_recent_repo(mut repo: crate::config::repo::SerializedRepository) -> Option<()> {
     use crate::config::repo::SavedRepositories;
 
     let mut conf = SavedRepositories::load_or_create_config();
@@ -257,6 +438,10 @@ fn save_recent_repo(repo: crate::config::repo::SerializedRepository) -> Option<(
         hash.insert(recent_repo.path.to_owned(), recent_repo);
     }
 
+    // The preferred remote is only set through `remote prefer`, opening the repository keeps it.
+    if let Some(saved) = hash.get(&repo.path) {
+        repo.preferred_remote = saved.preferred_remote.to_owned();
+    }
     hash.insert(repo.path.to_owned(), repo);
 
     conf.recent_repositories = hash.into_values().collect();